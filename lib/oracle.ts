@@ -1,11 +1,12 @@
 import BN from 'bn.js'
 import { keccak_256 } from '@noble/hashes/sha3.js'
 
-import { PublicKey } from '@solana/web3.js'
-import { createKeyPairFromBytes, fixCodecSize, getBytesCodec, getU8Codec, getStructCodec, getU64Codec, signBytes, getArrayCodec, getBooleanCodec } from '@solana/kit'
+import { Keypair, PublicKey } from '@solana/web3.js'
+import { createKeyPairFromBytes, fixCodecSize, getBytesCodec, getU8Codec, getStructCodec, getU64Codec, signBytes, getArrayCodec, getBooleanCodec, getU16Codec, getU32Codec, addCodecSizePrefix, getNullableCodec, getI64Codec, getI32Codec } from '@solana/kit'
 
 import { SPLToken } from './spl.ts'
 import { pda, randomString } from './utils.ts'
+import IDL from '../target/idl/ticker_token.json' with { type: 'json' }
 
 import * as Hasher from 'multiformats/hashes/sha2'
 import * as Block from 'multiformats/block'
@@ -13,11 +14,29 @@ import * as dagCbor from '@ipld/dag-cbor'
 
 enum OrderType { Market, Limit }
 export enum OrderSide { Buy, Sell }
+export enum OrderMode { Quantity, Notional, All }
+export enum TriggerType { Stop, StopLimit, TakeProfit }
+export enum TimeInForce { Gtc, Day }
+export enum MessageKind {
+	OrderPayload = 1, ExecutionReport, PriceAttestation, PriceFeedUpdate,
+	PlanPayload, SwitchPayload, SwitchReport, BasketPayload, BasketReport
+}
+
+const MESSAGE_DOMAIN = new TextEncoder().encode('ticker-token')
+const programId = new PublicKey(IDL.address)
+
+const modeName = (mode : OrderMode) => OrderMode[mode].replace(/^./, c => c.toLowerCase())
+const triggerName = (kind : TriggerType) => TriggerType[kind].replace(/^./, c => c.toLowerCase())
+const timeInForceName = (tif : TimeInForce) => TimeInForce[tif].toLowerCase()
+
+export type Trigger = { kind : TriggerType, price : number }
+export type TwapSchedule = { startAt : number, endAt : number, slices : number }
 
 export type OraclePayload = {
 	id : BN
 	maker : PublicKey // the maker of the order
 	market : boolean // is market order
+	mode : object // { quantity: {} } | { notional: {} } | { all: {} }
 
 	//orderType : OrderType
 	//orderSide : OrderSide
@@ -29,6 +48,11 @@ export type OraclePayload = {
 	price : BN
 	fee : BN
 
+	trigger : { kind : object, price : BN } | null // { stop: {} } | { stopLimit: {} } | { takeProfit: {} }
+	twap : { startAt : BN, endAt : BN, slices : number } | null
+	allowPartial : boolean // SELL: broker may complete the order with unsold shares
+	timeInForce : object // { gtc: {} } | { day: {} }
+
 	expiresAt : BN
 }
 
@@ -36,14 +60,238 @@ const payloadCodec = getStructCodec([
 	['id', getU64Codec()],
 	['maker', fixCodecSize(getBytesCodec(), 32)],
 	['market', getBooleanCodec()],
+	['mode', getU8Codec()],
 	['tickerMint', fixCodecSize(getBytesCodec(), 32)],
 	['amount', getU64Codec()],
 	['paymentMint', fixCodecSize(getBytesCodec(), 32)],
 	['price', getU64Codec()],
 	['fee', getU64Codec()],
+	['trigger', getNullableCodec(getStructCodec([
+		['kind', getU8Codec()],
+		['price', getU64Codec()]
+	]))],
+	['twap', getNullableCodec(getStructCodec([
+		['startAt', getU64Codec()],
+		['endAt', getU64Codec()],
+		['slices', getU16Codec()]
+	]))],
+	['allowPartial', getBooleanCodec()],
+	['timeInForce', getU8Codec()],
+	['expiresAt', getU64Codec()]
+])
+
+export type PriceAttestation = {
+	tickerMint : PublicKey
+	paymentMint : PublicKey
+
+	price : BN
+	timestamp : BN
+}
+
+const attestationCodec = getStructCodec([
+	['tickerMint', fixCodecSize(getBytesCodec(), 32)],
+	['paymentMint', fixCodecSize(getBytesCodec(), 32)],
+	['price', getU64Codec()],
+	['timestamp', getU64Codec()]
+])
+
+export type PriceFeedUpdate = {
+	tickerMint : PublicKey
+
+	price : BN
+	conf : BN
+	expo : number
+	publishTime : BN
+	marketOpen : boolean
+}
+
+const feedUpdateCodec = getStructCodec([
+	['tickerMint', fixCodecSize(getBytesCodec(), 32)],
+	['price', getI64Codec()],
+	['conf', getU64Codec()],
+	['expo', getI32Codec()],
+	['publishTime', getI64Codec()],
+	['marketOpen', getBooleanCodec()]
+])
+
+export type ExecutionReport = {
+	orderId : BN
+	maker : PublicKey
+
+	filled : BN
+	spent : BN
+	price : BN
+	complete : boolean
+
+	proofCid : Buffer
+	brokerExecId : number[]
+
+	timestamp : BN
+}
+
+const reportCodec = getStructCodec([
+	['orderId', getU64Codec()],
+	['maker', fixCodecSize(getBytesCodec(), 32)],
+	['filled', getU64Codec()],
+	['spent', getU64Codec()],
+	['price', getU64Codec()],
+	['complete', getBooleanCodec()],
+	['proofCid', addCodecSizePrefix(getBytesCodec(), getU32Codec())],
+	['brokerExecId', fixCodecSize(getBytesCodec(), 32)],
+	['timestamp', getU64Codec()]
+])
+
+export type PlanPayload = {
+	id : BN
+	maker : PublicKey
+
+	tickerMint : PublicKey
+	paymentMint : PublicKey
+
+	amount : BN // notional per interval
+	fee : BN
+
+	interval : BN
+	startAt : BN
+	endAt : BN
+
+	expiresAt : BN
+}
+
+const planCodec = getStructCodec([
+	['id', getU64Codec()],
+	['maker', fixCodecSize(getBytesCodec(), 32)],
+	['tickerMint', fixCodecSize(getBytesCodec(), 32)],
+	['paymentMint', fixCodecSize(getBytesCodec(), 32)],
+	['amount', getU64Codec()],
+	['fee', getU64Codec()],
+	['interval', getU64Codec()],
+	['startAt', getU64Codec()],
+	['endAt', getU64Codec()],
 	['expiresAt', getU64Codec()]
 ])
 
+export type SwitchPayload = {
+	id : BN
+	maker : PublicKey
+
+	fromMint : PublicKey
+	toMint : PublicKey
+	amount : BN
+
+	paymentMint : PublicKey
+	topUp : BN
+	fee : BN
+
+	minSellPrice : BN
+	maxBuyPrice : BN
+
+	expiresAt : BN
+}
+
+const switchCodec = getStructCodec([
+	['id', getU64Codec()],
+	['maker', fixCodecSize(getBytesCodec(), 32)],
+	['fromMint', fixCodecSize(getBytesCodec(), 32)],
+	['toMint', fixCodecSize(getBytesCodec(), 32)],
+	['amount', getU64Codec()],
+	['paymentMint', fixCodecSize(getBytesCodec(), 32)],
+	['topUp', getU64Codec()],
+	['fee', getU64Codec()],
+	['minSellPrice', getU64Codec()],
+	['maxBuyPrice', getU64Codec()],
+	['expiresAt', getU64Codec()]
+])
+
+export type SwitchReport = {
+	orderId : BN
+	maker : PublicKey
+
+	sold : BN
+	proceeds : BN
+	sellPrice : BN
+
+	bought : BN
+	cost : BN
+	buyPrice : BN
+
+	proofCid : Buffer
+	brokerExecId : number[]
+
+	timestamp : BN
+}
+
+const switchReportCodec = getStructCodec([
+	['orderId', getU64Codec()],
+	['maker', fixCodecSize(getBytesCodec(), 32)],
+	['sold', getU64Codec()],
+	['proceeds', getU64Codec()],
+	['sellPrice', getU64Codec()],
+	['bought', getU64Codec()],
+	['cost', getU64Codec()],
+	['buyPrice', getU64Codec()],
+	['proofCid', addCodecSizePrefix(getBytesCodec(), getU32Codec())],
+	['brokerExecId', fixCodecSize(getBytesCodec(), 32)],
+	['timestamp', getU64Codec()]
+])
+
+export type BasketPayload = {
+	id : BN
+	maker : PublicKey
+	market : boolean
+
+	paymentMint : PublicKey
+	legs : { tickerMint : PublicKey, amount : BN, price : BN }[]
+	fee : BN
+
+	allOrNone : boolean
+
+	expiresAt : BN
+}
+
+const basketCodec = getStructCodec([
+	['id', getU64Codec()],
+	['maker', fixCodecSize(getBytesCodec(), 32)],
+	['market', getBooleanCodec()],
+	['paymentMint', fixCodecSize(getBytesCodec(), 32)],
+	['legs', getArrayCodec(getStructCodec([
+		['tickerMint', fixCodecSize(getBytesCodec(), 32)],
+		['amount', getU64Codec()],
+		['price', getU64Codec()]
+	]))],
+	['fee', getU64Codec()],
+	['allOrNone', getBooleanCodec()],
+	['expiresAt', getU64Codec()]
+])
+
+export type BasketReport = {
+	orderId : BN
+	maker : PublicKey
+
+	fills : { leg : number, filled : BN, spent : BN, price : BN }[]
+	complete : boolean
+
+	proofCid : Buffer
+	brokerExecId : number[]
+
+	timestamp : BN
+}
+
+const basketReportCodec = getStructCodec([
+	['orderId', getU64Codec()],
+	['maker', fixCodecSize(getBytesCodec(), 32)],
+	['fills', getArrayCodec(getStructCodec([
+		['leg', getU8Codec()],
+		['filled', getU64Codec()],
+		['spent', getU64Codec()],
+		['price', getU64Codec()]
+	]))],
+	['complete', getBooleanCodec()],
+	['proofCid', addCodecSizePrefix(getBytesCodec(), getU32Codec())],
+	['brokerExecId', fixCodecSize(getBytesCodec(), 32)],
+	['timestamp', getU64Codec()]
+])
+
 const TTL = 60 // 60 seconds
 const fee = 10 // 10% fee
 
@@ -53,11 +301,19 @@ export class Oracle {
 	#secretKey
 	get secretKey () { return this.#secretKey }
 
+	get publicKey () { return Keypair.fromSecretKey(this.#secretKey).publicKey }
+	get paymentMint () : PublicKey { return paymentToken.mint }
+
 	constructor (secretKey) {
 		this.#secretKey = secretKey
 	}
 
-	async payload (programId, maker: PublicKey, symbol: string, amount: number, price?: number) {
+	async payload (
+		programId, maker: PublicKey, symbol: string, amount: number, price?: number,
+		{ mode = OrderMode.Quantity, trigger, twap, allowPartial = false, timeInForce = TimeInForce.Gtc, ttl = TTL } : {
+			mode? : OrderMode, trigger? : Trigger, twap? : TwapSchedule, allowPartial? : boolean, timeInForce? : TimeInForce, ttl? : number
+		} = {}
+	) {
 		const market = !price // if price is not set, it's a market order
 		//const id = crypto.randomUUID()
 		const now = Math.floor(Date.now() / 1000)
@@ -68,13 +324,15 @@ export class Oracle {
 
 		const bnPrice = new BN(price)
 		const bnAmount = new BN(amount)
-		const bnFee = bnPrice.mul(bnAmount).muln(fee).divn(100)
+		const bnNotional = mode === OrderMode.Notional ? bnAmount : bnPrice.mul(bnAmount)
+		const bnFee = bnNotional.muln(fee).divn(100)
 
 		const payload = {
 			id: new BN(now),
 			//orderType,
 			maker,
 			market, // if price is not set, it's a market order
+			mode: { [modeName(mode)]: {} },
 
 			tickerMint,
 			amount: new BN(amount) as BN,
@@ -82,34 +340,298 @@ export class Oracle {
 			paymentMint: paymentToken.mint,
 			price: bnPrice as BN,
 			fee: bnFee as BN,
+
+			trigger: trigger ? { kind: { [triggerName(trigger.kind)]: {} }, price: new BN(trigger.price) } : null,
+			twap: twap ? { startAt: new BN(twap.startAt), endAt: new BN(twap.endAt), slices: twap.slices } : null,
+			allowPartial,
+			timeInForce: { [timeInForceName(timeInForce)]: {} },
 			
-			expiresAt: new BN(now + TTL)
+			expiresAt: new BN(now + ttl)
 		}
 
 		const encoded = payloadCodec.encode({
 			...payload,
+			mode,
+			trigger: trigger ? { kind: trigger.kind, price: trigger.price } : null,
+			twap: twap ?? null,
+			timeInForce,
 			maker: payload.maker.toBytes(),
 			tickerMint: payload.tickerMint.toBytes(),
 			paymentMint: payload.paymentMint.toBytes()
 		})
 
-		const { signature, message } = await this.sign(encoded as Uint8Array)
-		return { payload, message, signature, _paymentToken: paymentToken }
+		const { signature, message, publicKey } = await this.sign(MessageKind.OrderPayload, encoded as Uint8Array)
+		return { payload, encoded, message, signature, publicKey, _paymentToken: paymentToken }
+	}
+
+	async plan (
+		programId, maker : PublicKey, symbol : string, amount : number,
+		{ interval = 7 * 24 * 3600, periods = 4, startAt } : { interval? : number, periods? : number, startAt? : number } = {}
+	) {
+		const now = Math.floor(Date.now() / 1000)
+		startAt ??= now
+
+		const [tickerMint] = pda(['mint', symbol], programId)
+
+		const payload : PlanPayload = {
+			id: new BN(now),
+			maker,
+
+			tickerMint,
+			paymentMint: paymentToken.mint,
+
+			amount: new BN(amount),
+			fee: new BN(amount).muln(fee).divn(100),
+
+			interval: new BN(interval),
+			startAt: new BN(startAt),
+			endAt: new BN(startAt + interval * (periods - 1)),
+
+			expiresAt: new BN(now + TTL)
+		}
+
+		const encoded = planCodec.encode({
+			...payload,
+			maker: maker.toBytes(),
+			tickerMint: tickerMint.toBytes(),
+			paymentMint: payload.paymentMint.toBytes()
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.PlanPayload, encoded as Uint8Array)
+		return { payload, encoded, message, signature, publicKey, _paymentToken: paymentToken }
+	}
+
+	async report (order, { filled, spent, price, complete = true, proofCid, brokerExecId } : {
+		filled? : bigint, spent : bigint, price? : bigint, complete? : boolean, proofCid : Uint8Array, brokerExecId? : Uint8Array
+	}) {
+		const report : ExecutionReport = {
+			orderId: new BN(order.id),
+			maker: order.maker,
+
+			filled: new BN((filled ?? BigInt(order.amount)).toString()),
+			spent: new BN(spent.toString()),
+			price: new BN((price ?? BigInt(order.price)).toString()),
+			complete,
+
+			proofCid: Buffer.from(proofCid),
+			brokerExecId: Array.from(brokerExecId ?? crypto.getRandomValues(new Uint8Array(32))),
+
+			timestamp: new BN(Math.floor(Date.now() / 1000) - 1)
+		}
+
+		const encoded = reportCodec.encode({
+			...report,
+			maker: report.maker.toBytes(),
+			brokerExecId: new Uint8Array(report.brokerExecId)
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.ExecutionReport, encoded as Uint8Array)
+		return { report, message, signature, publicKey }
+	}
+
+	async feed (tickerMint : PublicKey, price : number, { conf = 0, expo = 0, age = 1, marketOpen = true } : {
+		conf? : number, expo? : number, age? : number, marketOpen? : boolean
+	} = {}) {
+		const update : PriceFeedUpdate = {
+			tickerMint,
+
+			price: new BN(price),
+			conf: new BN(conf),
+			expo,
+			publishTime: new BN(Math.floor(Date.now() / 1000) - age),
+			marketOpen
+		}
+
+		const encoded = feedUpdateCodec.encode({
+			...update,
+			price: BigInt(price),
+			conf: BigInt(conf),
+			publishTime: BigInt(update.publishTime.toString()),
+			tickerMint: tickerMint.toBytes()
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.PriceFeedUpdate, encoded as Uint8Array)
+		return { update, message, signature, publicKey }
+	}
+
+	async attest (tickerMint : PublicKey, price : number, { paymentMint = paymentToken.mint, age = 1 } : {
+		paymentMint? : PublicKey, age? : number
+	} = {}) {
+		const attestation : PriceAttestation = {
+			tickerMint,
+			paymentMint,
+
+			price: new BN(price),
+			timestamp: new BN(Math.floor(Date.now() / 1000) - age)
+		}
+
+		const encoded = attestationCodec.encode({
+			...attestation,
+			tickerMint: tickerMint.toBytes(),
+			paymentMint: paymentMint.toBytes()
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.PriceAttestation, encoded as Uint8Array)
+		return { attestation, message, signature, publicKey }
+	}
+
+	async switch (
+		programId, maker : PublicKey, fromSymbol : string, toSymbol : string, amount : number,
+		{ topUp = 0, fee = 0, minSellPrice = 0, maxBuyPrice = Number.MAX_SAFE_INTEGER } : {
+			topUp? : number, fee? : number, minSellPrice? : number, maxBuyPrice? : number
+		} = {}
+	) {
+		const now = Math.floor(Date.now() / 1000)
+
+		const [fromMint] = pda(['mint', fromSymbol], programId)
+		const [toMint] = pda(['mint', toSymbol], programId)
+
+		const payload : SwitchPayload = {
+			id: new BN(now),
+			maker,
+
+			fromMint,
+			toMint,
+			amount: new BN(amount),
+
+			paymentMint: paymentToken.mint,
+			topUp: new BN(topUp),
+			fee: new BN(fee),
+
+			minSellPrice: new BN(minSellPrice),
+			maxBuyPrice: new BN(maxBuyPrice),
+
+			expiresAt: new BN(now + TTL)
+		}
+
+		const encoded = switchCodec.encode({
+			...payload,
+			maker: maker.toBytes(),
+			fromMint: fromMint.toBytes(),
+			toMint: toMint.toBytes(),
+			paymentMint: payload.paymentMint.toBytes()
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.SwitchPayload, encoded as Uint8Array)
+		return { payload, encoded, message, signature, publicKey, _paymentToken: paymentToken }
+	}
+
+	async switchReport (order, { sold, proceeds, bought, cost, proofCid, brokerExecId } : {
+		sold? : bigint, proceeds : bigint, bought : bigint, cost : bigint, proofCid : Uint8Array, brokerExecId? : Uint8Array
+	}) {
+		sold ??= BigInt(order.amount)
+
+		const report : SwitchReport = {
+			orderId: new BN(order.id),
+			maker: order.maker,
+
+			sold: new BN(sold.toString()),
+			proceeds: new BN(proceeds.toString()),
+			sellPrice: new BN((proceeds / sold).toString()),
+
+			bought: new BN(bought.toString()),
+			cost: new BN(cost.toString()),
+			buyPrice: new BN(((cost + bought - 1n) / bought).toString()),
+
+			proofCid: Buffer.from(proofCid),
+			brokerExecId: Array.from(brokerExecId ?? crypto.getRandomValues(new Uint8Array(32))),
+
+			timestamp: new BN(Math.floor(Date.now() / 1000) - 1)
+		}
+
+		const encoded = switchReportCodec.encode({
+			...report,
+			maker: report.maker.toBytes(),
+			brokerExecId: new Uint8Array(report.brokerExecId)
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.SwitchReport, encoded as Uint8Array)
+		return { report, message, signature, publicKey }
+	}
+
+	async basket (
+		programId, maker : PublicKey, legs : { symbol : string, amount : number, price : number }[],
+		{ allOrNone = false, market = false } : { allOrNone? : boolean, market? : boolean } = {}
+	) {
+		const now = Math.floor(Date.now() / 1000)
+		const notional = legs.reduce((sum, { amount, price }) => sum + amount * price, 0)
+
+		const payload : BasketPayload = {
+			id: new BN(now),
+			maker,
+			market,
+
+			paymentMint: paymentToken.mint,
+			legs: legs.map(({ symbol, amount, price }) => ({
+				tickerMint: pda(['mint', symbol], programId)[0],
+				amount: new BN(amount),
+				price: new BN(price)
+			})),
+			fee: new BN(Math.floor(notional * fee / 100)),
+
+			allOrNone,
+
+			expiresAt: new BN(now + TTL)
+		}
+
+		const encoded = basketCodec.encode({
+			...payload,
+			maker: maker.toBytes(),
+			paymentMint: payload.paymentMint.toBytes(),
+			legs: payload.legs.map(leg => ({ ...leg, tickerMint: leg.tickerMint.toBytes() }))
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.BasketPayload, encoded as Uint8Array)
+		return { payload, encoded, message, signature, publicKey, _paymentToken: paymentToken }
+	}
+
+	async basketReport (order, { fills, complete = true, proofCid, brokerExecId } : {
+		fills : { leg : number, filled : bigint, spent : bigint }[], complete? : boolean, proofCid : Uint8Array, brokerExecId? : Uint8Array
+	}) {
+		const report : BasketReport = {
+			orderId: new BN(order.id),
+			maker: order.maker,
+
+			fills: fills.map(({ leg, filled, spent }) => ({
+				leg,
+				filled: new BN(filled.toString()),
+				spent: new BN(spent.toString()),
+				price: new BN((spent / filled).toString())
+			})),
+			complete,
+
+			proofCid: Buffer.from(proofCid),
+			brokerExecId: Array.from(brokerExecId ?? crypto.getRandomValues(new Uint8Array(32))),
+
+			timestamp: new BN(Math.floor(Date.now() / 1000) - 1)
+		}
+
+		const encoded = basketReportCodec.encode({
+			...report,
+			maker: report.maker.toBytes(),
+			brokerExecId: new Uint8Array(report.brokerExecId)
+		})
+
+		const { signature, message, publicKey } = await this.sign(MessageKind.BasketReport, encoded as Uint8Array)
+		return { report, message, signature, publicKey }
 	}
 
 	async cid (id : number) {
 		const block = await Block.encode({ value: id, codec: dagCbor, hasher: Hasher.sha256 })
-		return block.cid.multihash.digest
+		return block.cid.bytes
 	}
 	
 
-	async sign (encoded : Uint8Array) {
+	// сообщение = домен || тип || program id || borsh, см. SignedMessage в programs/ticker-token/src/utils.rs
+	async sign (kind : MessageKind, encoded : Uint8Array) {
 		const { secretKey } = this
 		const { privateKey } = await createKeyPairFromBytes(secretKey)
 
-		const message = keccak_256(new Uint8Array(encoded))
+		const message = keccak_256(new Uint8Array([
+			...MESSAGE_DOMAIN, kind, ...programId.toBytes(), ...encoded
+		]))
 		const signature = await signBytes(privateKey, message)
 
-		return { signature: Array.from(signature), message }
+		return { signature: Array.from(signature), message, publicKey: this.publicKey }
 	}
 }
\ No newline at end of file
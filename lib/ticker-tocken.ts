@@ -1,13 +1,31 @@
 import * as anchor from '@coral-xyz/anchor'
 import BN from 'bn.js'
+import { keccak_256 } from '@noble/hashes/sha3.js'
 
-import { Keypair, PublicKey, Ed25519Program } from '@solana/web3.js'
+import { Keypair, PublicKey, Ed25519Program, Secp256k1Program } from '@solana/web3.js'
+import { createAssociatedTokenAccountIdempotentInstruction } from '@solana/spl-token'
 
 import { TickerToken } from '~/target/types/ticker_token'
 import IDL from '../target/idl/ticker_token.json' with { type: 'json' }
 
 import { pda, ata } from './utils.ts'
-import type { OraclePayload } from './oracle.ts'
+
+// должно совпадать с TICKERS_PER_PAGE в programs/ticker-token/src/ticker.rs
+const TICKERS_PER_PAGE = 64
+import type { OraclePayload, ExecutionReport, PriceAttestation, PriceFeedUpdate, PlanPayload, SwitchPayload, SwitchReport, BasketPayload, BasketReport } from './oracle.ts'
+
+export type OracleSignature = {
+	message : Uint8Array
+	signature : number[]
+	publicKey? : PublicKey // defaults to registry authority
+} | {
+	message : Uint8Array
+	signature : number[] // 64 bytes (r, s)
+	recoveryId : number
+	ethAddress : string | Buffer | Uint8Array
+}
+
+export type OracleKey = { ed25519 : [PublicKey] } | { secp256k1 : [number[]] }
 
 export const METAPLEX_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s")
 
@@ -63,27 +81,102 @@ export class Ticker {
 		return BigInt(balance.value.amount)
 	}
 
-	async order (maker : PublicKey, id : number) {
+	executionProof (brokerExecId : number[]) {
+		return this.#program.account.executionProof.fetch(
+			this.pda(['proof', Buffer.from(brokerExecId)])
+		)
+	}
+
+	async order (maker : PublicKey, id : number | BN) {
 		const orderPda = this.pda(
 			['order', maker.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)]
 		)
 		return this.#program.account.order.fetch(orderPda)
 	}
 
+	async plan (maker : PublicKey, id : number) {
+		return this.#program.account.recurringPlan.fetch(
+			this.pda(['plan', maker.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)])
+		)
+	}
+
+	async switchOrder (maker : PublicKey, id : number) {
+		return this.#program.account.switchOrder.fetch(
+			this.pda(['switch', maker.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)])
+		)
+	}
+
+	async basketOrder (maker : PublicKey, id : number) {
+		return this.#program.account.basketOrder.fetch(
+			this.pda(['basket', maker.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)])
+		)
+	}
+
+	feedPda (tickerMint : PublicKey) {
+		return this.pda(['feed', tickerMint.toBuffer()])
+	}
+
+	async calendar () {
+		return this.#program.account.marketCalendar.fetch(this.pda(['calendar']))
+	}
+
+	marketPda (tickerMint : PublicKey, paymentMint : PublicKey) {
+		return this.pda(['market', tickerMint.toBuffer(), paymentMint.toBuffer()])
+	}
+
+	async market (tickerMint : PublicKey, paymentMint : PublicKey) {
+		return this.#program.account.market.fetch(this.marketPda(tickerMint, paymentMint))
+	}
+
+	async tickerConfig (tickerMint : PublicKey) {
+		return this.#program.account.tickerConfig.fetch(this.pda(['config', tickerMint.toBuffer()]))
+	}
+
+	async priceFeed (tickerMint : PublicKey) {
+		return this.#program.account.priceFeed.fetch(this.feedPda(tickerMint))
+	}
+
+	bookPda (tickerMint : PublicKey, paymentMint : PublicKey) {
+		return this.pda(['book', tickerMint.toBuffer(), paymentMint.toBuffer()])
+	}
+
+	async book (tickerMint : PublicKey, paymentMint : PublicKey) {
+		return this.#program.account.orderBook.fetch(this.bookPda(tickerMint, paymentMint))
+	}
+
+	ammPda (tickerMint : PublicKey, paymentMint : PublicKey) {
+		return this.pda(['amm', tickerMint.toBuffer(), paymentMint.toBuffer()])
+	}
+
+	async amm (tickerMint : PublicKey, paymentMint : PublicKey) {
+		return this.#program.account.liquidityPool.fetch(this.ammPda(tickerMint, paymentMint))
+	}
+
 	async init () {
 		const { signer } = this
 
-		if (await this.#program.account.registry.fetchNullable(this.#registryPDA))
-			return this
+		if (!await this.#program.account.registry.fetchNullable(this.#registryPDA))
+			await this.#program.methods
+				.init()
+				.accounts({ payer: signer.publicKey })
+				.rpc()
 
-		await this.#program.methods
-			.init()
-			.accounts({ payer: signer.publicKey })
-			.rpc()
+		if (!await this.#program.account.marketCalendar.fetchNullable(this.pda(['calendar'])))
+			await this.initCalendar()
 
 		return this
 	}
 
+	initCalendar () {
+		const { signer } = this
+
+		return this.#program.methods
+			.initCalendar()
+			.accounts({ authority: signer.publicKey })
+			.signers([signer])
+			.rpc()
+	}
+
 	transferAuthority (authority : PublicKey) {
 		const { signer } = this
 
@@ -94,28 +187,112 @@ export class Ticker {
 			.rpc()
 	}
 
-	createTicker (symbol : string, decimals = 0) {
+	setOracles (oracles : OracleKey[], threshold : number) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setOracles(oracles, threshold)
+			.accounts({ authority: signer.publicKey })
+			.signers([signer])
+			.rpc()
+	}
+
+	setBroker (broker : PublicKey) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setBroker(broker)
+			.accounts({ authority: signer.publicKey })
+			.signers([signer])
+			.rpc()
+	}
+
+	async oracleInstructions (signatures : OracleSignature | OracleSignature[]) {
+		const { authority } = await this.registry
+
+		return [signatures].flat().map(sig => 'ethAddress' in sig
+			? Secp256k1Program.createInstructionWithEthAddress({
+				ethAddress: sig.ethAddress,
+				message: sig.message,
+				signature: Buffer.from(sig.signature),
+				recoveryId: sig.recoveryId
+			})
+			: Ed25519Program.createInstructionWithPublicKey({
+				publicKey: (sig.publicKey || authority).toBytes(),
+				message: sig.message,
+				signature: Buffer.from(sig.signature)
+			})
+		)
+	}
+
+	async createTicker (symbol : string, decimals = 0) {
 		const { signer } = this
 		const mint = this.pda(['mint', symbol])
+		const { tickerCount } = await this.registry
 
 		return this.#program.methods
 			.createTicker(symbol, decimals)
 			// @ts-ignore
-			.accounts({ mint })
+			.accounts({ mint, index: this.tickerIndexPda(tickerCount.divn(TICKERS_PER_PAGE).toNumber()) })
+			.signers([signer]).rpc()
+	}
+
+	tickerIndexPda (page : number) {
+		return this.pda(['index', new BN(page).toArrayLike(Buffer, 'le', 4)])
+	}
+
+	async tickerIndex (page : number) {
+		return this.#program.account.tickerIndex.fetchNullable(this.tickerIndexPda(page))
+	}
+
+	recordPda (mint : PublicKey) {
+		return this.pda(['ticker', mint.toBuffer()])
+	}
+
+	async tickerRecord (mint : PublicKey) {
+		return this.#program.account.tickerRecord.fetchNullable(this.recordPda(mint))
+	}
+
+	// все тикеры по порядку создания, постранично из индекса
+	async listTickers () {
+		const { tickerCount } = await this.registry
+		const pages = Math.ceil(tickerCount.toNumber() / TICKERS_PER_PAGE)
+
+		const indexes = await Promise.all(Array.from({ length: pages }, (_, page) => this.tickerIndex(page)))
+		return indexes.flatMap(index => index?.tickers ?? [])
+	}
+
+	// реестр, созданный до индекса тикеров: расширяется и начинает индекс с нуля
+	async migrateRegistry () {
+		const { signer } = this
+
+		return this.#program.methods
+			.migrateRegistry()
+			.accounts({ authority: signer.publicKey })
+			.signers([signer]).rpc()
+	}
+
+	// добавляет в индекс тикер, созданный до migrateRegistry
+	async indexTicker (symbol : string) {
+		const { signer } = this
+		const { tickerCount } = await this.registry
+
+		return this.#program.methods
+			.indexTicker(symbol)
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				mint: this.pda(['mint', symbol]),
+				index: this.tickerIndexPda(tickerCount.divn(TICKERS_PER_PAGE).toNumber())
+			})
 			.signers([signer]).rpc()
 	}
 
-	async buy (payload : OraclePayload, { message, signature }) {
+	async buy (payload : OraclePayload, signatures : OracleSignature | OracleSignature[]) {
 		const { signer } = this
 		
-		const { authority } = await this.registry
 		const makerPaymentAccount = await ata(payload.paymentMint, signer.publicKey)
-
-		const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
-			publicKey: authority.toBytes(),
-			message,
-			signature: Buffer.from(signature)
-		})
+		const ed25519Ixs = await this.oracleInstructions(signatures)
 
 		const createOrder = await this.#program.methods
       		.createBuyOrder(payload)
@@ -124,12 +301,16 @@ export class Ticker {
 				
 				tickerMintAccount: payload.tickerMint,
 				paymentMintAccount: payload.paymentMint,
-				makerPaymentAccount
+				makerPaymentAccount,
+				// @ts-ignore
+				priceFeed: this.feedPda(payload.tickerMint),
+				market: this.marketPda(payload.tickerMint, payload.paymentMint),
+				record: this.recordPda(payload.tickerMint)
 			})
 			.transaction()
 
 		const tx = new anchor.web3.Transaction()
-			.add(ed25519Ix)
+			.add(...ed25519Ixs)
 			.add(createOrder)
 
 		const txid = await this.#provider.sendAndConfirm(tx, [signer], {
@@ -142,25 +323,24 @@ export class Ticker {
 		})
 	}
 
-	async sell (payload : OraclePayload, { message, signature }) {
+	async sell (payload : OraclePayload, signatures : OracleSignature | OracleSignature[]) {
 		const { signer } = this
-		const { authority } = await this.registry
-		const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
-			publicKey: authority.toBytes(),
-			message,
-			signature: Buffer.from(signature)
-		})
+		const ed25519Ixs = await this.oracleInstructions(signatures)
 		const createOrder = await this.#program.methods
 			.createSellOrder(payload)
 			.accounts({
 				payer: signer.publicKey,
 				tickerMintAccount: payload.tickerMint,
-				paymentMintAccount: payload.paymentMint
+				paymentMintAccount: payload.paymentMint,
+				// @ts-ignore
+				priceFeed: this.feedPda(payload.tickerMint),
+				market: this.marketPda(payload.tickerMint, payload.paymentMint),
+				record: this.recordPda(payload.tickerMint)
 			})
 			.transaction()
 
 		const tx = new anchor.web3.Transaction()
-			.add(ed25519Ix)
+			.add(...ed25519Ixs)
 			.add(createOrder)
 
 		const txid = await this.#provider.sendAndConfirm(tx, [signer], {
@@ -173,6 +353,302 @@ export class Ticker {
 		})
 	}
 
+	async createPlan (payload : PlanPayload, signatures : OracleSignature | OracleSignature[]) {
+		const { signer } = this
+
+		const makerPaymentAccount = await ata(payload.paymentMint, signer.publicKey)
+		const oracleIxs = await this.oracleInstructions(signatures)
+
+		return this.#program.methods
+			.createPlan(payload)
+			.accounts({
+				payer: signer.publicKey,
+
+				tickerMintAccount: payload.tickerMint,
+				paymentMintAccount: payload.paymentMint,
+				market: this.marketPda(payload.tickerMint, payload.paymentMint),
+				makerPaymentAccount
+			})
+			.preInstructions(oracleIxs)
+			.signers([signer]).rpc()
+	}
+
+	#managePlan (method : 'pausePlan' | 'resumePlan', planId : number) {
+		const { signer } = this
+		const plan = this.pda(['plan', signer.publicKey.toBuffer(), new BN(planId).toArrayLike(Buffer, 'le', 8)])
+
+		return this.#program.methods[method]()
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				plan
+			})
+			.signers([signer]).rpc()
+	}
+
+	pausePlan (planId : number) {
+		return this.#managePlan('pausePlan', planId)
+	}
+
+	resumePlan (planId : number) {
+		return this.#managePlan('resumePlan', planId)
+	}
+
+	async cancelPlan (planId : number) {
+		const { signer } = this
+		const plan = this.pda(['plan', signer.publicKey.toBuffer(), new BN(planId).toArrayLike(Buffer, 'le', 8)])
+		const { paymentMint } = await this.plan(signer.publicKey, planId)
+
+		return this.#program.methods
+			.cancelPlan()
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				plan,
+				makerPaymentAccount: await ata(paymentMint, signer.publicKey)
+			})
+			.signers([signer]).rpc()
+	}
+
+	// должно совпадать с RecurringPlan::order_id
+	planOrderId (maker : PublicKey, planId : number, index : number) {
+		const hash = keccak_256(Buffer.concat([
+			Buffer.from('plan_order'),
+			maker.toBuffer(),
+			new BN(planId).toArrayLike(Buffer, 'le', 8),
+			new BN(index).toArrayLike(Buffer, 'le', 8)
+		]))
+
+		return new BN(hash.slice(0, 8), 'le')
+	}
+
+	async spawnPlanOrder (maker : PublicKey, planId : number) {
+		const { signer } = this
+		const plan = this.pda(['plan', maker.toBuffer(), new BN(planId).toArrayLike(Buffer, 'le', 8)])
+		const { tickerMint, paymentMint, spawned } = await this.plan(maker, planId)
+		const orderId = this.planOrderId(maker, planId, spawned.toNumber())
+
+		return this.#program.methods
+			.spawnPlanOrder()
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				plan,
+				order: this.pda(['order', maker.toBuffer(), orderId.toArrayLike(Buffer, 'le', 8)]),
+				tickerMintAccount: tickerMint,
+				paymentMintAccount: paymentMint,
+				makerPaymentAccount: await ata(paymentMint, maker),
+				tickerConfig: this.pda(['config', tickerMint.toBuffer()]),
+				market: this.marketPda(tickerMint, paymentMint),
+				record: this.recordPda(tickerMint)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async cross (
+		buy : { maker : PublicKey, id : number },
+		sell : { maker : PublicKey, id : number },
+		quantity : number, price : number,
+		{ attestation, priceFeed = false } : {
+			attestation? : { attestation : PriceAttestation, signatures : OracleSignature | OracleSignature[] },
+			priceFeed? : boolean
+		} = {}
+	) {
+		const { signer } = this
+		const orderPda = ({ maker, id }) => this.pda(['order', maker.toBuffer(), new BN(id).toArrayLike(Buffer, 'le', 8)])
+		const { tickerMint, paymentMint } = await this.order(buy.maker, buy.id)
+		const oracleIxs = attestation ? await this.oracleInstructions(attestation.signatures) : []
+
+		return this.#program.methods
+			.crossOrders(new BN(quantity), new BN(price), attestation?.attestation ?? null)
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				buyOrder: orderPda(buy),
+				sellOrder: orderPda(sell),
+				buyer: buy.maker,
+				seller: sell.maker,
+				buyerTickerAccount: await ata(tickerMint, buy.maker),
+				buyerRefundAccount: await ata(paymentMint, buy.maker),
+				sellerPaymentAccount: await ata(paymentMint, sell.maker),
+				sellerRefundAccount: await ata(tickerMint, sell.maker),
+				tickerMint,
+				paymentMint,
+				market: this.marketPda(tickerMint, paymentMint),
+				record: this.recordPda(tickerMint),
+				priceFeed: priceFeed ? this.feedPda(tickerMint) : null
+			})
+			.preInstructions(oracleIxs)
+			.signers([signer]).rpc()
+	}
+
+	async switch (payload : SwitchPayload, signatures : OracleSignature | OracleSignature[]) {
+		const { signer } = this
+		const oracleIxs = await this.oracleInstructions(signatures)
+
+		return this.#program.methods
+			.createSwitchOrder(payload)
+			.accounts({
+				payer: signer.publicKey,
+
+				fromMintAccount: payload.fromMint,
+				toMintAccount: payload.toMint,
+				paymentMintAccount: payload.paymentMint,
+				fromConfig: this.pda(['config', payload.fromMint.toBuffer()]),
+				fromMarket: this.marketPda(payload.fromMint, payload.paymentMint),
+				toMarket: this.marketPda(payload.toMint, payload.paymentMint),
+				fromRecord: this.recordPda(payload.fromMint),
+				toRecord: this.recordPda(payload.toMint),
+				makerFromAccount: await ata(payload.fromMint, signer.publicKey),
+				makerPaymentAccount: await ata(payload.paymentMint, signer.publicKey)
+			})
+			.preInstructions(oracleIxs)
+			.signers([signer]).rpc()
+	}
+
+	async cancelSwitch (orderId : number) {
+		const { signer } = this
+		const { fromMint, paymentMint } = await this.switchOrder(signer.publicKey, orderId)
+
+		return this.#program.methods
+			.cancelSwitchOrder(new BN(orderId))
+			.accounts({
+				payer: signer.publicKey,
+				refundAccount: await ata(fromMint, signer.publicKey),
+				makerPaymentAccount: await ata(paymentMint, signer.publicKey)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async executeSwitch (
+		report : SwitchReport, { message, signature } : { message : Uint8Array, signature : number[] }
+	) {
+		const { signer } = this
+		const { maker, orderId } = report
+		const { fromMint, toMint, paymentMint } = await this.switchOrder(maker, orderId.toNumber())
+		const { broker } = await this.registry
+
+		const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
+			publicKey: broker.toBytes(),
+			message,
+			signature: Buffer.from(signature)
+		})
+
+		return this.#program.methods
+			.executeSwitchOrder(report)
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				fill: this.pda(['fill', Buffer.from(report.brokerExecId)]),
+				maker,
+				fromMint,
+				toMint,
+				paymentMint,
+				fromFeed: this.feedPda(fromMint),
+				toFeed: this.feedPda(toMint),
+				fromRecord: this.recordPda(fromMint),
+				toRecord: this.recordPda(toMint),
+				makerToAccount: await ata(toMint, maker),
+				makerPaymentAccount: await ata(paymentMint, maker),
+				refundAccount: await ata(fromMint, maker)
+			})
+			.preInstructions([ed25519Ix])
+			.signers([signer]).rpc()
+	}
+
+	async basket (payload : BasketPayload, signatures : OracleSignature | OracleSignature[]) {
+		const { signer } = this
+		const oracleIxs = await this.oracleInstructions(signatures)
+
+		// АТА мейкера под каждый тикер корзины, чтобы исполнитель мог минтить в них
+		const ataIxs = await Promise.all(payload.legs.map(async ({ tickerMint }) =>
+			createAssociatedTokenAccountIdempotentInstruction(
+				signer.publicKey, await ata(tickerMint, signer.publicKey), signer.publicKey, tickerMint
+			)
+		))
+
+		return this.#program.methods
+			.createBasketOrder(payload)
+			.accounts({
+				payer: signer.publicKey,
+				paymentMintAccount: payload.paymentMint,
+				makerPaymentAccount: await ata(payload.paymentMint, signer.publicKey)
+			})
+			.remainingAccounts(payload.legs.flatMap(({ tickerMint }) =>
+				[tickerMint, this.pda(['config', tickerMint.toBuffer()]), this.marketPda(tickerMint, payload.paymentMint), this.recordPda(tickerMint)]
+					.map(pubkey => ({ pubkey, isSigner: false, isWritable: false }))
+			))
+			.preInstructions([...ataIxs, ...oracleIxs])
+			.signers([signer]).rpc()
+	}
+
+	async cancelBasket (orderId : number) {
+		const { signer } = this
+		const { paymentMint } = await this.basketOrder(signer.publicKey, orderId)
+
+		return this.#program.methods
+			.cancelBasketOrder(new BN(orderId))
+			.accounts({
+				payer: signer.publicKey,
+				refundAccount: await ata(paymentMint, signer.publicKey)
+			})
+			.signers([signer]).rpc()
+	}
+
+	// permissionless: после expires_at остаток эскроу возвращается мейкеру
+	async expireBasket (maker : PublicKey, orderId : number) {
+		const { signer } = this
+		const { paymentMint } = await this.basketOrder(maker, orderId)
+
+		return this.#program.methods
+			.expireBasketOrder()
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				basketOrder: this.pda(['basket', maker.toBuffer(), new BN(orderId).toArrayLike(Buffer, 'le', 8)]),
+				maker,
+				refundAccount: await ata(paymentMint, maker)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async executeBasket (report : BasketReport, { message, signature } : { message : Uint8Array, signature : number[] }) {
+		const { signer } = this
+		const { maker, orderId } = report
+		const { legs, paymentMint } = await this.basketOrder(maker, orderId.toNumber())
+		const { broker } = await this.registry
+
+		const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
+			publicKey: broker.toBytes(),
+			message,
+			signature: Buffer.from(signature)
+		})
+
+		const remainingAccounts = (await Promise.all(report.fills.map(async ({ leg }) => {
+			const { tickerMint } = legs[leg]
+			return [
+				...[tickerMint, await ata(tickerMint, maker), this.pda(['pool', tickerMint.toBuffer(), paymentMint.toBuffer()])]
+					.map(pubkey => ({ pubkey, isSigner: false, isWritable: true })),
+				...[this.pda(['config', tickerMint.toBuffer()]), this.feedPda(tickerMint), this.recordPda(tickerMint)]
+					.map(pubkey => ({ pubkey, isSigner: false, isWritable: false }))
+			]
+		}))).flat()
+
+		return this.#program.methods
+			.executeBasketOrder(report)
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				fill: this.pda(['fill', Buffer.from(report.brokerExecId)]),
+				maker,
+				paymentMint,
+				refundAccount: await ata(paymentMint, maker)
+			})
+			.remainingAccounts(remainingAccounts)
+			.preInstructions([ed25519Ix])
+			.signers([signer]).rpc()
+	}
+
 	async cancel (orderId : number) {
 		const { signer } = this
 
@@ -192,43 +668,119 @@ export class Ticker {
 			.signers([signer]).rpc()
 	}
 
-	async process (maker : PublicKey, orderId : number) {
+	async #terminate (method : 'rejectOrder' | 'expireOrder', maker : PublicKey, orderId : number) {
+		const { signer } = this
+
+		const order = this.pda(['order', maker.toBuffer(), new BN(orderId).toArrayLike(Buffer, 'le', 8)])
+		const { side, tickerMint, paymentMint } = await this.order(maker, orderId)
+		const refundAccount = await ata(
+			Object.keys(side)[0] === 'buy' ? paymentMint : tickerMint,
+			maker
+		)
+
+		return this.#program.methods[method]()
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				order,
+				maker,
+				refundAccount
+			})
+			.signers([signer]).rpc()
+	}
+
+	reject (maker : PublicKey, orderId : number) {
+		return this.#terminate('rejectOrder', maker, orderId)
+	}
+
+	expire (maker : PublicKey, orderId : number) {
+		return this.#terminate('expireOrder', maker, orderId)
+	}
+
+	cleanup (maker : PublicKey, orderId : number) {
+		const { signer } = this
+		const order = this.pda(['order', maker.toBuffer(), new BN(orderId).toArrayLike(Buffer, 'le', 8)])
+
+		return this.#program.methods
+			.cleanupOrder()
+			.accounts({
+				payer: signer.publicKey,
+				// @ts-ignore
+				order,
+				maker
+			})
+			.signers([signer]).rpc()
+	}
+
+	setOrderRetention (seconds : number) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setOrderRetention(new BN(seconds))
+			.accounts({ authority: signer.publicKey })
+			.signers([signer])
+			.rpc()
+	}
+
+	async process (
+		maker : PublicKey, orderId : number,
+		attestation? : { attestation : PriceAttestation, signatures : OracleSignature | OracleSignature[] }
+	) {
 		const { signer } = this
 		const order = this.pda(['order', maker.toBuffer(), new BN(orderId).toArrayLike(Buffer, 'le', 8)])
+		const oracleIxs = attestation ? await this.oracleInstructions(attestation.signatures) : []
 
 		return this.#program.methods
-			.processOrder()
+			.processOrder(attestation?.attestation ?? null)
 			.accounts({
 				payer: signer.publicKey,
 				// @ts-ignore
 				order
 			})
+			.preInstructions(oracleIxs)
 			.signers([signer]).rpc()
 	}
 
-	async execute (maker : PublicKey, orderId : number, spent : bigint, proofCid : number[]) {
+	async execute (
+		report : ExecutionReport,
+		{ message, signature } : { message : Uint8Array, signature : number[] },
+		{ storeProof = false, priceFeed = false } = {}
+	) {
 		const { signer } = this
-		const { side, tickerMint, paymentMint } = await this.order(maker, orderId)
+		const { maker, orderId } = report
+		const { side, tickerMint, paymentMint } = await this.order(maker, orderId.toNumber())
+		const { broker } = await this.registry
 		
-		const refundAccount = await ata(paymentMint, maker)
-		const makerAccount = Object.keys(side)[0] === 'buy' 
-			? await ata(tickerMint, maker)
-			: refundAccount
+		const isBuy = Object.keys(side)[0] === 'buy'
+		const makerAccount = await ata(isBuy ? tickerMint : paymentMint, maker)
+		const refundAccount = await ata(isBuy ? paymentMint : tickerMint, maker)
+
 
+		const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
+			publicKey: broker.toBytes(),
+			message,
+			signature: Buffer.from(signature)
+		})
 
 		const executeOrder = await this.#program.methods
-			.executeOrder(orderId, new BN(spent), Buffer.from(proofCid))
+			.executeOrder(report)
 			.accounts({
 				payer: signer.publicKey,
+				// @ts-ignore
+				fill: this.pda(['fill', Buffer.from(report.brokerExecId)]),
+				executionProof: storeProof ? this.pda(['proof', Buffer.from(report.brokerExecId)]) : null,
+				priceFeed: priceFeed ? this.feedPda(tickerMint) : null,
 				maker,
 				makerAccount,
 				refundAccount,
 				paymentMint,
 				tickerMint,
+				record: this.recordPda(tickerMint),
 			})
 			.transaction()
 		
 		const tx = new anchor.web3.Transaction()
+			.add(ed25519Ix)
 			.add(executeOrder)
 
 		const txid = await this.#provider.sendAndConfirm(tx, [signer], {
@@ -240,7 +792,355 @@ export class Ticker {
 			maxSupportedTransactionVersion: 0
 		})
 	}
-}
 
-const TickerToken = await new Ticker().init()
-export default TickerToken
+	async createBook (tickerMint : PublicKey, paymentMint : PublicKey, makerFeeBps : number, takerFeeBps : number) {
+		const { signer } = this
+
+		return this.#program.methods
+			.createBook(makerFeeBps, takerFeeBps)
+			.accounts({
+				payer: signer.publicKey,
+				tickerMint,
+				paymentMint
+			})
+			.signers([signer]).rpc()
+	}
+
+	async placeBookOrder (
+		tickerMint : PublicKey, paymentMint : PublicKey,
+		side : 'buy' | 'sell', price : number, quantity : number
+	) {
+		const { signer } = this
+		const book = this.bookPda(tickerMint, paymentMint)
+
+		return this.#program.methods
+			.placeBookOrder({ [side]: {} } as any, new BN(price), new BN(quantity))
+			.accounts({
+				owner: signer.publicKey,
+				// @ts-ignore
+				book,
+				market: this.marketPda(tickerMint, paymentMint),
+				record: this.recordPda(tickerMint),
+				tickerConfig: this.pda(['config', tickerMint.toBuffer()]),
+				ownerTickerAccount: await ata(tickerMint, signer.publicKey),
+				ownerPaymentAccount: await ata(paymentMint, signer.publicKey)
+			})
+			.preInstructions([
+				createAssociatedTokenAccountIdempotentInstruction(signer.publicKey, await ata(tickerMint, signer.publicKey), signer.publicKey, tickerMint),
+				createAssociatedTokenAccountIdempotentInstruction(signer.publicKey, await ata(paymentMint, signer.publicKey), signer.publicKey, paymentMint)
+			])
+			.signers([signer]).rpc()
+	}
+
+	async cancelBookOrder (tickerMint : PublicKey, paymentMint : PublicKey, side : 'buy' | 'sell', orderId : number) {
+		const { signer } = this
+
+		return this.#program.methods
+			.cancelBookOrder({ [side]: {} } as any, new BN(orderId))
+			.accounts({
+				owner: signer.publicKey,
+				// @ts-ignore
+				book: this.bookPda(tickerMint, paymentMint),
+				refundAccount: await ata(side === 'buy' ? paymentMint : tickerMint, signer.publicKey)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async consumeBookEvents (tickerMint : PublicKey, paymentMint : PublicKey, limit = 8) {
+		const { signer } = this
+		const { events } = await this.book(tickerMint, paymentMint)
+		const batch = events.slice(0, limit)
+
+		const remainingAccounts = []
+		const preInstructions = []
+		for (const { owner } of batch) {
+			for (const mint of [tickerMint, paymentMint]) {
+				const account = await ata(mint, owner)
+				remainingAccounts.push({ pubkey: account, isSigner: false, isWritable: true })
+				preInstructions.push(createAssociatedTokenAccountIdempotentInstruction(signer.publicKey, account, owner, mint))
+			}
+		}
+
+		return this.#program.methods
+			.consumeBookEvents(batch.length)
+			.accounts({
+				// @ts-ignore
+				book: this.bookPda(tickerMint, paymentMint)
+			})
+			.remainingAccounts(remainingAccounts)
+			.preInstructions(preInstructions)
+			.signers([signer]).rpc()
+	}
+
+	async createAmm (
+		tickerMint : PublicKey, paymentMint : PublicKey,
+		{ marketMaker, spreadBps, maxAge } : { marketMaker : PublicKey, spreadBps : number, maxAge : number }
+	) {
+		const { signer } = this
+
+		return this.#program.methods
+			.createAmm(marketMaker, spreadBps, new BN(maxAge))
+			.accounts({
+				payer: signer.publicKey,
+				tickerMint,
+				paymentMint
+			})
+			.signers([signer]).rpc()
+	}
+
+	async configureAmm (
+		tickerMint : PublicKey, paymentMint : PublicKey,
+		{ marketMaker, spreadBps, maxAge } : { marketMaker : PublicKey, spreadBps : number, maxAge : number }
+	) {
+		const { signer } = this
+
+		return this.#program.methods
+			.configureAmm(marketMaker, spreadBps, new BN(maxAge))
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				amm: this.ammPda(tickerMint, paymentMint)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async updateAmmReference (attestation : PriceAttestation, signatures : OracleSignature | OracleSignature[]) {
+		const { signer } = this
+		const oracleIxs = await this.oracleInstructions(signatures)
+
+		return this.#program.methods
+			.updateAmmReference(attestation)
+			.accounts({
+				// @ts-ignore
+				amm: this.ammPda(attestation.tickerMint, attestation.paymentMint)
+			})
+			.preInstructions(oracleIxs)
+			.signers([signer]).rpc()
+	}
+
+	async #liquidity (method : 'addLiquidity' | 'removeLiquidity', tickerMint : PublicKey, paymentMint : PublicKey, tickerAmount : number, paymentAmount : number) {
+		const { signer } = this
+
+		return this.#program.methods[method](new BN(tickerAmount), new BN(paymentAmount))
+			.accounts({
+				marketMaker: signer.publicKey,
+				// @ts-ignore
+				amm: this.ammPda(tickerMint, paymentMint),
+				makerTickerAccount: await ata(tickerMint, signer.publicKey),
+				makerPaymentAccount: await ata(paymentMint, signer.publicKey)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async addLiquidity (tickerMint : PublicKey, paymentMint : PublicKey, tickerAmount : number, paymentAmount : number) {
+		return this.#liquidity('addLiquidity', tickerMint, paymentMint, tickerAmount, paymentAmount)
+	}
+
+	async removeLiquidity (tickerMint : PublicKey, paymentMint : PublicKey, tickerAmount : number, paymentAmount : number) {
+		return this.#liquidity('removeLiquidity', tickerMint, paymentMint, tickerAmount, paymentAmount)
+	}
+
+	async ammSwap (
+		tickerMint : PublicKey, paymentMint : PublicKey,
+		side : 'buy' | 'sell', quantity : number, limit : number
+	) {
+		const { signer } = this
+		const userTickerAccount = await ata(tickerMint, signer.publicKey)
+
+		return this.#program.methods
+			.ammSwap({ [side]: {} } as any, new BN(quantity), new BN(limit))
+			.accounts({
+				user: signer.publicKey,
+				// @ts-ignore
+				amm: this.ammPda(tickerMint, paymentMint),
+				userTickerAccount,
+				userPaymentAccount: await ata(paymentMint, signer.publicKey),
+				paymentMint,
+				market: this.marketPda(tickerMint, paymentMint),
+				record: this.recordPda(tickerMint),
+				priceFeed: this.feedPda(tickerMint)
+			})
+			.preInstructions([
+				createAssociatedTokenAccountIdempotentInstruction(signer.publicKey, userTickerAccount, signer.publicKey, tickerMint)
+			])
+			.signers([signer]).rpc()
+	}
+
+	async updatePriceFeed (update : PriceFeedUpdate, signatures : OracleSignature | OracleSignature[]) {
+		const { signer } = this
+		const oracleIxs = await this.oracleInstructions(signatures)
+
+		return this.#program.methods
+			.updatePriceFeed(update)
+			.accounts({
+				// @ts-ignore
+				feed: this.feedPda(update.tickerMint)
+			})
+			.preInstructions(oracleIxs)
+			.signers([signer]).rpc()
+	}
+
+	async configurePriceFeed (
+		tickerMint : PublicKey,
+		{ maxAge, maxConfBps, maxDeviationBps } : { maxAge : number, maxConfBps : number, maxDeviationBps : number }
+	) {
+		const { signer } = this
+
+		return this.#program.methods
+			.configurePriceFeed(new BN(maxAge), maxConfBps, maxDeviationBps)
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				feed: this.feedPda(tickerMint)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async initTickerConfig (symbol : string) {
+		const { signer } = this
+
+		return this.#program.methods
+			.initTickerConfig(symbol)
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				mint: this.pda(['mint', symbol])
+			})
+			.signers([signer]).rpc()
+	}
+
+	async initPriceFeed (symbol : string) {
+		const { signer } = this
+
+		return this.#program.methods
+			.initPriceFeed(symbol)
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				mint: this.pda(['mint', symbol])
+			})
+			.signers([signer]).rpc()
+	}
+
+	async migrateTickerConfig (tickerMint : PublicKey) {
+		const { signer } = this
+
+		return this.#program.methods
+			.migrateTickerConfig()
+			.accounts({
+				authority: signer.publicKey,
+				config: this.pda(['config', tickerMint.toBuffer()])
+			})
+			.signers([signer]).rpc()
+	}
+
+	async setPriceBand (tickerMint : PublicKey, bandBps : number) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setPriceBand(bandBps)
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				config: this.pda(['config', tickerMint.toBuffer()])
+			})
+			.signers([signer]).rpc()
+	}
+
+	async setMarketSession (
+		{ weekdays, openMinute, closeMinute, utcOffset } : { weekdays : number, openMinute : number, closeMinute : number, utcOffset : number }
+	) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setMarketSession(weekdays, openMinute, closeMinute, utcOffset)
+			.accounts({ authority: signer.publicKey })
+			.signers([signer]).rpc()
+	}
+
+	// holidays — местные даты, дней с 1970-01-01
+	async setMarketHolidays (holidays : number[], earlyCloses : { day : number, closeMinute : number }[] = []) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setMarketHolidays(holidays, earlyCloses)
+			.accounts({ authority: signer.publicKey })
+			.signers([signer]).rpc()
+	}
+
+	async setOrderLimits (
+		tickerMint : PublicKey,
+		{ minQuantity = 0, quantityStep = 0, priceTick = 0, minNotional = 0 } : {
+			minQuantity? : number, quantityStep? : number, priceTick? : number, minNotional? : number
+		}
+	) {
+		const { signer } = this
+
+		return this.#program.methods
+			.setOrderLimits(new BN(minQuantity), new BN(quantityStep), new BN(priceTick), new BN(minNotional))
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				config: this.pda(['config', tickerMint.toBuffer()])
+			})
+			.signers([signer]).rpc()
+	}
+
+	async openMarket (tickerMint : PublicKey, paymentMint : PublicKey, { maxQuantity = 0, maxNotional = 0 } = {}) {
+		const { signer } = this
+
+		return this.#program.methods
+			.openMarket(new BN(maxQuantity), new BN(maxNotional))
+			.accounts({
+				payer: signer.publicKey,
+				tickerMint,
+				paymentMint
+			})
+			.signers([signer]).rpc()
+	}
+
+	async configureMarket (
+		tickerMint : PublicKey, paymentMint : PublicKey,
+		status : 'open' | 'halted' | 'closed', { maxQuantity = 0, maxNotional = 0 } = {}
+	) {
+		const { signer } = this
+
+		return this.#program.methods
+			.configureMarket({ [status]: {} } as any, new BN(maxQuantity), new BN(maxNotional))
+			.accounts({
+				authority: signer.publicKey,
+				// @ts-ignore
+				market: this.marketPda(tickerMint, paymentMint)
+			})
+			.signers([signer]).rpc()
+	}
+
+	async delistTicker (tickerMint : PublicKey, paymentMint : PublicKey, finalPrice : number | bigint) {
+		const { signer } = this
+
+		return this.#program.methods
+			.delistTicker(new BN(finalPrice.toString()))
+			.accounts({
+				authority: signer.publicKey,
+				tickerMint,
+				paymentMint
+			})
+			.signers([signer]).rpc()
+	}
+
+	// погасить тикер держателя по финальной цене: сам держатель или его делегат
+	async redeemTicker (tickerMint : PublicKey, holder : PublicKey = this.signer.publicKey) {
+		const { signer } = this
+		const { paymentMint } = await this.tickerRecord(tickerMint)
+
+		return this.#program.methods
+			.redeemTicker()
+			.accounts({
+				payer: signer.publicKey,
+				tickerMint,
+				holderAccount: await ata(tickerMint, holder),
+				holderPaymentAccount: await ata(paymentMint, holder)
+			})
+			.signers([signer]).rpc()
+	}
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{TokenAccount, Token, Mint},
+};
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	price,
+	amm::state::*,
+};
+
+#[derive(Accounts)]
+pub struct CreateAmm<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	pub ticker_mint: Box<Account<'info, Mint>>,
+	pub payment_mint: Box<Account<'info, Mint>>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"amm", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		space = LiquidityPool::SPACE,
+	)]
+	pub amm: Box<Account<'info, LiquidityPool>>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"amm_vault", amm.key().as_ref(), ticker_mint.key().as_ref()],
+		bump,
+		token::mint = ticker_mint,
+		token::authority = amm,
+	)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"amm_vault", amm.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		token::mint = payment_mint,
+		token::authority = amm,
+	)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+	pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAmm<'info> {
+	#[account(constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized)]
+	pub authority: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+		seeds = [b"amm", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref()],
+		bump = amm.bump,
+	)]
+	pub amm: Box<Account<'info, LiquidityPool>>,
+}
+
+fn validate(spread_bps: u16, max_age: i64) -> Result<()> {
+	require!(spread_bps <= MAX_AMM_SPREAD_BPS, ErrorCode::InvalidAmmConfig);
+	require!(max_age > 0, ErrorCode::InvalidAmmConfig);
+	Ok(())
+}
+
+/// Открывает пул ликвидности тикера. Обмен недоступен до первой референсной цены.
+pub fn create(ctx: Context<CreateAmm>, market_maker: Pubkey, spread_bps: u16, max_age: i64) -> Result<()> {
+	validate(spread_bps, max_age)?;
+
+	let amm = &mut ctx.accounts.amm;
+	amm.ticker_mint = ctx.accounts.ticker_mint.key();
+	amm.payment_mint = ctx.accounts.payment_mint.key();
+	amm.price_scale = price::price_scale(ctx.accounts.ticker_mint.decimals)?;
+	amm.market_maker = market_maker;
+	amm.reference_price = 0;
+	amm.reference_at = 0;
+	amm.max_age = max_age;
+	amm.spread_bps = spread_bps;
+	amm.bump = ctx.bumps.amm;
+
+	emit!(AmmCreated {
+		ticker_mint: amm.ticker_mint,
+		payment_mint: amm.payment_mint,
+
+		market_maker,
+		spread_bps,
+		max_age,
+	});
+
+	Ok(())
+}
+
+/// Меняет маркет-мейкера, спред и допустимый возраст референсной цены
+pub fn configure(ctx: Context<ConfigureAmm>, market_maker: Pubkey, spread_bps: u16, max_age: i64) -> Result<()> {
+	validate(spread_bps, max_age)?;
+
+	let amm = &mut ctx.accounts.amm;
+	amm.market_maker = market_maker;
+	amm.spread_bps = spread_bps;
+	amm.max_age = max_age;
+
+	emit!(AmmConfigured {
+		ticker_mint: amm.ticker_mint,
+		payment_mint: amm.payment_mint,
+
+		market_maker,
+		spread_bps,
+		max_age,
+	});
+
+	Ok(())
+}
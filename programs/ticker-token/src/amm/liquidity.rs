@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{self, TokenAccount, Token},
+};
+use crate::{
+	errors::ErrorCode,
+	amm::state::*,
+};
+
+#[derive(Accounts)]
+pub struct ManageLiquidity<'info> {
+	#[account(constraint = market_maker.key() == amm.market_maker @ ErrorCode::Unauthorized)]
+	pub market_maker: Signer<'info>,
+
+	#[account(
+		seeds = [b"amm", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref()],
+		bump = amm.bump,
+	)]
+	pub amm: Box<Account<'info, LiquidityPool>>,
+
+	#[account(
+		mut,
+		constraint = maker_ticker_account.owner == market_maker.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_ticker_account.mint == amm.ticker_mint @ ErrorCode::InvalidTickerMint,
+	)]
+	pub maker_ticker_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == market_maker.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_payment_account.mint == amm.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub maker_payment_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"amm_vault", amm.key().as_ref(), amm.ticker_mint.as_ref()], bump)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"amm_vault", amm.key().as_ref(), amm.payment_mint.as_ref()], bump)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+/// Маркет-мейкер вносит тикер и/или платёжный токен в пул
+pub fn add_liquidity(ctx: Context<ManageLiquidity>, ticker_amount: u64, payment_amount: u64) -> Result<()> {
+	require!(ticker_amount > 0 || payment_amount > 0, ErrorCode::InvalidFillAmount);
+
+	let accounts = &ctx.accounts;
+	for (from, to, amount) in [
+		(&accounts.maker_ticker_account, &accounts.ticker_vault, ticker_amount),
+		(&accounts.maker_payment_account, &accounts.payment_vault, payment_amount),
+	] {
+		if amount == 0 {
+			continue;
+		}
+		token::transfer(
+			CpiContext::new(
+				accounts.token_program.to_account_info(),
+				token::Transfer {
+					from: from.to_account_info(),
+					to: to.to_account_info(),
+					authority: accounts.market_maker.to_account_info(),
+				},
+			),
+			amount,
+		)?;
+	}
+
+	emit!(AmmLiquidityChanged {
+		ticker_mint: accounts.amm.ticker_mint,
+		payment_mint: accounts.amm.payment_mint,
+
+		market_maker: accounts.market_maker.key(),
+		deposit: true,
+		ticker_amount,
+		payment_amount,
+
+		timestamp: Clock::get()?.unix_timestamp,
+	});
+
+	Ok(())
+}
+
+/// Маркет-мейкер выводит тикер и/или платёжный токен из пула
+pub fn remove_liquidity(ctx: Context<ManageLiquidity>, ticker_amount: u64, payment_amount: u64) -> Result<()> {
+	require!(ticker_amount > 0 || payment_amount > 0, ErrorCode::InvalidFillAmount);
+
+	let accounts = &ctx.accounts;
+	require!(
+		ticker_amount <= accounts.ticker_vault.amount && payment_amount <= accounts.payment_vault.amount,
+		ErrorCode::InsufficientLiquidity
+	);
+
+	let amm = &accounts.amm;
+	let bump = [amm.bump];
+	let seeds: [&[u8]; 4] = [b"amm", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref(), &bump];
+
+	for (from, to, amount) in [
+		(&accounts.ticker_vault, &accounts.maker_ticker_account, ticker_amount),
+		(&accounts.payment_vault, &accounts.maker_payment_account, payment_amount),
+	] {
+		if amount == 0 {
+			continue;
+		}
+		token::transfer(
+			CpiContext::new_with_signer(
+				accounts.token_program.to_account_info(),
+				token::Transfer {
+					from: from.to_account_info(),
+					to: to.to_account_info(),
+					authority: amm.to_account_info(),
+				},
+				&[&seeds],
+			),
+			amount,
+		)?;
+	}
+
+	emit!(AmmLiquidityChanged {
+		ticker_mint: amm.ticker_mint,
+		payment_mint: amm.payment_mint,
+
+		market_maker: accounts.market_maker.key(),
+		deposit: false,
+		ticker_amount,
+		payment_amount,
+
+		timestamp: Clock::get()?.unix_timestamp,
+	});
+
+	Ok(())
+}
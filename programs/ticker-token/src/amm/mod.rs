@@ -0,0 +1,13 @@
+pub mod state;
+
+pub mod create;
+pub use create::*;
+
+pub mod reference;
+pub use reference::*;
+
+pub mod liquidity;
+pub use liquidity::*;
+
+pub mod swap;
+pub use swap::*;
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	utils::{verify_oracle_quorum, SignedMessage},
+	order::{types::PriceAttestation, execute::MAX_ATTESTATION_AGE},
+	amm::state::*,
+};
+
+#[derive(Accounts)]
+pub struct UpdateAmmReference<'info> {
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+		seeds = [b"amm", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref()],
+		bump = amm.bump,
+	)]
+	pub amm: Box<Account<'info, LiquidityPool>>,
+
+	/// CHECK: instruction sysvar, used for verifying oracle price attestation
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+}
+
+/// Обновляет референсную цену пула по аттестации кворума оракулов.
+/// Вызывать может кто угодно: доверие — к подписям, не к отправителю.
+pub fn update_reference(ctx: Context<UpdateAmmReference>, attestation: PriceAttestation) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+
+	let serialized = attestation.message()?;
+
+	let registry = &ctx.accounts.registry;
+	verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+	let amm = &mut ctx.accounts.amm;
+	require!(
+		attestation.ticker_mint == amm.ticker_mint && attestation.payment_mint == amm.payment_mint,
+		ErrorCode::InvalidPriceAttestation
+	);
+	require!(
+		attestation.timestamp <= now && now - attestation.timestamp <= MAX_ATTESTATION_AGE,
+		ErrorCode::InvalidPriceAttestation
+	);
+	// более старая аттестация не может откатить цену
+	require!(attestation.timestamp > amm.reference_at, ErrorCode::InvalidPriceAttestation);
+	require!(attestation.price > 0, ErrorCode::InvalidPrice);
+
+	amm.reference_price = attestation.price;
+	amm.reference_at = attestation.timestamp;
+
+	emit!(AmmReferenceUpdated {
+		ticker_mint: amm.ticker_mint,
+		payment_mint: amm.payment_mint,
+
+		price: attestation.price,
+		timestamp: attestation.timestamp,
+	});
+
+	Ok(())
+}
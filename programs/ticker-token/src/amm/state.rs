@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use crate::{
+    errors::ErrorCode,
+    price::{self, Rounding},
+    order::types::OrderSide,
+};
+
+/// Максимальный спред пула, bps
+pub const MAX_AMM_SPREAD_BPS: u16 = 5_000;
+
+/// Пул ликвидности тикера для торговли вне биржевой сессии,
+/// PDA ['amm', ticker_mint, payment_mint]. Цена обмена не свободная (не x*y=k),
+/// а привязана к референсной цене оракулов: покупка по `ref * (1 + spread)`,
+/// продажа по `ref * (1 - spread)`. Спред — доход маркет-мейкера.
+#[account]
+pub struct LiquidityPool {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+    pub price_scale: u64,
+
+    pub market_maker: Pubkey, // единственный, кто вносит и выводит ликвидность
+
+    pub reference_price: u64, // в платёжном токене за price_scale базовых единиц тикера
+    pub reference_at: i64, // время аттестации референсной цены
+    pub max_age: i64, // после этого срока обмен останавливается до новой цены
+
+    pub spread_bps: u16,
+    pub bump: u8,
+}
+
+impl LiquidityPool {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // ticker_mint
+        + 32 // payment_mint
+        + 8 // price_scale
+        + 32 // market_maker
+        + 8 // reference_price
+        + 8 // reference_at
+        + 8 // max_age
+        + 2 // spread_bps
+        + 1; // bump
+
+    /// Цена обмена для стороны пользователя: покупка — верх полосы, продажа — низ
+    pub fn quote(&self, side: OrderSide, now: i64) -> Result<u64> {
+        require!(
+            self.reference_price > 0 && now - self.reference_at <= self.max_age,
+            ErrorCode::StaleReferencePrice
+        );
+
+        let spread = self.spread_bps as u64;
+        match side {
+            OrderSide::Buy => price::mul_div(self.reference_price, 10_000 + spread, 10_000, Rounding::Up),
+            OrderSide::Sell => price::mul_div(self.reference_price, 10_000 - spread, 10_000, Rounding::Down),
+        }
+    }
+}
+
+#[event]
+pub struct AmmCreated {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub market_maker: Pubkey,
+    pub spread_bps: u16,
+    pub max_age: i64,
+}
+
+#[event]
+pub struct AmmConfigured {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub market_maker: Pubkey,
+    pub spread_bps: u16,
+    pub max_age: i64,
+}
+
+#[event]
+pub struct AmmReferenceUpdated {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub price: u64,
+    pub timestamp: i64, // время аттестации
+}
+
+#[event]
+pub struct AmmLiquidityChanged {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub market_maker: Pubkey,
+    pub deposit: bool,
+    pub ticker_amount: u64,
+    pub payment_amount: u64,
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AmmSwap {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub user: Pubkey,
+    pub side: OrderSide,
+
+    pub quantity: u64, // тикер
+    pub price: u64, // цена обмена с учётом спреда
+    pub reference_price: u64,
+    pub payment: u64, // уплачено (Buy) или получено (Sell)
+
+    pub timestamp: i64,
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{self, TokenAccount, Token, Mint},
+};
+use crate::{
+	errors::ErrorCode,
+	config::TickerConfig,
+	market::{Market, MarketStatus},
+	ticker::{TickerRecord, TickerStatus},
+	feed::PriceFeed,
+	price::{self, Rounding},
+	order::types::OrderSide,
+	amm::state::*,
+};
+
+#[derive(Accounts)]
+pub struct AmmSwapAccounts<'info> {
+	pub user: Signer<'info>,
+
+	#[account(
+		seeds = [b"amm", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref()],
+		bump = amm.bump,
+	)]
+	pub amm: Box<Account<'info, LiquidityPool>>,
+
+	#[account(
+		mut,
+		constraint = user_ticker_account.owner == user.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = user_ticker_account.mint == amm.ticker_mint @ ErrorCode::InvalidTickerMint,
+	)]
+	pub user_ticker_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		constraint = user_payment_account.owner == user.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = user_payment_account.mint == amm.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub user_payment_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"amm_vault", amm.key().as_ref(), amm.ticker_mint.as_ref()], bump)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"amm_vault", amm.key().as_ref(), amm.payment_mint.as_ref()], bump)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(constraint = payment_mint.key() == amm.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint: Box<Account<'info, Mint>>,
+
+	/// Открытый рынок пары (тикер, платёжный токен)
+	#[account(
+		seeds = [b"market", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref()],
+		bump = market.bump,
+		constraint = market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	/// Тикер не делистингован
+	#[account(
+		seeds = [b"ticker", amm.ticker_mint.as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
+	#[account(seeds = [b"config", amm.ticker_mint.as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Box<Account<'info, TickerConfig>>,
+
+	/// Цена тикера от оракулов; нужна, если у тикера задан коридор цен
+	#[account(seeds = [b"feed", amm.ticker_mint.as_ref()], bump = price_feed.bump)]
+	pub price_feed: Option<Box<Account<'info, PriceFeed>>>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+/// Обмен с пулом по референсной цене ± спред.
+/// `quantity` — количество тикера; `limit` — максимум к оплате (Buy) или минимум к получению (Sell).
+pub fn swap(ctx: Context<AmmSwapAccounts>, side: OrderSide, quantity: u64, limit: u64) -> Result<()> {
+	require!(quantity > 0, ErrorCode::InvalidFillAmount);
+	ctx.accounts.market.check_quantity(quantity)?;
+
+	let now = Clock::get()?.unix_timestamp;
+	let accounts = &ctx.accounts;
+	let amm = &accounts.amm;
+
+	let price = amm.quote(side, now)?;
+	accounts.ticker_config.check_band(accounts.price_feed.as_deref().map(|feed| &**feed), price, accounts.payment_mint.decimals, now)?;
+
+	let (payment, from_user, to_user) = match side {
+		OrderSide::Buy => {
+			let cost = price::notional(quantity, price, amm.price_scale, Rounding::Up)?;
+			require!(cost <= limit, ErrorCode::SlippageExceeded);
+			require!(quantity <= accounts.ticker_vault.amount, ErrorCode::InsufficientLiquidity);
+			(
+				cost,
+				(&accounts.user_payment_account, &accounts.payment_vault, cost),
+				(&accounts.ticker_vault, &accounts.user_ticker_account, quantity),
+			)
+		}
+		OrderSide::Sell => {
+			let proceeds = price::notional(quantity, price, amm.price_scale, Rounding::Down)?;
+			require!(proceeds >= limit, ErrorCode::SlippageExceeded);
+			require!(proceeds <= accounts.payment_vault.amount, ErrorCode::InsufficientLiquidity);
+			(
+				proceeds,
+				(&accounts.user_ticker_account, &accounts.ticker_vault, quantity),
+				(&accounts.payment_vault, &accounts.user_payment_account, proceeds),
+			)
+		}
+	};
+
+	let bump = [amm.bump];
+	let seeds: [&[u8]; 4] = [b"amm", amm.ticker_mint.as_ref(), amm.payment_mint.as_ref(), &bump];
+
+	if from_user.2 > 0 {
+		token::transfer(
+			CpiContext::new(
+				accounts.token_program.to_account_info(),
+				token::Transfer {
+					from: from_user.0.to_account_info(),
+					to: from_user.1.to_account_info(),
+					authority: accounts.user.to_account_info(),
+				},
+			),
+			from_user.2,
+		)?;
+	}
+	if to_user.2 > 0 {
+		token::transfer(
+			CpiContext::new_with_signer(
+				accounts.token_program.to_account_info(),
+				token::Transfer {
+					from: to_user.0.to_account_info(),
+					to: to_user.1.to_account_info(),
+					authority: amm.to_account_info(),
+				},
+				&[&seeds],
+			),
+			to_user.2,
+		)?;
+	}
+
+	emit!(AmmSwap {
+		ticker_mint: amm.ticker_mint,
+		payment_mint: amm.payment_mint,
+
+		user: accounts.user.key(),
+		side,
+
+		quantity,
+		price,
+		reference_price: amm.reference_price,
+		payment,
+
+		timestamp: now,
+	});
+
+	Ok(())
+}
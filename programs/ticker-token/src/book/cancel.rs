@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{self, TokenAccount, Token},
+};
+use crate::{
+	errors::ErrorCode,
+	order::types::OrderSide,
+	book::state::*,
+};
+
+#[derive(Accounts)]
+pub struct CancelBookOrder<'info> {
+	pub owner: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"book", book.ticker_mint.as_ref(), book.payment_mint.as_ref()],
+		bump = book.bump,
+	)]
+	pub book: Box<Account<'info, OrderBook>>,
+
+	/// Куда возвращается залог: тикер для ask, платёжный токен для bid
+	#[account(
+		mut,
+		constraint = refund_account.owner == owner.key() @ ErrorCode::InvalidRefundOwner,
+	)]
+	pub refund_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"book_vault", book.key().as_ref(), book.ticker_mint.as_ref()], bump)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"book_vault", book.key().as_ref(), book.payment_mint.as_ref()], bump)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+/// Снимает заявку владельца из стакана и возвращает неиспользованный залог
+pub fn cancel(ctx: Context<CancelBookOrder>, side: OrderSide, order_id: u64) -> Result<()> {
+	let owner = ctx.accounts.owner.key();
+	let book = &mut ctx.accounts.book;
+
+	let orders = book.side_mut(side);
+	let index = orders.iter()
+		.position(|order| order.id == order_id)
+		.ok_or(ErrorCode::BookOrderNotFound)?;
+	require!(orders[index].owner == owner, ErrorCode::Unauthorized);
+	let order = orders.remove(index);
+
+	let (vault, mint) = match side {
+		OrderSide::Buy => (&ctx.accounts.payment_vault, book.payment_mint),
+		OrderSide::Sell => (&ctx.accounts.ticker_vault, book.ticker_mint),
+	};
+	require!(ctx.accounts.refund_account.mint == mint, ErrorCode::InvalidRefundMint);
+
+	let ticker_mint = book.ticker_mint;
+	let payment_mint = book.payment_mint;
+	let bump = [book.bump];
+	let seeds: [&[u8]; 4] = [b"book", ticker_mint.as_ref(), payment_mint.as_ref(), &bump];
+
+	if order.locked > 0 {
+		token::transfer(
+			CpiContext::new_with_signer(
+				ctx.accounts.token_program.to_account_info(),
+				token::Transfer {
+					from: vault.to_account_info(),
+					to: ctx.accounts.refund_account.to_account_info(),
+					authority: ctx.accounts.book.to_account_info(),
+				},
+				&[&seeds],
+			),
+			order.locked,
+		)?;
+	}
+
+	emit!(BookOrderCanceled {
+		ticker_mint,
+		payment_mint,
+
+		id: order.id,
+		owner,
+		side,
+		refunded: order.locked,
+
+		timestamp: Clock::get()?.unix_timestamp,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{self, TokenAccount, Token},
+};
+use crate::{
+	utils::assert_ata,
+	book::state::*,
+};
+
+#[derive(Accounts)]
+pub struct ConsumeBookEvents<'info> {
+	#[account(
+		mut,
+		seeds = [b"book", book.ticker_mint.as_ref(), book.payment_mint.as_ref()],
+		bump = book.bump,
+	)]
+	pub book: Box<Account<'info, OrderBook>>,
+
+	#[account(mut, seeds = [b"book_vault", book.key().as_ref(), book.ticker_mint.as_ref()], bump)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"book_vault", book.key().as_ref(), book.payment_mint.as_ref()], bump)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+
+	// remaining_accounts: для каждого события с начала очереди — [АТА тикера владельца (mut), АТА платёжного токена владельца (mut)]
+}
+
+/// ATA владельца может принять выплату: создан и не заморожен
+fn is_payable<'info>(account: &'info AccountInfo<'info>) -> bool {
+	Account::<TokenAccount>::try_from(account).is_ok_and(|account| !account.is_frozen())
+}
+
+/// Кранк без разрешений: выплачивает мейкерам причитающееся по первым `limit` событиям очереди.
+/// Событие, владелец которого не может принять выплату (нет ATA или он заморожен),
+/// пропускается и остаётся в очереди, не блокируя остальные.
+pub fn consume<'info>(ctx: Context<'_, '_, 'info, 'info, ConsumeBookEvents<'info>>, limit: u8) -> Result<()> {
+	let book = &ctx.accounts.book;
+	let count = (limit as usize)
+		.min(book.events.len())
+		.min(ctx.remaining_accounts.len() / 2);
+
+	let ticker_mint = book.ticker_mint;
+	let payment_mint = book.payment_mint;
+	let bump = [book.bump];
+	let seeds: [&[u8]; 4] = [b"book", ticker_mint.as_ref(), payment_mint.as_ref(), &bump];
+
+	let token_program = ctx.accounts.token_program.to_account_info();
+	let book_info = book.to_account_info();
+
+	let vault_transfer = |from: &Account<'info, TokenAccount>, to: &AccountInfo<'info>, amount: u64| -> Result<()> {
+		if amount == 0 {
+			return Ok(());
+		}
+		token::transfer(
+			CpiContext::new_with_signer(
+				token_program.clone(),
+				token::Transfer {
+					from: from.to_account_info(),
+					to: to.clone(),
+					authority: book_info.clone(),
+				},
+				&[&seeds],
+			),
+			amount,
+		)
+	};
+
+	let mut paid = Vec::with_capacity(count);
+	for (i, event) in book.events.iter().take(count).enumerate() {
+		let ticker_account = &ctx.remaining_accounts[i * 2];
+		let payment_account = &ctx.remaining_accounts[i * 2 + 1];
+
+		assert_ata(ticker_account.key(), &event.owner, &ticker_mint)?;
+		assert_ata(payment_account.key(), &event.owner, &payment_mint)?;
+
+		let payable = (event.ticker == 0 || is_payable(ticker_account))
+			&& (event.payment == 0 || is_payable(payment_account));
+		if payable {
+			vault_transfer(&ctx.accounts.ticker_vault, ticker_account, event.ticker)?;
+			vault_transfer(&ctx.accounts.payment_vault, payment_account, event.payment)?;
+		}
+		paid.push(payable);
+	}
+
+	// удаляем выплаченные события, пропущенные остаются в начале очереди в прежнем порядке
+	let book = &mut ctx.accounts.book;
+	let mut position = 0;
+	book.events.retain(|_| {
+		let keep = !paid.get(position).copied().unwrap_or(false);
+		position += 1;
+		keep
+	});
+
+	let consumed = paid.iter().filter(|payable| **payable).count();
+	emit!(BookEventsConsumed {
+		ticker_mint,
+		payment_mint,
+
+		consumed: consumed as u8,
+		skipped: (count - consumed) as u8,
+		remaining: book.events.len() as u8,
+	});
+
+	Ok(())
+}
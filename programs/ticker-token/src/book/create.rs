@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{TokenAccount, Token, Mint},
+};
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	price,
+	book::state::*,
+};
+
+#[derive(Accounts)]
+pub struct CreateBook<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	pub ticker_mint: Box<Account<'info, Mint>>,
+	pub payment_mint: Box<Account<'info, Mint>>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"book", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		space = OrderBook::SPACE,
+	)]
+	pub book: Box<Account<'info, OrderBook>>,
+
+	/// Залоги продавцов (ask) и тикер к выплате покупателям
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"book_vault", book.key().as_ref(), ticker_mint.key().as_ref()],
+		bump,
+		token::mint = ticker_mint,
+		token::authority = book,
+	)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	/// Залоги покупателей (bid) и оплата к выплате продавцам
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"book_vault", book.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		token::mint = payment_mint,
+		token::authority = book,
+	)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	/// Пул получает комиссии стакана
+	#[account(
+		init_if_needed,
+		payer = payer,
+		seeds = [b"pool", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		token::mint = payment_mint,
+		token::authority = payer,
+	)]
+	pub pool: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+	pub system_program: Program<'info, System>,
+}
+
+/// Открывает стакан вторичного рынка для пары (тикер, платёжный токен)
+pub fn create(ctx: Context<CreateBook>, maker_fee_bps: u16, taker_fee_bps: u16) -> Result<()> {
+	require!(
+		maker_fee_bps <= MAX_BOOK_FEE_BPS && taker_fee_bps <= MAX_BOOK_FEE_BPS,
+		ErrorCode::InvalidBookFee
+	);
+
+	let book = &mut ctx.accounts.book;
+	book.ticker_mint = ctx.accounts.ticker_mint.key();
+	book.payment_mint = ctx.accounts.payment_mint.key();
+	book.price_scale = price::price_scale(ctx.accounts.ticker_mint.decimals)?;
+	book.maker_fee_bps = maker_fee_bps;
+	book.taker_fee_bps = taker_fee_bps;
+	book.next_order_id = 0;
+	book.bids = Vec::new();
+	book.asks = Vec::new();
+	book.events = Vec::new();
+	book.bump = ctx.bumps.book;
+
+	emit!(BookCreated {
+		ticker_mint: book.ticker_mint,
+		payment_mint: book.payment_mint,
+
+		maker_fee_bps,
+		taker_fee_bps,
+	});
+
+	Ok(())
+}
@@ -0,0 +1,13 @@
+pub mod state;
+
+pub mod create;
+pub use create::*;
+
+pub mod place;
+pub use place::*;
+
+pub mod cancel;
+pub use cancel::*;
+
+pub mod consume;
+pub use consume::*;
@@ -0,0 +1,309 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{self, TokenAccount, Token},
+};
+use crate::{
+	errors::ErrorCode,
+	price::{self, Rounding},
+	order::types::OrderSide,
+	market::{Market, MarketStatus},
+	ticker::{TickerRecord, TickerStatus},
+	config::TickerConfig,
+	book::state::*,
+};
+
+#[derive(Accounts)]
+pub struct PlaceBookOrder<'info> {
+	#[account(mut)]
+	pub owner: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"book", book.ticker_mint.as_ref(), book.payment_mint.as_ref()],
+		bump = book.bump,
+	)]
+	pub book: Box<Account<'info, OrderBook>>,
+
+	/// Заявки в стакан принимаются только на открытом рынке пары
+	#[account(
+		seeds = [b"market", book.ticker_mint.as_ref(), book.payment_mint.as_ref()],
+		bump = market.bump,
+		constraint = market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	#[account(
+		seeds = [b"ticker", book.ticker_mint.as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
+	/// Минимальный размер и шаг заявки, чтобы сторону нельзя было забить пылью
+	#[account(seeds = [b"config", book.ticker_mint.as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Box<Account<'info, TickerConfig>>,
+
+	#[account(
+		mut,
+		constraint = owner_ticker_account.owner == owner.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = owner_ticker_account.mint == book.ticker_mint @ ErrorCode::InvalidTickerMint,
+	)]
+	pub owner_ticker_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		constraint = owner_payment_account.owner == owner.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = owner_payment_account.mint == book.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub owner_payment_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"book_vault", book.key().as_ref(), book.ticker_mint.as_ref()], bump)]
+	pub ticker_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"book_vault", book.key().as_ref(), book.payment_mint.as_ref()], bump)]
+	pub payment_vault: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"pool", book.ticker_mint.as_ref(), book.payment_mint.as_ref()], bump)]
+	pub pool: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+/// Итог исполнения входящей заявки против встречной стороны стакана
+#[derive(Default)]
+struct Matched {
+	filled: u64, // тикер, исполненный тейкером
+	cost: u64, // стоимость исполненного по ценам мейкеров
+	taker_fees: u64,
+	maker_fees: u64,
+}
+
+/// Исполняет входящую заявку против лучших встречных цен (price-time priority).
+/// Мейкерам причитающееся ставится в очередь событий, тейкер рассчитывается сразу.
+fn match_orders(book: &mut OrderBook, taker: Pubkey, taker_id: u64, side: OrderSide, limit: u64, quantity: u64, now: i64) -> Result<Matched> {
+	let mut matched = Matched::default();
+	let opposite = match side {
+		OrderSide::Buy => OrderSide::Sell,
+		OrderSide::Sell => OrderSide::Buy,
+	};
+
+	while matched.filled < quantity {
+		let Some(maker) = book.side(opposite).first().copied() else { break };
+		let crosses = match side {
+			OrderSide::Buy => maker.price <= limit,
+			OrderSide::Sell => maker.price >= limit,
+		};
+		if !crosses {
+			break;
+		}
+
+		let fill = (quantity - matched.filled).min(maker.quantity);
+		let cost = price::notional(fill, maker.price, book.price_scale, Rounding::Down)?;
+		let maker_fee = OrderBook::fee(cost, book.maker_fee_bps, Rounding::Down)?;
+		let taker_fee = OrderBook::fee(cost, book.taker_fee_bps, Rounding::Up)?;
+
+		let mut resting = maker;
+		resting.quantity -= fill;
+		let event = match side {
+			// мейкер продал: получает оплату за вычетом своей комиссии
+			OrderSide::Buy => {
+				resting.locked = resting.locked.checked_sub(fill).ok_or(ErrorCode::InsufficientEscrowBalance)?;
+				BookEvent { owner: maker.owner, order_id: maker.id, ticker: 0, payment: cost - maker_fee, timestamp: now }
+			}
+			// мейкер купил: получает тикер, а при полном исполнении — остаток залога
+			OrderSide::Sell => {
+				let charged = cost.checked_add(maker_fee).ok_or(ErrorCode::Overflow)?;
+				resting.locked = resting.locked.checked_sub(charged).ok_or(ErrorCode::InsufficientEscrowBalance)?;
+				let refund = if resting.quantity == 0 { resting.locked } else { 0 };
+				BookEvent { owner: maker.owner, order_id: maker.id, ticker: fill, payment: refund, timestamp: now }
+			}
+		};
+		book.push_event(event)?;
+
+		let orders = book.side_mut(opposite);
+		if resting.quantity == 0 {
+			orders.remove(0);
+		} else {
+			orders[0] = resting;
+		}
+
+		matched.filled += fill;
+		matched.cost = matched.cost.checked_add(cost).ok_or(ErrorCode::Overflow)?;
+		matched.taker_fees = matched.taker_fees.checked_add(taker_fee).ok_or(ErrorCode::Overflow)?;
+		matched.maker_fees = matched.maker_fees.checked_add(maker_fee).ok_or(ErrorCode::Overflow)?;
+
+		emit!(BookTrade {
+			ticker_mint: book.ticker_mint,
+			payment_mint: book.payment_mint,
+
+			maker_order_id: maker.id,
+			maker: maker.owner,
+			taker_order_id: taker_id,
+			taker,
+			taker_side: side,
+
+			price: maker.price,
+			quantity: fill,
+			cost,
+			maker_fee,
+			taker_fee,
+
+			timestamp: now,
+		});
+	}
+
+	Ok(matched)
+}
+
+/// Ставит остаток заявки в стакан. На заполненной стороне вытесняется худшая заявка,
+/// её залог возвращается владельцу через очередь событий.
+fn rest(book: &mut OrderBook, side: OrderSide, order: BookOrder) -> Result<()> {
+	let Some(evicted) = book.insert(side, order)? else {
+		return Ok(());
+	};
+
+	let (ticker, payment) = match side {
+		OrderSide::Buy => (0, evicted.locked),
+		OrderSide::Sell => (evicted.locked, 0),
+	};
+	book.push_event(BookEvent { owner: evicted.owner, order_id: evicted.id, ticker, payment, timestamp: order.timestamp })?;
+
+	emit!(BookOrderEvicted {
+		ticker_mint: book.ticker_mint,
+		payment_mint: book.payment_mint,
+
+		id: evicted.id,
+		owner: evicted.owner,
+		side,
+		refunded: evicted.locked,
+
+		by_order_id: order.id,
+		timestamp: order.timestamp,
+	});
+
+	Ok(())
+}
+
+/// Выставляет лимитную заявку в стакан. Пересекающаяся часть сразу исполняется
+/// по ценам встречных заявок, остаток встаёт в стакан с залогом в хранилище стакана.
+pub fn place<'info>(ctx: Context<'_, '_, '_, 'info, PlaceBookOrder<'info>>, side: OrderSide, price: u64, quantity: u64) -> Result<()> {
+	require!(price > 0, ErrorCode::InvalidPrice);
+	require!(quantity > 0, ErrorCode::InvalidFillAmount);
+
+	// стакан не идёт к брокеру и торгует и вне торговой сессии
+	let now = Clock::get()?.unix_timestamp;
+
+	let owner = ctx.accounts.owner.key();
+	let book = &mut ctx.accounts.book;
+
+	let config = &ctx.accounts.ticker_config;
+	config.check_quantity(quantity)?;
+	config.check_tick(price)?;
+	config.check_notional(price::notional(quantity, price, book.price_scale, Rounding::Down)?)?;
+
+	let id = book.next_order_id;
+	book.next_order_id = id.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+	// залог считается до исполнения, чтобы остаток гарантированно покрывался
+	let bid_lock = match side {
+		OrderSide::Buy => book.bid_lock(quantity, price)?,
+		OrderSide::Sell => 0,
+	};
+
+	let matched = match_orders(book, owner, id, side, price, quantity, now)?;
+	let remaining = quantity - matched.filled;
+
+	// тейкер-покупатель платит стоимость и комиссию; продавец платит комиссию из выручки
+	let (deposit, taker_payment, taker_ticker) = match side {
+		OrderSide::Buy => {
+			let paid = matched.cost.checked_add(matched.taker_fees).ok_or(ErrorCode::Overflow)?;
+			let locked = if remaining > 0 {
+				bid_lock.checked_sub(paid).ok_or(ErrorCode::InsufficientEscrowBalance)?
+			} else {
+				0
+			};
+			if remaining > 0 {
+				rest(book, side, BookOrder { id, owner, price, quantity: remaining, locked, timestamp: now })?;
+			}
+			(paid + locked, 0, matched.filled)
+		}
+		OrderSide::Sell => {
+			let taker_fees = matched.taker_fees.min(matched.cost);
+			if remaining > 0 {
+				rest(book, side, BookOrder { id, owner, price, quantity: remaining, locked: remaining, timestamp: now })?;
+			}
+			(quantity, matched.cost - taker_fees, 0)
+		}
+	};
+	let fees = match side {
+		OrderSide::Buy => matched.taker_fees + matched.maker_fees,
+		OrderSide::Sell => matched.taker_fees.min(matched.cost) + matched.maker_fees,
+	};
+
+	let ticker_mint = book.ticker_mint;
+	let payment_mint = book.payment_mint;
+	let bump = [book.bump];
+	let seeds: [&[u8]; 4] = [b"book", ticker_mint.as_ref(), payment_mint.as_ref(), &bump];
+
+	let token_program = ctx.accounts.token_program.to_account_info();
+	let book_info = ctx.accounts.book.to_account_info();
+
+	// залог тейкера: платёжный токен для bid, тикер для ask
+	let (from, to) = match side {
+		OrderSide::Buy => (&ctx.accounts.owner_payment_account, &ctx.accounts.payment_vault),
+		OrderSide::Sell => (&ctx.accounts.owner_ticker_account, &ctx.accounts.ticker_vault),
+	};
+	if deposit > 0 {
+		token::transfer(
+			CpiContext::new(
+				token_program.clone(),
+				token::Transfer {
+					from: from.to_account_info(),
+					to: to.to_account_info(),
+					authority: ctx.accounts.owner.to_account_info(),
+				},
+			),
+			deposit,
+		)?;
+	}
+
+	let vault_transfer = |from: &Account<'info, TokenAccount>, to: AccountInfo<'info>, amount: u64| -> Result<()> {
+		if amount == 0 {
+			return Ok(());
+		}
+		token::transfer(
+			CpiContext::new_with_signer(
+				token_program.clone(),
+				token::Transfer {
+					from: from.to_account_info(),
+					to,
+					authority: book_info.clone(),
+				},
+				&[&seeds],
+			),
+			amount,
+		)
+	};
+
+	vault_transfer(&ctx.accounts.ticker_vault, ctx.accounts.owner_ticker_account.to_account_info(), taker_ticker)?;
+	vault_transfer(&ctx.accounts.payment_vault, ctx.accounts.owner_payment_account.to_account_info(), taker_payment)?;
+	vault_transfer(&ctx.accounts.payment_vault, ctx.accounts.pool.to_account_info(), fees)?;
+
+	emit!(BookOrderPlaced {
+		ticker_mint,
+		payment_mint,
+
+		id,
+		owner,
+		side,
+		price,
+
+		quantity,
+		filled: matched.filled,
+		resting: remaining,
+
+		timestamp: now,
+	});
+
+	Ok(())
+}
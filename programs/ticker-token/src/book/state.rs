@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+use crate::{
+    errors::ErrorCode,
+    price::{self, Rounding},
+    order::types::OrderSide,
+};
+
+/// Максимум заявок на каждой стороне стакана
+pub const MAX_BOOK_ORDERS: usize = 32;
+
+/// Максимум необработанных событий в очереди стакана
+pub const MAX_BOOK_EVENTS: usize = 32;
+
+/// Максимальная комиссия стакана, bps
+pub const MAX_BOOK_FEE_BPS: u16 = 1_000;
+
+/// Заявка в стакане
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BookOrder {
+    pub id: u64,
+    pub owner: Pubkey,
+
+    pub price: u64, // в платёжном токене за price_scale базовых единиц тикера
+    pub quantity: u64, // неисполненный остаток тикера
+    pub locked: u64, // остаток залога: платёжный токен для bid, тикер для ask
+
+    pub timestamp: i64,
+}
+
+impl BookOrder {
+    pub const SIZE: usize = 8 + 32 + 8 + 8 + 8 + 8;
+}
+
+/// Что причитается мейкеру после сделки; выплачивается кранком `consume_events`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BookEvent {
+    pub owner: Pubkey,
+    pub order_id: u64,
+
+    pub ticker: u64, // тикер к выплате (исполненный bid)
+    pub payment: u64, // платёжный токен к выплате (исполненный ask, остаток залога bid)
+
+    pub timestamp: i64,
+}
+
+impl BookEvent {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 8;
+}
+
+/// Стакан заявок рынка (PDA `['book', ticker_mint, payment_mint]`).
+/// Заявки упорядочены по цене, при равной цене — по времени (price-time priority).
+#[account]
+pub struct OrderBook {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+    pub price_scale: u64,
+
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+
+    pub next_order_id: u64,
+
+    pub bids: Vec<BookOrder>, // по убыванию цены
+    pub asks: Vec<BookOrder>, // по возрастанию цены
+    pub events: Vec<BookEvent>, // очередь выплат мейкерам
+
+    pub bump: u8,
+}
+
+impl OrderBook {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // ticker_mint
+        + 32 // payment_mint
+        + 8 // price_scale
+        + 2 // maker_fee_bps
+        + 2 // taker_fee_bps
+        + 8 // next_order_id
+        + 4 + BookOrder::SIZE * MAX_BOOK_ORDERS // bids
+        + 4 + BookOrder::SIZE * MAX_BOOK_ORDERS // asks
+        + 4 + BookEvent::SIZE * MAX_BOOK_EVENTS // events
+        + 1; // bump
+
+    pub fn side(&self, side: OrderSide) -> &Vec<BookOrder> {
+        match side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        }
+    }
+
+    pub fn side_mut(&mut self, side: OrderSide) -> &mut Vec<BookOrder> {
+        match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        }
+    }
+
+    /// Ставит заявку после всех заявок с той же или лучшей ценой. На заполненной стороне
+    /// заявка с лучшей ценой вытесняет последнюю (худшую по цене, затем самую позднюю) и возвращает её.
+    pub fn insert(&mut self, side: OrderSide, order: BookOrder) -> Result<Option<BookOrder>> {
+        let orders = self.side_mut(side);
+
+        let position = orders.iter()
+            .position(|other| match side {
+                OrderSide::Buy => other.price < order.price,
+                OrderSide::Sell => other.price > order.price,
+            })
+            .unwrap_or(orders.len());
+
+        // не лучше худшей заявки — места нет
+        let evicted = if orders.len() < MAX_BOOK_ORDERS {
+            None
+        } else {
+            require!(position < orders.len(), ErrorCode::BookFull);
+            orders.pop()
+        };
+
+        orders.insert(position, order);
+        Ok(evicted)
+    }
+
+    pub fn push_event(&mut self, event: BookEvent) -> Result<()> {
+        require!(self.events.len() < MAX_BOOK_EVENTS, ErrorCode::EventQueueFull);
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Комиссия стакана с суммы сделки
+    pub fn fee(amount: u64, bps: u16, rounding: Rounding) -> Result<u64> {
+        price::mul_div(amount, bps as u64, 10_000, rounding)
+    }
+
+    /// Залог bid: стоимость по лимитной цене + максимальная из комиссий
+    pub fn bid_lock(&self, quantity: u64, price: u64) -> Result<u64> {
+        let cost = price::notional(quantity, price, self.price_scale, Rounding::Up)?;
+        let fee = Self::fee(cost, self.maker_fee_bps.max(self.taker_fee_bps), Rounding::Up)?;
+        cost.checked_add(fee).ok_or(error!(ErrorCode::Overflow))
+    }
+}
+
+#[event]
+pub struct BookCreated {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+}
+
+#[event]
+pub struct BookOrderPlaced {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub id: u64,
+    pub owner: Pubkey,
+    pub side: OrderSide,
+    pub price: u64,
+
+    pub quantity: u64, // запрошено
+    pub filled: u64, // исполнено сразу как тейкер
+    pub resting: u64, // осталось в стакане
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BookTrade {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub maker_order_id: u64,
+    pub maker: Pubkey,
+    pub taker_order_id: u64,
+    pub taker: Pubkey,
+    pub taker_side: OrderSide,
+
+    pub price: u64, // цена мейкера
+    pub quantity: u64,
+    pub cost: u64,
+    pub maker_fee: u64,
+    pub taker_fee: u64,
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BookOrderCanceled {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub id: u64,
+    pub owner: Pubkey,
+    pub side: OrderSide,
+    pub refunded: u64,
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BookOrderEvicted {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub id: u64,
+    pub owner: Pubkey,
+    pub side: OrderSide,
+    pub refunded: u64, // залог, поставленный в очередь событий
+
+    pub by_order_id: u64, // вытеснившая заявка
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BookEventsConsumed {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub consumed: u8,
+    pub skipped: u8, // события, чьи ATA владельцев не могут принять выплату; остаются в очереди
+    pub remaining: u8,
+}
@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    Registry,
+    errors::ErrorCode,
+};
+
+/// Максимум праздничных дней в календаре
+pub const MAX_HOLIDAYS: usize = 64;
+/// Максимум сокращённых сессий в календаре
+pub const MAX_EARLY_CLOSES: usize = 16;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const MINUTES_PER_DAY: u16 = 1_440;
+/// Допустимое смещение часового пояса, сек
+const MAX_UTC_OFFSET: i32 = 14 * 3_600;
+/// На сколько дней вперёд искать следующую сессию
+const SESSION_LOOKAHEAD_DAYS: i64 = 14;
+
+/// Сокращённая сессия: в день `day` биржа закрывается раньше
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct EarlyClose {
+    pub day: i32, // местная дата, дней с 1970-01-01
+    pub close_minute: u16,
+}
+
+impl EarlyClose {
+    pub const SIZE: usize = 4 + 2;
+}
+
+/// Торговый календарь биржи, PDA ['calendar']. Создаётся в `init_calendar` круглосуточным,
+/// расписание задаёт администратор. Переход на летнее время — сменой `utc_offset`.
+#[account]
+pub struct MarketCalendar {
+    pub weekdays: u8, // маска торговых дней: бит 0 — понедельник, ..., бит 6 — воскресенье
+    pub open_minute: u16, // открытие, минут от местной полуночи
+    pub close_minute: u16, // закрытие, минут от местной полуночи
+    pub utc_offset: i32, // смещение местного времени от UTC, сек
+
+    pub holidays: Vec<i32>, // местные даты без торгов, дней с 1970-01-01
+    pub early_closes: Vec<EarlyClose>,
+
+    pub bump: u8,
+}
+
+impl MarketCalendar {
+    pub const SPACE: usize = 8 // discriminator
+        + 1 // weekdays
+        + 2 // open_minute
+        + 2 // close_minute
+        + 4 // utc_offset
+        + 4 + 4 * MAX_HOLIDAYS // holidays
+        + 4 + EarlyClose::SIZE * MAX_EARLY_CLOSES // early_closes
+        + 1; // bump
+
+    /// Круглосуточно без выходных
+    pub fn reset(&mut self, bump: u8) {
+        self.weekdays = 0x7f;
+        self.open_minute = 0;
+        self.close_minute = MINUTES_PER_DAY;
+        self.utc_offset = 0;
+        self.holidays = Vec::new();
+        self.early_closes = Vec::new();
+        self.bump = bump;
+    }
+
+    fn local_day(&self, now: i64) -> i64 {
+        (now + self.utc_offset as i64).div_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Сессия местного дня `day` в UTC: `[open, close)`, если день торговый
+    pub fn session(&self, day: i64) -> Option<(i64, i64)> {
+        // 1970-01-01 — четверг
+        let weekday = (day + 3).rem_euclid(7);
+        if self.weekdays & (1 << weekday) == 0 || self.holidays.contains(&(day as i32)) {
+            return None;
+        }
+
+        let close_minute = self.early_closes.iter()
+            .find(|early| early.day as i64 == day)
+            .map_or(self.close_minute, |early| early.close_minute);
+
+        let midnight = day * SECONDS_PER_DAY - self.utc_offset as i64;
+        Some((midnight + self.open_minute as i64 * 60, midnight + close_minute as i64 * 60))
+    }
+
+    pub fn is_open(&self, now: i64) -> bool {
+        self.session(self.local_day(now))
+            .is_some_and(|(open, close)| open <= now && now < close)
+    }
+
+    /// Закрытие текущей или ближайшей следующей сессии
+    pub fn next_close(&self, now: i64) -> Result<i64> {
+        let today = self.local_day(now);
+        (today..=today + SESSION_LOOKAHEAD_DAYS)
+            .filter_map(|day| self.session(day))
+            .map(|(_, close)| close)
+            .find(|close| *close > now)
+            .ok_or(error!(ErrorCode::MarketClosed))
+    }
+}
+
+#[event]
+pub struct CalendarUpdated {
+    pub weekdays: u8,
+    pub open_minute: u16,
+    pub close_minute: u16,
+    pub utc_offset: i32,
+
+    pub holidays: Vec<i32>,
+    pub early_closes: Vec<EarlyClose>,
+}
+
+#[derive(Accounts)]
+pub struct InitCalendar<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init,
+        seeds = [b"calendar"],
+        bump,
+        payer = authority,
+        space = MarketCalendar::SPACE,
+    )]
+    pub calendar: Account<'info, MarketCalendar>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCalendar<'info> {
+    #[account(constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(mut, seeds = [b"calendar"], bump = calendar.bump)]
+    pub calendar: Account<'info, MarketCalendar>,
+}
+
+pub fn init(ctx: Context<InitCalendar>) -> Result<()> {
+    let calendar = &mut ctx.accounts.calendar;
+    calendar.reset(ctx.bumps.calendar);
+
+    emit_updated(calendar)
+}
+
+pub fn set_session(ctx: Context<ConfigureCalendar>, weekdays: u8, open_minute: u16, close_minute: u16, utc_offset: i32) -> Result<()> {
+    require!(weekdays & 0x80 == 0, ErrorCode::InvalidCalendar);
+    require!(open_minute < close_minute && close_minute <= MINUTES_PER_DAY, ErrorCode::InvalidCalendar);
+    require!(utc_offset.abs() <= MAX_UTC_OFFSET, ErrorCode::InvalidCalendar);
+
+    let calendar = &mut ctx.accounts.calendar;
+    calendar.weekdays = weekdays;
+    calendar.open_minute = open_minute;
+    calendar.close_minute = close_minute;
+    calendar.utc_offset = utc_offset;
+
+    // сокращённые сессии должны оставаться внутри новой сессии
+    for early in calendar.early_closes.iter() {
+        require!(
+            early.close_minute > open_minute && early.close_minute < close_minute,
+            ErrorCode::InvalidCalendar
+        );
+    }
+
+    emit_updated(calendar)
+}
+
+/// Полностью заменяет списки праздников и сокращённых сессий
+pub fn set_holidays(ctx: Context<ConfigureCalendar>, holidays: Vec<i32>, early_closes: Vec<EarlyClose>) -> Result<()> {
+    require!(
+        holidays.len() <= MAX_HOLIDAYS && early_closes.len() <= MAX_EARLY_CLOSES,
+        ErrorCode::InvalidCalendar
+    );
+
+    let calendar = &mut ctx.accounts.calendar;
+    for early in early_closes.iter() {
+        require!(
+            early.close_minute > calendar.open_minute && early.close_minute < calendar.close_minute,
+            ErrorCode::InvalidCalendar
+        );
+    }
+
+    calendar.holidays = holidays;
+    calendar.early_closes = early_closes;
+
+    emit_updated(calendar)
+}
+
+fn emit_updated(calendar: &MarketCalendar) -> Result<()> {
+    emit!(CalendarUpdated {
+        weekdays: calendar.weekdays,
+        open_minute: calendar.open_minute,
+        close_minute: calendar.close_minute,
+        utc_offset: calendar.utc_offset,
+
+        holidays: calendar.holidays.clone(),
+        early_closes: calendar.early_closes.clone(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::Mint;
+
+use crate::{
+    Registry,
+    errors::ErrorCode,
+    price::{self, Rounding},
+    feed::PriceFeed,
+    order::types::{OrderPayload, OrderMode},
+};
+
+/// Настройки торговли тикером, PDA ['config', ticker_mint]. Создаётся вместе с тикером.
+#[account]
+pub struct TickerConfig {
+    pub ticker_mint: Pubkey,
+
+    pub band_bps: u16, // коридор limit-up/limit-down от цены фида, 0 — без коридора
+
+    // ограничения размера заявки, 0 — без ограничения
+    pub min_quantity: u64, // минимальное количество тикера, базовых единиц
+    pub quantity_step: u64, // шаг количества (лот, доли акции)
+    pub price_tick: u64, // шаг лимитной цены
+    pub min_notional: u64, // минимальная сумма заявки в платёжном токене
+
+    pub bump: u8,
+}
+
+impl TickerConfig {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // ticker_mint
+        + 2 // band_bps
+        + 8 // min_quantity
+        + 8 // quantity_step
+        + 8 // price_tick
+        + 8 // min_notional
+        + 1; // bump
+
+    /// Коридор цен и ограничения заявок выключены, пока не заданы администратором
+    pub fn reset(&mut self, ticker_mint: Pubkey, bump: u8) {
+        self.ticker_mint = ticker_mint;
+        self.band_bps = 0;
+        self.min_quantity = 0;
+        self.quantity_step = 0;
+        self.price_tick = 0;
+        self.min_notional = 0;
+        self.bump = bump;
+    }
+
+    /// Цена в коридоре `[ref * (1 - band), ref * (1 + band)]` вокруг цены фида.
+    /// При выходе за коридор возвращается `PriceOutsideBand`.
+    pub fn check_band(&self, feed: Option<&PriceFeed>, price: u64, payment_decimals: u8, now: i64) -> Result<()> {
+        if self.band_bps == 0 {
+            return Ok(());
+        }
+
+        let feed = feed.ok_or(ErrorCode::PriceFeedRequired)?;
+        let reference = feed.current(now, payment_decimals)?;
+
+        let band = self.band_bps as u64;
+        let lower = price::mul_div(reference, 10_000u64.saturating_sub(band), 10_000, Rounding::Up)?;
+        let upper = price::mul_div(reference, 10_000 + band, 10_000, Rounding::Down)?;
+
+        require!(price >= lower && price <= upper, ErrorCode::PriceOutsideBand);
+
+        Ok(())
+    }
+
+    /// Цена исполнения: в коридоре и не дальше `max_deviation_bps` от цены фида
+    pub fn check_price(&self, feed: &PriceFeed, price: u64, payment_decimals: u8, now: i64) -> Result<()> {
+        self.check_band(Some(feed), price, payment_decimals, now)?;
+        feed.check_quote(price, payment_decimals, now)
+    }
+}
+
+impl TickerConfig {
+    /// Размер и цена заявки при создании. `amount` — фактически блокируемый объём
+    /// (для «продать всё» — баланс мейкера, к нему ограничения не применяются).
+    pub fn check_order(&self, payload: &OrderPayload, amount: u64, price_scale: u64) -> Result<()> {
+        if !payload.market {
+            self.check_tick(payload.price)?;
+        }
+
+        match payload.mode {
+            OrderMode::Quantity => {
+                self.check_quantity(amount)?;
+                // сумма рыночной заявки неизвестна до исполнения
+                if !payload.market {
+                    self.check_notional(price::notional(amount, payload.price, price_scale, Rounding::Down)?)?;
+                }
+            }
+            OrderMode::Notional => self.check_notional(amount)?,
+            OrderMode::All => {}
+        }
+
+        Ok(())
+    }
+
+    /// Количество тикера не меньше минимума и кратно шагу
+    pub fn check_quantity(&self, quantity: u64) -> Result<()> {
+        require!(quantity >= self.min_quantity, ErrorCode::OrderBelowMinimum);
+        if self.quantity_step > 0 {
+            require!(quantity % self.quantity_step == 0, ErrorCode::InvalidQuantityStep);
+        }
+        Ok(())
+    }
+
+    /// Сумма заявки в платёжном токене не меньше минимальной
+    pub fn check_notional(&self, notional: u64) -> Result<()> {
+        require!(notional >= self.min_notional, ErrorCode::OrderBelowMinimum);
+        Ok(())
+    }
+
+    /// Лимитная цена кратна шагу цены
+    pub fn check_tick(&self, price: u64) -> Result<()> {
+        if self.price_tick > 0 {
+            require!(price % self.price_tick == 0, ErrorCode::InvalidPriceTick);
+        }
+        Ok(())
+    }
+}
+
+#[event]
+pub struct PriceBandConfigured {
+    pub ticker_mint: Pubkey,
+
+    pub band_bps: u16,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTicker<'info> {
+    #[account(constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"config", config.ticker_mint.as_ref()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, TickerConfig>,
+}
+
+/// Настройки для тикера, созданного до появления `TickerConfig`
+#[derive(Accounts)]
+#[instruction(symbol: String)]
+pub struct InitTickerConfig<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(seeds = [b"mint", symbol.as_bytes()], bump)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [b"config", mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = TickerConfig::SPACE,
+    )]
+    pub config: Account<'info, TickerConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init(ctx: Context<InitTickerConfig>) -> Result<()> {
+    ctx.accounts.config.reset(ctx.accounts.mint.key(), ctx.bumps.config);
+    Ok(())
+}
+
+/// Размер `TickerConfig` до появления ограничений заявок: ticker_mint, band_bps, bump
+const LEGACY_SPACE: usize = 8 + 32 + 2 + 1;
+
+/// Расширение `TickerConfig`, созданного до появления ограничений заявок
+#[derive(Accounts)]
+pub struct MigrateTickerConfig<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    /// CHECK: старая раскладка не читается как `TickerConfig`, PDA и дискриминатор проверяются в `migrate`
+    #[account(mut, owner = crate::ID @ ErrorCode::InvalidLegacyAccount)]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Дописывает ограничения заявок (выключенными) и доплачивает ренту за новый размер
+pub fn migrate(ctx: Context<MigrateTickerConfig>) -> Result<()> {
+    let info = ctx.accounts.config.to_account_info();
+
+    let (ticker_mint, band_bps, bump) = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() == LEGACY_SPACE && data[..8] == *TickerConfig::DISCRIMINATOR,
+            ErrorCode::InvalidLegacyAccount
+        );
+        let ticker_mint = Pubkey::try_from(&data[8..40]).map_err(|_| error!(ErrorCode::InvalidLegacyAccount))?;
+        (ticker_mint, u16::from_le_bytes([data[40], data[41]]), data[42])
+    };
+
+    let expected = Pubkey::create_program_address(&[b"config", ticker_mint.as_ref(), &[bump]], &crate::ID)
+        .map_err(|_| error!(ErrorCode::InvalidPDA))?;
+    require!(info.key() == expected, ErrorCode::InvalidPDA);
+
+    let rent = Rent::get()?.minimum_balance(TickerConfig::SPACE).saturating_sub(info.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    info.resize(TickerConfig::SPACE)?;
+
+    // ограничения заявок выключены, коридор сохраняется
+    let config = TickerConfig {
+        ticker_mint,
+        band_bps,
+        min_quantity: 0,
+        quantity_step: 0,
+        price_tick: 0,
+        min_notional: 0,
+        bump,
+    };
+
+    let mut data = info.try_borrow_mut_data()?;
+    config.try_serialize(&mut &mut data[..])
+}
+
+pub fn set_price_band(ctx: Context<ConfigureTicker>, band_bps: u16) -> Result<()> {
+    // коридор шире 100% снизу не ограничивает, сверху теряет смысл
+    require!(band_bps <= 10_000, ErrorCode::InvalidPriceBand);
+
+    let config = &mut ctx.accounts.config;
+    config.band_bps = band_bps;
+
+    emit!(PriceBandConfigured {
+        ticker_mint: config.ticker_mint,
+
+        band_bps,
+    });
+
+    Ok(())
+}
+
+pub fn set_order_limits(
+    ctx: Context<ConfigureTicker>,
+    min_quantity: u64,
+    quantity_step: u64,
+    price_tick: u64,
+    min_notional: u64,
+) -> Result<()> {
+    // минимум должен быть кратен шагу, иначе минимальная заявка невозможна
+    if quantity_step > 0 {
+        require!(min_quantity % quantity_step == 0, ErrorCode::InvalidQuantityStep);
+    }
+
+    let config = &mut ctx.accounts.config;
+    config.min_quantity = min_quantity;
+    config.quantity_step = quantity_step;
+    config.price_tick = price_tick;
+    config.min_notional = min_notional;
+
+    Ok(())
+}
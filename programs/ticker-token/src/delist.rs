@@ -0,0 +1,238 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, TokenAccount, Token, Mint};
+
+use crate::{
+    Registry,
+    errors::ErrorCode,
+    price::{self, Rounding},
+    ticker::{TickerRecord, TickerStatus},
+    market::{Market, MarketStatus},
+};
+
+#[event]
+pub struct TickerDelisted {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub final_price: u64,
+    pub supply: u64,
+    pub funded: u64, // переведено из пула в расчётный счёт
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TickerRedeemed {
+    pub ticker_mint: Pubkey,
+    pub holder: Pubkey,
+    pub executor: Pubkey, // держатель или его делегат (крэнк)
+
+    pub burned: u64,
+    pub paid: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Accounts)]
+pub struct DelistTicker<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(mut)]
+    pub ticker_mint: Box<Account<'info, Mint>>,
+
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticker", ticker_mint.key().as_ref()],
+        bump = record.bump,
+        constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+    )]
+    pub record: Box<Account<'info, TickerRecord>>,
+
+    /// Рынок, за платёжный токен которого выкупается тикер; закрывается для новых заявок
+    #[account(
+        mut,
+        seeds = [b"market", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(mut, seeds = [b"pool", ticker_mint.key().as_ref(), payment_mint.key().as_ref()], bump)]
+    pub pool: Box<Account<'info, TokenAccount>>,
+
+    /// Расчётный счёт, из которого держатели получают финальную выплату
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"settlement", ticker_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = record,
+    )]
+    pub settlement_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemTicker<'info> {
+    /// Держатель или делегат его тикер-аккаунта
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub ticker_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [b"ticker", ticker_mint.key().as_ref()],
+        bump = record.bump,
+        constraint = record.status == TickerStatus::Delisted @ ErrorCode::TickerNotDelisted,
+    )]
+    pub record: Box<Account<'info, TickerRecord>>,
+
+    #[account(mut, constraint = holder_account.mint == ticker_mint.key())]
+    pub holder_account: Box<Account<'info, TokenAccount>>,
+
+    /// Выплата идёт только на счёт владельца тикер-аккаунта
+    #[account(
+        mut,
+        constraint = holder_payment_account.owner == holder_account.owner @ ErrorCode::Unauthorized,
+        constraint = holder_payment_account.mint == record.payment_mint,
+    )]
+    pub holder_payment_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"settlement", ticker_mint.key().as_ref()], bump)]
+    pub settlement_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Закрывается только рынок `payment_mint`: рынки тикера за другие платёжные токены и его AMM-пулы
+/// остаются `Open`, торговлю по ним блокирует единственный общий замок — статус `Delisted` в `TickerRecord`,
+/// поэтому каждая инструкция, открывающая или исполняющая сделку по тикеру, обязана проверять `record`
+pub fn delist(ctx: Context<DelistTicker>, final_price: u64) -> Result<()> {
+    require!(final_price > 0, ErrorCode::InvalidFinalPrice);
+    let now = Clock::get()?.unix_timestamp;
+
+    // новые заявки на рынке больше не принимаются
+    let market = &mut ctx.accounts.market;
+    market.status = MarketStatus::Closed;
+    let price_scale = market.price_scale;
+
+    // весь выпуск выкупается по финальной цене, округление в пользу держателей
+    let supply = ctx.accounts.ticker_mint.supply;
+    let funded = price::notional(supply, final_price, price_scale, Rounding::Up)?;
+    require!(ctx.accounts.pool.amount >= funded, ErrorCode::InsufficientPoolBalance);
+
+    if funded > 0 {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.pool.to_account_info(),
+                to: ctx.accounts.settlement_vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, funded)?;
+    }
+
+    // mint замораживается: выпуск прекращается навсегда, а счета держателей больше нельзя
+    // заморозить, чтобы погашение всегда оставалось доступным; supply дальше только уменьшается в redeem
+    let ticker_mint = &ctx.accounts.ticker_mint;
+    let authorities = [
+        (AuthorityType::MintTokens, ticker_mint.mint_authority.is_some()),
+        (AuthorityType::FreezeAccount, ticker_mint.freeze_authority.is_some()),
+    ];
+    for (authority_type, _) in authorities.into_iter().filter(|(_, set)| *set) {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::SetAuthority {
+                account_or_mint: ctx.accounts.ticker_mint.to_account_info(),
+                current_authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token::set_authority(cpi_ctx, authority_type, None)?;
+    }
+
+    let record = &mut ctx.accounts.record;
+    record.status = TickerStatus::Delisted;
+    record.payment_mint = ctx.accounts.payment_mint.key();
+    record.final_price = final_price;
+    record.price_scale = price_scale;
+    record.delisted_at = now;
+
+    emit!(TickerDelisted {
+        ticker_mint: record.mint,
+        payment_mint: record.payment_mint,
+
+        final_price,
+        supply,
+        funded,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+pub fn redeem(ctx: Context<RedeemTicker>) -> Result<()> {
+    let payer = ctx.accounts.payer.key();
+    let holder = &ctx.accounts.holder_account;
+
+    // владелец погашает весь баланс, делегат — не больше разрешённого
+    let burned = if holder.owner == payer {
+        holder.amount
+    } else if holder.delegate == COption::Some(payer) {
+        holder.delegated_amount.min(holder.amount)
+    } else {
+        return err!(ErrorCode::Unauthorized);
+    };
+    require!(burned > 0, ErrorCode::NothingToRedeem);
+
+    let record = &ctx.accounts.record;
+    let paid = price::notional(burned, record.final_price, record.price_scale, Rounding::Down)?;
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        token::Burn {
+            mint: ctx.accounts.ticker_mint.to_account_info(),
+            from: ctx.accounts.holder_account.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        },
+    );
+    token::burn(cpi_ctx, burned)?;
+
+    if paid > 0 {
+        let mint = ctx.accounts.ticker_mint.key();
+        let seeds: &[&[&[u8]]] = &[&[b"ticker", mint.as_ref(), &[record.bump]]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.settlement_vault.to_account_info(),
+                to: ctx.accounts.holder_payment_account.to_account_info(),
+                authority: ctx.accounts.record.to_account_info(),
+            },
+            seeds,
+        );
+        token::transfer(cpi_ctx, paid)?;
+    }
+
+    emit!(TickerRedeemed {
+        ticker_mint: record.mint,
+        holder: ctx.accounts.holder_account.owner,
+        executor: payer,
+
+        burned,
+        paid,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
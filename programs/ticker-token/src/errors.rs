@@ -10,6 +10,18 @@ pub enum TickerError {
     InvalidAuthority,
     #[msg("Invalid metadata PDA")]
     InvalidMetadataPda,
+    #[msg("Too many oracle keys")]
+    TooManyOracles,
+    #[msg("Oracle threshold must be between 1 and the number of oracles")]
+    InvalidOracleThreshold,
+    #[msg("Oracle key must not be zero")]
+    InvalidOracleKey,
+    #[msg("Duplicate oracle key")]
+    DuplicateOracle,
+    #[msg("Retention period must not be negative")]
+    InvalidRetention,
+    #[msg("Broker key must not be zero")]
+    InvalidBrokerKey,
 }
 
 #[error_code]
@@ -56,12 +68,24 @@ pub enum ErrorCode {
     #[msg("Invalid order type")]
     InvalidOrderType,
 
+    #[msg("Invalid order mode")]
+    InvalidOrderMode,
+
     #[msg("Insufficient funds")]
     InsufficientFunds,
 
     #[msg("Order already processed")]
     OrderAlreadyProcessed,
 
+    #[msg("Invalid order status transition")]
+    InvalidStatusTransition,
+
+    #[msg("Order has not expired yet")]
+    OrderNotExpired,
+
+    #[msg("Order record retention period has not elapsed")]
+    RetentionNotElapsed,
+
     #[msg("Invalid escrow mint")]
     InvalidEscrowMint,
 
@@ -107,5 +131,161 @@ pub enum ErrorCode {
     #[msg("Insufficient escrow balance")]
     InsufficientEscrowBalance,
 
+    #[msg("Not enough valid oracle signatures")]
+    OracleQuorumNotReached,
+
+    #[msg("Execution report does not match the order")]
+    InvalidExecutionReport,
+
+    #[msg("Execution report timestamp is in the future")]
+    InvalidReportTimestamp,
+
+    #[msg("Invalid filled amount")]
+    InvalidFillAmount,
+
+    #[msg("Proof CID is malformed or too long")]
+    InvalidProofCid,
+
+    #[msg("Invalid price")]
+    InvalidPrice,
+
+    #[msg("Fill price violates the order limit")]
+    LimitPriceViolated,
+
+    #[msg("Invalid order trigger")]
+    InvalidTrigger,
+
+    #[msg("Trigger price has not been reached")]
+    TriggerNotReached,
+
+    #[msg("Price attestation is missing, stale or does not match the order")]
+    InvalidPriceAttestation,
+
+    #[msg("Invalid recurring plan parameters")]
+    InvalidPlan,
+
+    #[msg("Recurring plan is not active")]
+    PlanNotActive,
+
+    #[msg("Next plan period has not started yet")]
+    PlanNotDue,
+
+    #[msg("Recurring plan has ended")]
+    PlanEnded,
+
+    #[msg("Invalid TWAP schedule")]
+    InvalidTwapSchedule,
+
+    #[msg("Fill exceeds the TWAP schedule")]
+    TwapScheduleExceeded,
+
+    #[msg("Basket must have between 1 and 20 legs")]
+    InvalidBasketLegs,
+
+    #[msg("All-or-none basket must be filled completely in one report")]
+    AllOrNoneViolated,
+
+    #[msg("Orders cannot be crossed")]
+    OrdersNotCrossable,
+
+    #[msg("Order book fee exceeds the maximum")]
+    InvalidBookFee,
+
+    #[msg("Order book side is full")]
+    BookFull,
+
+    #[msg("Order book event queue is full")]
+    EventQueueFull,
+
+    #[msg("Order not found in the book")]
+    BookOrderNotFound,
+
+    #[msg("Invalid AMM spread or reference price age")]
+    InvalidAmmConfig,
+
+    #[msg("AMM reference price is missing or stale")]
+    StaleReferencePrice,
+
+    #[msg("Not enough liquidity in the AMM pool")]
+    InsufficientLiquidity,
 
-}
\ No newline at end of file
+    #[msg("Swap price is outside the caller's limit")]
+    SlippageExceeded,
+
+    #[msg("Invalid price feed update or configuration")]
+    InvalidPriceFeed,
+
+    #[msg("Price feed is stale")]
+    StalePriceFeed,
+
+    #[msg("Price feed confidence interval is too wide")]
+    PriceFeedConfidence,
+
+    #[msg("Order price deviates too far from the price feed")]
+    PriceDeviation,
+
+    #[msg("Price feed is required for this ticker")]
+    PriceFeedRequired,
+
+    #[msg("Price is outside the limit-up/limit-down band")]
+    PriceOutsideBand,
+
+    #[msg("Invalid market calendar")]
+    InvalidCalendar,
+
+    #[msg("Market is closed")]
+    MarketClosed,
+
+    #[msg("Invalid time in force")]
+    InvalidTimeInForce,
+
+    #[msg("Order is below the minimum size")]
+    OrderBelowMinimum,
+
+    #[msg("Order quantity is not a multiple of the quantity step")]
+    InvalidQuantityStep,
+
+    #[msg("Order price is not a multiple of the price tick")]
+    InvalidPriceTick,
+
+    #[msg("Market for this ticker and payment mint is not open")]
+    MarketNotOpen,
+
+    #[msg("Order exceeds the market limit")]
+    OrderAboveMaximum,
+
+    #[msg("Ticker is already delisted")]
+    TickerAlreadyDelisted,
+
+    #[msg("Ticker is not delisted")]
+    TickerNotDelisted,
+
+    #[msg("Final settlement price must be positive")]
+    InvalidFinalPrice,
+
+    #[msg("Nothing to redeem")]
+    NothingToRedeem,
+
+    #[msg("Partial fill is not allowed for this order")]
+    PartialFillNotAllowed,
+
+    #[msg("Plan allowance is exhausted")]
+    PlanAllowanceExhausted,
+
+    #[msg("Order has expired")]
+    OrderExpired,
+
+    #[msg("Orders of the same maker cannot be crossed")]
+    SelfCrossNotAllowed,
+
+    #[msg("Account is not in the legacy layout")]
+    InvalidLegacyAccount,
+
+    #[msg("Registry must be migrated first")]
+    RegistryNotMigrated,
+
+    #[msg("Price band must not exceed 10000 bps")]
+    InvalidPriceBand,
+
+
+}
@@ -0,0 +1,261 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use crate::{
+    Registry,
+    errors::ErrorCode,
+    utils::{verify_oracle_quorum, SignedMessage, MessageKind},
+};
+
+/// Допустимый возраст цены по умолчанию, сек
+pub const DEFAULT_FEED_MAX_AGE: i64 = 60;
+/// Допустимый доверительный интервал по умолчанию, bps от цены
+pub const DEFAULT_FEED_MAX_CONF_BPS: u16 = 100;
+
+/// Цена тикера от оракулов, PDA ['feed', ticker_mint]. Создаётся вместе с тикером.
+/// Значение цены — `price * 10^expo` USD за целый тикер (10^decimals базовых единиц).
+/// Платёжные токены считаются привязанными к USD, поэтому в единицы `OrderPayload.price`
+/// цена переводится умножением на `10^decimals` платёжного токена конкретного рынка.
+#[account]
+pub struct PriceFeed {
+    pub ticker_mint: Pubkey,
+
+    pub price: i64,
+    pub conf: u64, // доверительный интервал, в единицах `price`
+    pub expo: i32,
+    pub publish_time: i64,
+    pub market_open: bool, // биржа открыта на момент публикации
+
+    pub max_age: i64, // старше — цена не используется
+    pub max_conf_bps: u16, // шире — цена не используется
+    pub max_deviation_bps: u16, // отклонение лимитной цены ордера от цены фида, 0 — без проверки
+
+    pub bump: u8,
+}
+
+impl PriceFeed {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // ticker_mint
+        + 8 // price
+        + 8 // conf
+        + 4 // expo
+        + 8 // publish_time
+        + 1 // market_open
+        + 8 // max_age
+        + 2 // max_conf_bps
+        + 2 // max_deviation_bps
+        + 1; // bump
+
+    /// Пустой фид с порогами по умолчанию: цены нет до первой публикации оракулов
+    pub fn reset(&mut self, ticker_mint: Pubkey, bump: u8) {
+        self.ticker_mint = ticker_mint;
+        self.price = 0;
+        self.conf = 0;
+        self.expo = 0;
+        self.publish_time = 0;
+        self.market_open = false;
+        self.max_age = DEFAULT_FEED_MAX_AGE;
+        self.max_conf_bps = DEFAULT_FEED_MAX_CONF_BPS;
+        self.max_deviation_bps = 0;
+        self.bump = bump;
+    }
+
+    /// Свежая цена с приемлемым доверительным интервалом, приведённая к единицам ордера
+    /// рынка с платёжным токеном точности `payment_decimals`
+    pub fn current(&self, now: i64, payment_decimals: u8) -> Result<u64> {
+        require!(
+            self.publish_time > 0 && now - self.publish_time <= self.max_age,
+            ErrorCode::StalePriceFeed
+        );
+        require!(self.price > 0, ErrorCode::StalePriceFeed);
+
+        let price = self.price as u128;
+        require!(
+            (self.conf as u128) * 10_000 <= price * self.max_conf_bps as u128,
+            ErrorCode::PriceFeedConfidence
+        );
+
+        let expo = self.expo + payment_decimals as i32;
+        let factor = 10u128.checked_pow(expo.unsigned_abs()).ok_or(ErrorCode::Overflow)?;
+        let value = if expo >= 0 {
+            price.checked_mul(factor).ok_or(ErrorCode::Overflow)?
+        } else {
+            price / factor
+        };
+        u64::try_from(value).map_err(|_| error!(ErrorCode::Overflow))
+    }
+
+    /// Лимитная цена не дальше `max_deviation_bps` от текущей цены фида
+    pub fn check_quote(&self, price: u64, payment_decimals: u8, now: i64) -> Result<()> {
+        if self.max_deviation_bps == 0 {
+            return Ok(());
+        }
+
+        let reference = self.current(now, payment_decimals)?;
+
+        let deviation = reference.abs_diff(price) as u128 * 10_000;
+        require!(
+            deviation <= reference as u128 * self.max_deviation_bps as u128,
+            ErrorCode::PriceDeviation
+        );
+        Ok(())
+    }
+}
+
+/// Обновление цены, подписывается кворумом оракулов
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceFeedUpdate {
+    pub ticker_mint: Pubkey,
+
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub market_open: bool,
+}
+
+impl SignedMessage for PriceFeedUpdate {
+    const KIND: MessageKind = MessageKind::PriceFeedUpdate;
+}
+
+#[event]
+pub struct PriceFeedUpdated {
+    pub ticker_mint: Pubkey,
+
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub market_open: bool,
+}
+
+#[event]
+pub struct PriceFeedConfigured {
+    pub ticker_mint: Pubkey,
+
+    pub max_age: i64,
+    pub max_conf_bps: u16,
+    pub max_deviation_bps: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(update: PriceFeedUpdate)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"feed", update.ticker_mint.as_ref()],
+        bump = feed.bump,
+    )]
+    pub feed: Account<'info, PriceFeed>,
+
+    /// CHECK: instruction sysvar, used for verifying oracle signature
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePriceFeed<'info> {
+    #[account(constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"feed", feed.ticker_mint.as_ref()],
+        bump = feed.bump,
+    )]
+    pub feed: Account<'info, PriceFeed>,
+}
+
+/// Фид для тикера, созданного до появления `PriceFeed`
+#[derive(Accounts)]
+#[instruction(symbol: String)]
+pub struct InitPriceFeed<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(seeds = [b"mint", symbol.as_bytes()], bump)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [b"feed", mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = PriceFeed::SPACE,
+    )]
+    pub feed: Account<'info, PriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init(ctx: Context<InitPriceFeed>) -> Result<()> {
+    ctx.accounts.feed.reset(ctx.accounts.mint.key(), ctx.bumps.feed);
+    Ok(())
+}
+
+/// Публикует цену, подписанную кворумом оракулов. Отправитель может быть любым.
+pub fn update(ctx: Context<UpdatePriceFeed>, update: PriceFeedUpdate) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    let serialized = update.message()?;
+
+    let registry = &ctx.accounts.registry;
+    verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+    let feed = &mut ctx.accounts.feed;
+    // цена не из будущего и не откатывает более свежую
+    require!(
+        update.publish_time <= now && update.publish_time > feed.publish_time,
+        ErrorCode::InvalidPriceFeed
+    );
+    require!(update.price > 0, ErrorCode::InvalidPriceFeed);
+
+    feed.price = update.price;
+    feed.conf = update.conf;
+    feed.expo = update.expo;
+    feed.publish_time = update.publish_time;
+    feed.market_open = update.market_open;
+
+    emit!(PriceFeedUpdated {
+        ticker_mint: feed.ticker_mint,
+
+        price: update.price,
+        conf: update.conf,
+        expo: update.expo,
+        publish_time: update.publish_time,
+        market_open: update.market_open,
+    });
+
+    Ok(())
+}
+
+pub fn configure(ctx: Context<ConfigurePriceFeed>, max_age: i64, max_conf_bps: u16, max_deviation_bps: u16) -> Result<()> {
+    require!(max_age > 0 && max_conf_bps <= 10_000, ErrorCode::InvalidPriceFeed);
+
+    let feed = &mut ctx.accounts.feed;
+    feed.max_age = max_age;
+    feed.max_conf_bps = max_conf_bps;
+    feed.max_deviation_bps = max_deviation_bps;
+
+    emit!(PriceFeedConfigured {
+        ticker_mint: feed.ticker_mint,
+
+        max_age,
+        max_conf_bps,
+        max_deviation_bps,
+    });
+
+    Ok(())
+}
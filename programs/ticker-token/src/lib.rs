@@ -2,21 +2,153 @@ use anchor_lang::prelude::*;
 use anchor_lang::prelude::Pubkey;
 
 pub mod utils;
+pub mod price;
 
 mod errors;
-use errors::{TickerError};
+use errors::{TickerError, ErrorCode};
 
 mod ticker;
 pub use ticker::*;
 
+mod feed;
+use feed::*;
+
+mod config;
+use config::*;
+
+mod calendar;
+use calendar::*;
+
+mod market;
+use market::*;
+
+mod delist;
+use delist::*;
+
 mod order;
 use order::*;
 
+mod plan;
+use plan::*;
+
+mod book;
+use book::*;
+
+mod amm;
+use amm::*;
+
 declare_id!("EjJFMSVeNQYjjJJkC3fic9pTHj9AcowTbEz7CcGFkXXk");
 
+/// Максимальное количество ключей оракулов в registry
+pub const MAX_ORACLES: usize = 8;
+
+/// Ключ оракула: ed25519 (Solana) или secp256k1 (Ethereum-адрес)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OracleKey {
+    Ed25519(Pubkey),
+    Secp256k1([u8; 20]),
+}
+
+impl OracleKey {
+    pub const SIZE: usize = 1 + 32;
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            OracleKey::Ed25519(pubkey) => *pubkey == Pubkey::default(),
+            OracleKey::Secp256k1(address) => *address == [0u8; 20],
+        }
+    }
+}
+
 #[account]
 pub struct Registry {
     pub authority: Pubkey,
+
+    pub oracles: Vec<OracleKey>, // ключи независимых ценовых оракулов
+    pub threshold: u8, // сколько подписей оракулов нужно для валидного payload
+
+    pub broker: Pubkey, // ключ брокера, подписывающий отчёты об исполнении
+
+    pub order_retention: i64, // сколько секунд хранить терминальные ордера (0 — закрывать сразу)
+
+    pub ticker_count: u64, // сколько тикеров создано, порядковый номер следующего в индексе
+}
+
+impl Registry {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // authority
+        + 4 + OracleKey::SIZE * MAX_ORACLES // oracles
+        + 1 // threshold
+        + 32 // broker
+        + 8 // order_retention
+        + 8; // ticker_count
+
+    /// Размеры прежних раскладок: только authority; + оракулы `Pubkey` и порог;
+    /// оракулы `OracleKey`; + broker; + order_retention
+    const LEGACY_SPACES: [usize; 5] = [
+        8 + 32,
+        8 + 32 + 4 + 32 * MAX_ORACLES + 1,
+        8 + 32 + 4 + OracleKey::SIZE * MAX_ORACLES + 1,
+        8 + 32 + 4 + OracleKey::SIZE * MAX_ORACLES + 1 + 32,
+        8 + 32 + 4 + OracleKey::SIZE * MAX_ORACLES + 1 + 32 + 8,
+    ];
+
+    /// Читает реестр любой прежней раскладки; полей, которых в ней не было, — по умолчанию
+    pub fn from_legacy(data: &[u8]) -> Result<Self> {
+        let layout = Self::LEGACY_SPACES.iter().position(|space| *space == data.len());
+        let Some(layout) = layout.filter(|_| data[..8] == *Self::DISCRIMINATOR) else {
+            return err!(ErrorCode::InvalidLegacyAccount);
+        };
+
+        let invalid = |_| error!(ErrorCode::InvalidLegacyAccount);
+        let mut body = &data[8..];
+
+        let mut registry = Registry {
+            authority: Pubkey::deserialize(&mut body).map_err(invalid)?,
+            oracles: Vec::new(),
+            threshold: 0,
+            broker: Pubkey::default(),
+            order_retention: 0,
+            ticker_count: 0,
+        };
+        if layout == 0 {
+            return Ok(registry);
+        }
+
+        // до secp256k1 оракулы хранились как ключи ed25519
+        registry.oracles = if layout == 1 {
+            Vec::<Pubkey>::deserialize(&mut body).map_err(invalid)?
+                .into_iter().map(OracleKey::Ed25519).collect()
+        } else {
+            Vec::<OracleKey>::deserialize(&mut body).map_err(invalid)?
+        };
+        registry.threshold = u8::deserialize(&mut body).map_err(invalid)?;
+
+        if layout >= 3 {
+            registry.broker = Pubkey::deserialize(&mut body).map_err(invalid)?;
+        }
+        if layout >= 4 {
+            registry.order_retention = i64::deserialize(&mut body).map_err(invalid)?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Страница индекса, в которую попадёт следующий тикер
+    pub fn index_page(&self) -> u32 {
+        (self.ticker_count / TICKERS_PER_PAGE as u64) as u32
+    }
+}
+
+#[event]
+pub struct OraclesUpdated {
+    pub oracles: Vec<OracleKey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct BrokerUpdated {
+    pub broker: Pubkey,
 }
 
 #[derive(Accounts)]
@@ -29,10 +161,10 @@ pub struct Init<'info> {
         seeds = [b"registry"],
         bump,
         payer = payer,
-        space = 8					// Anchor-дескриптор (дисриминатор, нужен всегда)
-              + 32                  // authority: Pubkey
+        space = Registry::SPACE,
     )]
     pub registry: Account<'info, Registry>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -55,6 +187,9 @@ pub mod ticker_token {
     pub fn init(ctx: Context<Init>) -> Result<()> {
         let registry = &mut ctx.accounts.registry;
         registry.authority = ctx.accounts.payer.key();
+        registry.oracles = vec![OracleKey::Ed25519(ctx.accounts.payer.key())];
+        registry.threshold = 1;
+        registry.broker = ctx.accounts.payer.key();
 
         Ok(())
     }
@@ -66,11 +201,53 @@ pub mod ticker_token {
         Ok(())
     }
 
-    pub fn create_ticker(_ctx: Context<CreateTicker>, symbol: String, decimals: u8) -> Result<()> {
-        emit!(TickerCreated { ticker: symbol.clone() });
+    pub fn set_oracles(ctx: Context<Authority>, oracles: Vec<OracleKey>, threshold: u8) -> Result<()> {
+        require!(oracles.len() <= MAX_ORACLES, TickerError::TooManyOracles);
+        require!(threshold > 0 && threshold as usize <= oracles.len(), TickerError::InvalidOracleThreshold);
+
+        for (i, oracle) in oracles.iter().enumerate() {
+            require!(!oracle.is_zero(), TickerError::InvalidOracleKey);
+            require!(!oracles[..i].contains(oracle), TickerError::DuplicateOracle);
+        }
+
+        let registry = &mut ctx.accounts.registry;
+        registry.oracles = oracles;
+        registry.threshold = threshold;
+
+        emit!(OraclesUpdated {
+            oracles: registry.oracles.clone(),
+            threshold,
+        });
+        Ok(())
+    }
+
+    pub fn set_broker(ctx: Context<Authority>, broker: Pubkey) -> Result<()> {
+        require!(broker != Pubkey::default(), TickerError::InvalidBrokerKey);
+
+        ctx.accounts.registry.broker = broker;
+        emit!(BrokerUpdated { broker });
         Ok(())
     }
 
+    pub fn set_order_retention(ctx: Context<Authority>, seconds: i64) -> Result<()> {
+        require!(seconds >= 0, TickerError::InvalidRetention);
+
+        ctx.accounts.registry.order_retention = seconds;
+        Ok(())
+    }
+
+    pub fn create_ticker(ctx: Context<CreateTicker>, symbol: String, decimals: u8) -> Result<()> {
+        ticker::initialize(ctx, symbol)
+    }
+
+    pub fn migrate_registry(ctx: Context<MigrateRegistry>) -> Result<()> {
+        ticker::extend_registry(ctx)
+    }
+
+    pub fn index_ticker(ctx: Context<IndexTicker>, symbol: String) -> Result<()> {
+        ticker::index(ctx, symbol)
+    }
+
     pub fn create_buy_order(ctx: Context<CreateBuyOrder>, payload: OrderPayload) -> Result<()> {
         order::create::buy(ctx, payload)
     }
@@ -83,11 +260,277 @@ pub mod ticker_token {
         order::cancel(ctx)
     }
 
-    pub fn process_order(ctx: Context<ProcessOrder>) -> Result<()> {
-        order::process(ctx)
+    pub fn process_order(ctx: Context<ProcessOrder>, attestation: Option<PriceAttestation>) -> Result<()> {
+        order::process(ctx, attestation)
+    }
+
+    pub fn execute_order(ctx: Context<ExecuteOrder>, report: ExecutionReport) -> Result<()> {
+        order::execute(ctx, report)
+    }
+
+    pub fn reject_order(ctx: Context<RejectOrder>) -> Result<()> {
+        order::reject(ctx)
     }
 
-    pub fn execute_order(ctx: Context<ExecuteOrder>, order_id: u64, spent: u64, proof_cid: Vec<u8>) -> Result<()> {
-        order::execute(ctx, spent, proof_cid)
+    pub fn expire_order(ctx: Context<ExpireOrder>) -> Result<()> {
+        order::expire(ctx)
+    }
+
+    pub fn cleanup_order(ctx: Context<CleanupOrder>) -> Result<()> {
+        order::cleanup(ctx)
+    }
+
+    pub fn cross_orders<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrossOrders<'info>>,
+        quantity: u64,
+        price: u64,
+        attestation: Option<PriceAttestation>,
+    ) -> Result<()> {
+        order::cross(ctx, quantity, price, attestation)
+    }
+
+    pub fn create_switch_order(ctx: Context<CreateSwitchOrder>, payload: SwitchPayload) -> Result<()> {
+        order::create_switch(ctx, payload)
+    }
+
+    pub fn execute_switch_order(ctx: Context<ExecuteSwitchOrder>, report: SwitchReport) -> Result<()> {
+        order::execute_switch(ctx, report)
+    }
+
+    pub fn cancel_switch_order(ctx: Context<CancelSwitchOrder>, _id: u64) -> Result<()> {
+        order::cancel_switch(ctx)
+    }
+
+    pub fn create_basket_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateBasketOrder<'info>>,
+        payload: BasketPayload,
+    ) -> Result<()> {
+        order::create_basket(ctx, payload)
+    }
+
+    pub fn execute_basket_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteBasketOrder<'info>>,
+        report: BasketReport,
+    ) -> Result<()> {
+        order::execute_basket(ctx, report)
+    }
+
+    pub fn cancel_basket_order(ctx: Context<CancelBasketOrder>, _id: u64) -> Result<()> {
+        order::cancel_basket(ctx)
+    }
+
+    pub fn expire_basket_order(ctx: Context<ExpireBasketOrder>) -> Result<()> {
+        order::expire_basket(ctx)
+    }
+
+    pub fn create_plan(ctx: Context<CreatePlan>, payload: PlanPayload) -> Result<()> {
+        plan::create(ctx, payload)
+    }
+
+    pub fn pause_plan(ctx: Context<ManagePlan>) -> Result<()> {
+        plan::pause(ctx)
+    }
+
+    pub fn resume_plan(ctx: Context<ManagePlan>) -> Result<()> {
+        plan::resume(ctx)
+    }
+
+    pub fn cancel_plan(ctx: Context<CancelPlan>) -> Result<()> {
+        plan::cancel(ctx)
+    }
+
+    pub fn spawn_plan_order(ctx: Context<SpawnPlanOrder>) -> Result<()> {
+        plan::spawn(ctx)
+    }
+
+    pub fn create_book(ctx: Context<CreateBook>, maker_fee_bps: u16, taker_fee_bps: u16) -> Result<()> {
+        book::create(ctx, maker_fee_bps, taker_fee_bps)
+    }
+
+    pub fn place_book_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, PlaceBookOrder<'info>>,
+        side: OrderSide,
+        price: u64,
+        quantity: u64,
+    ) -> Result<()> {
+        book::place(ctx, side, price, quantity)
+    }
+
+    pub fn cancel_book_order(ctx: Context<CancelBookOrder>, side: OrderSide, order_id: u64) -> Result<()> {
+        book::cancel(ctx, side, order_id)
+    }
+
+    pub fn consume_book_events<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ConsumeBookEvents<'info>>,
+        limit: u8,
+    ) -> Result<()> {
+        book::consume(ctx, limit)
+    }
+
+    pub fn create_amm(ctx: Context<CreateAmm>, market_maker: Pubkey, spread_bps: u16, max_age: i64) -> Result<()> {
+        amm::create(ctx, market_maker, spread_bps, max_age)
+    }
+
+    pub fn configure_amm(ctx: Context<ConfigureAmm>, market_maker: Pubkey, spread_bps: u16, max_age: i64) -> Result<()> {
+        amm::configure(ctx, market_maker, spread_bps, max_age)
+    }
+
+    pub fn update_amm_reference(ctx: Context<UpdateAmmReference>, attestation: PriceAttestation) -> Result<()> {
+        amm::update_reference(ctx, attestation)
+    }
+
+    pub fn add_liquidity(ctx: Context<ManageLiquidity>, ticker_amount: u64, payment_amount: u64) -> Result<()> {
+        amm::add_liquidity(ctx, ticker_amount, payment_amount)
+    }
+
+    pub fn remove_liquidity(ctx: Context<ManageLiquidity>, ticker_amount: u64, payment_amount: u64) -> Result<()> {
+        amm::remove_liquidity(ctx, ticker_amount, payment_amount)
+    }
+
+    pub fn amm_swap(ctx: Context<AmmSwapAccounts>, side: OrderSide, quantity: u64, limit: u64) -> Result<()> {
+        amm::swap(ctx, side, quantity, limit)
+    }
+
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, update: PriceFeedUpdate) -> Result<()> {
+        feed::update(ctx, update)
+    }
+
+    pub fn init_price_feed(ctx: Context<InitPriceFeed>, _symbol: String) -> Result<()> {
+        feed::init(ctx)
+    }
+
+    pub fn configure_price_feed(
+        ctx: Context<ConfigurePriceFeed>,
+        max_age: i64,
+        max_conf_bps: u16,
+        max_deviation_bps: u16,
+    ) -> Result<()> {
+        feed::configure(ctx, max_age, max_conf_bps, max_deviation_bps)
+    }
+
+    pub fn init_ticker_config(ctx: Context<InitTickerConfig>, _symbol: String) -> Result<()> {
+        config::init(ctx)
+    }
+
+    pub fn migrate_ticker_config(ctx: Context<MigrateTickerConfig>) -> Result<()> {
+        config::migrate(ctx)
+    }
+
+    pub fn set_price_band(ctx: Context<ConfigureTicker>, band_bps: u16) -> Result<()> {
+        config::set_price_band(ctx, band_bps)
+    }
+
+    pub fn init_calendar(ctx: Context<InitCalendar>) -> Result<()> {
+        calendar::init(ctx)
+    }
+
+    pub fn set_market_session(
+        ctx: Context<ConfigureCalendar>,
+        weekdays: u8,
+        open_minute: u16,
+        close_minute: u16,
+        utc_offset: i32,
+    ) -> Result<()> {
+        calendar::set_session(ctx, weekdays, open_minute, close_minute, utc_offset)
+    }
+
+    pub fn set_market_holidays(ctx: Context<ConfigureCalendar>, holidays: Vec<i32>, early_closes: Vec<EarlyClose>) -> Result<()> {
+        calendar::set_holidays(ctx, holidays, early_closes)
+    }
+
+    pub fn set_order_limits(
+        ctx: Context<ConfigureTicker>,
+        min_quantity: u64,
+        quantity_step: u64,
+        price_tick: u64,
+        min_notional: u64,
+    ) -> Result<()> {
+        config::set_order_limits(ctx, min_quantity, quantity_step, price_tick, min_notional)
+    }
+
+    pub fn open_market(ctx: Context<OpenMarket>, max_quantity: u64, max_notional: u64) -> Result<()> {
+        market::open(ctx, max_quantity, max_notional)
+    }
+
+    pub fn configure_market(
+        ctx: Context<ConfigureMarket>,
+        status: MarketStatus,
+        max_quantity: u64,
+        max_notional: u64,
+    ) -> Result<()> {
+        market::configure(ctx, status, max_quantity, max_notional)
+    }
+
+    pub fn delist_ticker(ctx: Context<DelistTicker>, final_price: u64) -> Result<()> {
+        delist::delist(ctx, final_price)
+    }
+
+    pub fn redeem_ticker(ctx: Context<RedeemTicker>) -> Result<()> {
+        delist::redeem(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy(space: usize, fields: &[u8]) -> Vec<u8> {
+        let mut data = Registry::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(fields);
+        data.resize(space, 0);
+        data
+    }
+
+    #[test]
+    fn migrates_baseline_registry() {
+        let authority = Pubkey::new_unique();
+        let data = legacy(Registry::LEGACY_SPACES[0], authority.as_ref());
+
+        let registry = Registry::from_legacy(&data).unwrap();
+        assert_eq!(registry.authority, authority);
+        assert!(registry.oracles.is_empty());
+        assert_eq!(registry.threshold, 0);
+    }
+
+    #[test]
+    fn converts_ed25519_oracle_keys() {
+        let authority = Pubkey::new_unique();
+        let oracle = Pubkey::new_unique();
+
+        let mut fields = authority.to_bytes().to_vec();
+        fields.extend_from_slice(&vec![oracle].try_to_vec().unwrap());
+        fields.push(1);
+        let data = legacy(Registry::LEGACY_SPACES[1], &fields);
+
+        let registry = Registry::from_legacy(&data).unwrap();
+        assert!(registry.oracles == vec![OracleKey::Ed25519(oracle)]);
+        assert_eq!(registry.threshold, 1);
+    }
+
+    #[test]
+    fn keeps_broker_and_retention() {
+        let broker = Pubkey::new_unique();
+
+        let mut fields = Pubkey::new_unique().to_bytes().to_vec();
+        fields.extend_from_slice(&vec![OracleKey::Secp256k1([7; 20])].try_to_vec().unwrap());
+        fields.push(1);
+        fields.extend_from_slice(broker.as_ref());
+        fields.extend_from_slice(&3600i64.to_le_bytes());
+        let data = legacy(Registry::LEGACY_SPACES[4], &fields);
+
+        let registry = Registry::from_legacy(&data).unwrap();
+        assert_eq!(registry.broker, broker);
+        assert_eq!(registry.order_retention, 3600);
+        assert_eq!(registry.ticker_count, 0);
+    }
+
+    #[test]
+    fn rejects_current_and_unknown_layouts() {
+        assert!(Registry::from_legacy(&legacy(Registry::SPACE, &[])).is_err());
+        assert!(Registry::from_legacy(&legacy(100, &[])).is_err());
+
+        let mut data = legacy(Registry::LEGACY_SPACES[0], &[]);
+        data[0] ^= 1;
+        assert!(Registry::from_legacy(&data).is_err());
     }
 }
@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{TokenAccount, Token, Mint};
+
+use crate::{
+    Registry,
+    errors::ErrorCode,
+    price::{self, Rounding},
+    order::types::{OrderPayload, OrderMode},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MarketStatus {
+    Open, // заявки принимаются
+    Halted, // приостановлен, новые заявки не принимаются
+    Closed, // выведен из обращения
+}
+
+/// Рынок тикера за конкретный платёжный токен, PDA ['market', ticker_mint, payment_mint].
+/// Заявки принимаются только на открытых администратором рынках.
+#[account]
+pub struct Market {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub status: MarketStatus,
+    pub price_scale: u64, // фиксируется при открытии по decimals тикера
+
+    pub max_quantity: u64, // максимальное количество тикера в заявке, 0 — без ограничения
+    pub max_notional: u64, // максимальная сумма заявки в платёжном токене, 0 — без ограничения
+
+    pub bump: u8,
+}
+
+impl Market {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // ticker_mint
+        + 32 // payment_mint
+        + 1 // status
+        + 8 // price_scale
+        + 8 // max_quantity
+        + 8 // max_notional
+        + 1; // bump
+
+    /// Рынок открыт и заявка не превышает его лимиты
+    pub fn check_order(&self, payload: &OrderPayload, amount: u64) -> Result<()> {
+        require!(self.status == MarketStatus::Open, ErrorCode::MarketNotOpen);
+
+        match payload.mode {
+            OrderMode::Notional => self.check_notional(amount)?,
+            OrderMode::Quantity | OrderMode::All => {
+                self.check_quantity(amount)?;
+                if !payload.market && self.max_notional > 0 {
+                    self.check_notional(price::notional(amount, payload.price, self.price_scale, Rounding::Up)?)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Количество тикера в заявке не больше `max_quantity`
+    pub fn check_quantity(&self, quantity: u64) -> Result<()> {
+        require!(self.max_quantity == 0 || quantity <= self.max_quantity, ErrorCode::OrderAboveMaximum);
+        Ok(())
+    }
+
+    /// Сумма заявки не больше `max_notional`
+    pub fn check_notional(&self, notional: u64) -> Result<()> {
+        require!(self.max_notional == 0 || notional <= self.max_notional, ErrorCode::OrderAboveMaximum);
+        Ok(())
+    }
+}
+
+#[event]
+pub struct MarketOpened {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub price_scale: u64,
+    pub max_quantity: u64,
+    pub max_notional: u64,
+}
+
+#[event]
+pub struct MarketUpdated {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub status: MarketStatus,
+    pub max_quantity: u64,
+    pub max_notional: u64,
+}
+
+#[derive(Accounts)]
+pub struct OpenMarket<'info> {
+    #[account(
+        mut,
+        constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    pub ticker_mint: Box<Account<'info, Mint>>,
+
+    #[account(constraint = payment_mint.key() != ticker_mint.key() @ ErrorCode::InvalidPaymentMint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        seeds = [b"market", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = Market::SPACE,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// Пул пары создаётся вместе с рынком, а не при первом исполнении
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"pool", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = payer,
+    )]
+    pub pool: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureMarket<'info> {
+    #[account(constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"market", market.ticker_mint.as_ref(), market.payment_mint.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn open(ctx: Context<OpenMarket>, max_quantity: u64, max_notional: u64) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    market.ticker_mint = ctx.accounts.ticker_mint.key();
+    market.payment_mint = ctx.accounts.payment_mint.key();
+    market.status = MarketStatus::Open;
+    market.price_scale = price::price_scale(ctx.accounts.ticker_mint.decimals)?;
+    market.max_quantity = max_quantity;
+    market.max_notional = max_notional;
+    market.bump = ctx.bumps.market;
+
+    emit!(MarketOpened {
+        ticker_mint: market.ticker_mint,
+        payment_mint: market.payment_mint,
+
+        price_scale: market.price_scale,
+        max_quantity,
+        max_notional,
+    });
+
+    Ok(())
+}
+
+pub fn configure(ctx: Context<ConfigureMarket>, status: MarketStatus, max_quantity: u64, max_notional: u64) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    market.status = status;
+    market.max_quantity = max_quantity;
+    market.max_notional = max_notional;
+
+    emit!(MarketUpdated {
+        ticker_mint: market.ticker_mint,
+        payment_mint: market.payment_mint,
+
+        status,
+        max_quantity,
+        max_notional,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,632 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+	token::{self, TokenAccount, Token, Mint},
+};
+use crate::{
+	Registry,
+	OracleKey,
+	errors::ErrorCode,
+	config::TickerConfig,
+	feed::PriceFeed,
+	calendar::MarketCalendar,
+	market::{Market, MarketStatus},
+	ticker::{TickerRecord, TickerStatus},
+	price::{self, Rounding},
+	utils::{verify_oracle_quorum, assert_cid, assert_ata, SignedMessage},
+	order::{types::*, state::*, switch::release},
+};
+
+#[derive(Accounts)]
+#[instruction(payload: BasketPayload)]
+pub struct CreateBasketOrder<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == payload.maker @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"basket", payer.key().as_ref(), &payload.id.to_le_bytes()],
+		bump,
+		space = BasketOrder::space(payload.legs.len()),
+	)]
+	pub basket_order: Account<'info, BasketOrder>,
+
+	#[account(constraint = payment_mint_account.key() == payload.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint_account: Account<'info, Mint>,
+
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == payer.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_payment_account.mint == payload.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub maker_payment_account: Account<'info, TokenAccount>,
+
+	/// Единый эскроу платёжного токена для всех позиций
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"escrow", basket_order.key().as_ref()],
+		bump,
+		token::mint = payment_mint_account,
+		token::authority = basket_order,
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	/// Торговая сессия: рыночные заявки создаются и исполняются только при открытой бирже
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Box<Account<'info, MarketCalendar>>,
+
+	/// CHECK: instruction sysvar, used for verifying oracle signature
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+	pub system_program: Program<'info, System>,
+	pub token_program: Program<'info, Token>,
+	// remaining_accounts: для каждой позиции в порядке payload.legs — [ticker_mint, config ['config', ticker_mint],
+	// market ['market', ticker_mint, payment_mint], record ['ticker', ticker_mint]]
+}
+
+/// Аккаунтов в remaining_accounts на одну позицию payload
+const LEG_ACCOUNTS: usize = 4;
+/// Аккаунтов в remaining_accounts на одну позицию отчёта
+const FILL_ACCOUNTS: usize = 6;
+
+/// Аккаунтов, блокируемых одной транзакцией
+const MAX_TX_ACCOUNTS: usize = 64;
+/// Аккаунты `ExecuteBasketOrder`, программа и программа ed25519 для подписи брокера
+const EXECUTE_ACCOUNTS: usize = 12 + 2;
+
+// полный отчёт по корзине максимального размера помещается в одну транзакцию
+const _: () = assert!(EXECUTE_ACCOUNTS + MAX_BASKET_LEGS * FILL_ACCOUNTS <= MAX_TX_ACCOUNTS);
+
+#[derive(Accounts)]
+#[instruction(report: BasketReport)]
+pub struct ExecuteBasketOrder<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+		seeds = [b"basket", maker.key().as_ref(), &report.order_id.to_le_bytes()],
+		bump,
+		constraint = basket_order.status.can_transition_to(&OrderStatus::Filled) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub basket_order: Account<'info, BasketOrder>,
+
+	/// CHECK: checked manually via constraint basket_order.maker == maker.key()
+	#[account(
+		mut,
+		constraint = basket_order.maker == maker.key() @ ErrorCode::InvalidMaker,
+	)]
+	pub maker: AccountInfo<'info>,
+
+	#[account(
+		mut,
+		constraint = refund_account.owner == maker.key() @ ErrorCode::InvalidRefundAccount,
+		constraint = refund_account.mint == basket_order.payment_mint @ ErrorCode::InvalidRefundMint,
+	)]
+	pub refund_account: Account<'info, TokenAccount>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", basket_order.key().as_ref()],
+		bump,
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	#[account(constraint = payment_mint.key() == basket_order.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint: Account<'info, Mint>,
+
+	/// Торговая сессия: рыночные заявки создаются и исполняются только при открытой бирже
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Box<Account<'info, MarketCalendar>>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"fill", report.broker_exec_id.as_ref()],
+		bump,
+		space = 8 + std::mem::size_of::<Fill>(),
+	)]
+	pub fill: Account<'info, Fill>,
+
+	/// CHECK: instruction sysvar, used for verifying broker signature
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+	pub token_program: Program<'info, Token>,
+	pub system_program: Program<'info, System>,
+	// remaining_accounts: для каждого report.fills — [ticker_mint (mut), АТА тикера мейкера (mut), pool (mut),
+	// config ['config', ticker_mint], feed ['feed', ticker_mint], record ['ticker', ticker_mint]];
+	// пул создаётся вместе с рынком пары (open_market), здесь только проверяется
+}
+
+/// Истечение корзины — permissionless, остаток эскроу возвращается мейкеру
+#[derive(Accounts)]
+pub struct ExpireBasketOrder<'info> {
+	pub payer: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"basket", basket_order.maker.as_ref(), &basket_order.id.to_le_bytes()],
+		bump,
+		constraint = basket_order.status.can_transition_to(&OrderStatus::Expired) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub basket_order: Account<'info, BasketOrder>,
+
+	/// CHECK: checked via constraint basket_order.maker == maker.key()
+	#[account(
+		mut,
+		constraint = basket_order.maker == maker.key() @ ErrorCode::InvalidMaker,
+	)]
+	pub maker: AccountInfo<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", basket_order.key().as_ref()],
+		bump,
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	#[account(
+		mut,
+		constraint = refund_account.owner == maker.key() @ ErrorCode::InvalidRefundOwner,
+		constraint = refund_account.mint == basket_order.payment_mint @ ErrorCode::InvalidRefundMint,
+	)]
+	pub refund_account: Account<'info, TokenAccount>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CancelBasketOrder<'info> {
+	#[account(mut)]
+	pub payer: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"basket", payer.key().as_ref(), &id.to_le_bytes()],
+		bump,
+		constraint = basket_order.maker == payer.key() @ ErrorCode::Unauthorized,
+		constraint = basket_order.status.can_transition_to(&OrderStatus::Canceled) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub basket_order: Account<'info, BasketOrder>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", basket_order.key().as_ref()],
+		bump,
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	#[account(
+		mut,
+		constraint = refund_account.owner == payer.key() @ ErrorCode::InvalidRefundOwner,
+		constraint = refund_account.mint == basket_order.payment_mint @ ErrorCode::InvalidRefundMint,
+	)]
+	pub refund_account: Account<'info, TokenAccount>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+pub fn create_basket<'info>(
+	ctx: Context<'_, '_, 'info, 'info, CreateBasketOrder<'info>>,
+	payload: BasketPayload,
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	require!(now <= payload.expires_at, ErrorCode::PayloadExpired);
+
+	let serialized = payload.message()?;
+
+	let registry = &ctx.accounts.registry;
+	verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+	require!(
+		!payload.legs.is_empty() && payload.legs.len() <= MAX_BASKET_LEGS,
+		ErrorCode::InvalidBasketLegs
+	);
+	require!(ctx.remaining_accounts.len() == payload.legs.len() * LEG_ACCOUNTS, ErrorCode::InvalidBasketLegs);
+	require!(!payload.market || ctx.accounts.calendar.is_open(now), ErrorCode::MarketClosed);
+
+	let mut legs = Vec::with_capacity(payload.legs.len());
+	for (i, (leg, accounts)) in payload.legs.iter().zip(ctx.remaining_accounts.chunks(LEG_ACCOUNTS)).enumerate() {
+		require!(leg.amount > 0, ErrorCode::InvalidFillAmount);
+		require!(
+			!payload.legs[..i].iter().any(|other| other.ticker_mint == leg.ticker_mint),
+			ErrorCode::InvalidBasketLegs
+		);
+
+		let mint = Account::<Mint>::try_from(&accounts[0])?;
+		require!(mint.key() == leg.ticker_mint, ErrorCode::InvalidTickerMint);
+		let price_scale = price::price_scale(mint.decimals)?;
+
+		// позиция — заявка на количество тикера со своими ограничениями размера и шага цены
+		let config = load_pda::<TickerConfig>(&accounts[1], &[b"config", leg.ticker_mint.as_ref()])?;
+		config.check_quantity(leg.amount)?;
+		if !payload.market {
+			config.check_tick(leg.price)?;
+			config.check_notional(price::notional(leg.amount, leg.price, price_scale, Rounding::Down)?)?;
+		}
+
+		// и на открытом рынке пары в пределах его лимитов
+		let market = load_pda::<Market>(&accounts[2], &[b"market", leg.ticker_mint.as_ref(), payload.payment_mint.as_ref()])?;
+		require!(market.status == MarketStatus::Open, ErrorCode::MarketNotOpen);
+		market.check_quantity(leg.amount)?;
+		if !payload.market {
+			market.check_notional(price::notional(leg.amount, leg.price, price_scale, Rounding::Up)?)?;
+		}
+
+		// делистингованный тикер в корзину не попадает
+		let record = load_pda::<TickerRecord>(&accounts[3], &[b"ticker", leg.ticker_mint.as_ref()])?;
+		require!(record.status == TickerStatus::Listed, ErrorCode::TickerAlreadyDelisted);
+
+		legs.push(BasketLegState {
+			ticker_mint: leg.ticker_mint,
+			amount: leg.amount,
+			price: leg.price,
+			price_scale,
+
+			filled: 0,
+			spent: 0,
+		});
+	}
+
+	let order = &mut ctx.accounts.basket_order;
+	order.id = payload.id;
+	order.maker = payload.maker;
+	order.market = payload.market;
+
+	order.payment_mint = payload.payment_mint;
+	order.legs = legs;
+
+	order.fee = payload.fee;
+	order.fee_paid = 0;
+	order.all_or_none = payload.all_or_none;
+
+	// статус остаётся начальным Pending (нулевой вариант после init), дальше — только через transition
+	order.expires_at = payload.expires_at;
+
+	let escrowed = order.escrow_amount()?;
+	let cpi_ctx = CpiContext::new(
+		ctx.accounts.token_program.to_account_info(),
+		token::Transfer {
+			from: ctx.accounts.maker_payment_account.to_account_info(),
+			to: ctx.accounts.escrow_account.to_account_info(),
+			authority: ctx.accounts.payer.to_account_info(),
+		},
+	);
+	token::transfer(cpi_ctx, escrowed)?;
+
+	emit!(BasketOrderCreated {
+		id: order.id,
+		maker: order.maker,
+
+		payment_mint: order.payment_mint,
+		legs: order.legs.len() as u8,
+		escrowed,
+		all_or_none: order.all_or_none,
+
+		timestamp: now,
+		expires_at: order.expires_at,
+	});
+
+	Ok(())
+}
+
+pub fn execute_basket<'info>(
+	ctx: Context<'_, '_, 'info, 'info, ExecuteBasketOrder<'info>>,
+	report: BasketReport,
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+
+	// Проверка подписи брокера под отчётом
+	let serialized = report.message()?;
+
+	verify_oracle_quorum(
+		&ctx.accounts.instruction_sysvar,
+		&[OracleKey::Ed25519(ctx.accounts.registry.broker)], 1,
+		&serialized,
+	)?;
+
+	let order = &ctx.accounts.basket_order;
+
+	require!(report.order_id == order.id && report.maker == order.maker, ErrorCode::InvalidExecutionReport);
+	require!(report.timestamp <= now, ErrorCode::InvalidReportTimestamp);
+	require!(now <= order.expires_at, ErrorCode::OrderExpired);
+	require!(!order.market || ctx.accounts.calendar.is_open(now), ErrorCode::MarketClosed);
+	// пустой отчёт допустим только как завершающий
+	require!(!report.fills.is_empty() || report.complete, ErrorCode::InvalidFillAmount);
+	require!(ctx.remaining_accounts.len() == report.fills.len() * FILL_ACCOUNTS, ErrorCode::InvalidBasketLegs);
+	assert_cid(&report.proof_cid)?;
+
+	let mut legs = order.legs.clone();
+	let mut total_spent: u64 = 0;
+
+	// проверяем позиции до любых переводов
+	for (i, fill) in report.fills.iter().enumerate() {
+		require!(
+			!report.fills[..i].iter().any(|other| other.leg == fill.leg),
+			ErrorCode::InvalidBasketLegs
+		);
+
+		let leg = legs.get_mut(fill.leg as usize).ok_or(ErrorCode::InvalidBasketLegs)?;
+		require!(fill.filled > 0, ErrorCode::InvalidFillAmount);
+
+		leg.filled = leg.filled.checked_add(fill.filled).ok_or(ErrorCode::Overflow)?;
+		require!(leg.filled <= leg.amount, ErrorCode::InvalidFillAmount);
+		leg.spent = leg.spent.checked_add(fill.spent).ok_or(ErrorCode::Overflow)?;
+
+		// лимитная корзина: брокер не мог потратить больше, чем по цене позиции
+		if !order.market {
+			let max_spent = price::notional(fill.filled, leg.price, leg.price_scale, Rounding::Up)?;
+			require!(fill.spent <= max_spent, ErrorCode::LimitPriceViolated);
+		}
+
+		// фактическая цена позиции (spent / filled) в коридоре своего тикера
+		let effective = price::mul_div(fill.spent, leg.price_scale, fill.filled, Rounding::Down)?;
+		let accounts = &ctx.remaining_accounts[i * FILL_ACCOUNTS..(i + 1) * FILL_ACCOUNTS];
+		let config = load_pda::<TickerConfig>(&accounts[3], &[b"config", leg.ticker_mint.as_ref()])?;
+		let feed = load_pda::<PriceFeed>(&accounts[4], &[b"feed", leg.ticker_mint.as_ref()])?;
+		config.check_band(Some(&feed), effective, ctx.accounts.payment_mint.decimals, now)?;
+
+		let record = load_pda::<TickerRecord>(&accounts[5], &[b"ticker", leg.ticker_mint.as_ref()])?;
+		require!(record.status == TickerStatus::Listed, ErrorCode::TickerAlreadyDelisted);
+
+		total_spent = total_spent.checked_add(fill.spent).ok_or(ErrorCode::Overflow)?;
+	}
+
+	let filled = legs.iter().all(|leg| leg.filled == leg.amount);
+
+	// «всё или ничего»: либо все позиции целиком одним отчётом, либо пустой завершающий отчёт
+	if order.all_or_none {
+		require!(report.fills.is_empty() || filled, ErrorCode::AllOrNoneViolated);
+	}
+
+	let escrowed = ctx.accounts.escrow_account.amount;
+	let fee = if report.fills.is_empty() {
+		0
+	} else {
+		order.fee.checked_sub(order.fee_paid).ok_or(ErrorCode::Overflow)?
+	};
+	let charged = total_spent.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+	require!(charged <= escrowed, ErrorCode::InsufficientEscrowBalance);
+
+	let complete = report.complete || filled;
+	let refund = if complete { escrowed - charged } else { 0 };
+
+	let maker = order.maker;
+	let order_id = order.id.to_le_bytes();
+	let signer_seeds: [&[u8]; 4] = [
+		b"basket",
+		maker.as_ref(),
+		&order_id,
+		&[ctx.bumps.basket_order],
+	];
+	let signer: &[&[&[u8]]] = &[&signer_seeds];
+
+	let token_program = ctx.accounts.token_program.to_account_info();
+	let payer = ctx.accounts.payer.to_account_info();
+	let order_info = ctx.accounts.basket_order.to_account_info();
+
+	for (i, fill) in report.fills.iter().enumerate() {
+		let leg = &legs[fill.leg as usize];
+		let accounts = &ctx.remaining_accounts[i * FILL_ACCOUNTS..(i + 1) * FILL_ACCOUNTS];
+		let (ticker_mint, maker_account, pool) = (&accounts[0], &accounts[1], &accounts[2]);
+
+		require!(ticker_mint.key() == leg.ticker_mint, ErrorCode::InvalidTickerMint);
+		assert_ata(maker_account.key(), &maker, &leg.ticker_mint)?;
+		check_pool(pool, &leg.ticker_mint, &ctx.accounts.payment_mint.key())?;
+
+		// комиссия за корзину уходит в пул первой исполненной позиции
+		let amount = if i == 0 { fill.spent + fee } else { fill.spent };
+		if amount > 0 {
+			token::transfer(
+				CpiContext::new_with_signer(
+					token_program.clone(),
+					token::Transfer {
+						from: ctx.accounts.escrow_account.to_account_info(),
+						to: pool.clone(),
+						authority: order_info.clone(),
+					},
+					signer,
+				),
+				amount,
+			)?;
+		}
+
+		token::mint_to(
+			CpiContext::new(
+				token_program.clone(),
+				token::MintTo {
+					mint: ticker_mint.clone(),
+					to: maker_account.clone(),
+					authority: payer.clone(),
+				},
+			),
+			fill.filled,
+		)?;
+
+		emit!(BasketLegExecuted {
+			id: order.id,
+			maker,
+
+			leg: fill.leg,
+			ticker_mint: leg.ticker_mint,
+			filled: fill.filled,
+			spent: fill.spent,
+			fill_price: fill.price,
+
+			broker_exec_id: report.broker_exec_id,
+			timestamp: now,
+		});
+	}
+
+	let record = &mut ctx.accounts.fill;
+	record.broker_exec_id = report.broker_exec_id;
+	record.order_id = order.id;
+	record.maker = maker;
+	record.filled = report.fills.iter().map(|fill| fill.filled).sum();
+	record.spent = total_spent;
+	record.timestamp = now;
+
+	let status = if !complete {
+		OrderStatus::PartiallyFilled
+	} else if report.fills.is_empty() && order.legs.iter().all(|leg| leg.filled == 0) {
+		OrderStatus::Rejected // брокер не исполнил ни одной позиции
+	} else {
+		OrderStatus::Filled
+	};
+
+	let order = &mut ctx.accounts.basket_order;
+	order.legs = legs;
+	order.fee_paid = order.fee_paid.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+
+	if complete {
+		finalize(
+			order,
+			status,
+			&ctx.accounts.maker,
+			&token_program,
+			ctx.bumps.basket_order,
+			(&ctx.accounts.escrow_account, &ctx.accounts.refund_account, refund),
+		)?;
+	} else {
+		order.transition(status)?;
+	}
+
+	emit!(BasketOrderExecuted {
+		id: order.id,
+		maker,
+
+		spent: total_spent,
+		fee,
+		refund,
+
+		status,
+		executor: ctx.accounts.payer.key(),
+
+		proof_cid: report.proof_cid,
+		broker_exec_id: report.broker_exec_id,
+
+		timestamp: now,
+	});
+
+	Ok(())
+}
+
+pub fn cancel_basket(ctx: Context<CancelBasketOrder>) -> Result<()> {
+	let refunded = ctx.accounts.escrow_account.amount;
+	let payer = ctx.accounts.payer.to_account_info();
+
+	finalize(
+		&mut ctx.accounts.basket_order,
+		OrderStatus::Canceled,
+		&payer,
+		&ctx.accounts.token_program.to_account_info(),
+		ctx.bumps.basket_order,
+		(&ctx.accounts.escrow_account, &ctx.accounts.refund_account, refunded),
+	)?;
+
+	let order = &ctx.accounts.basket_order;
+	emit!(BasketOrderCanceled {
+		id: order.id,
+		maker: order.maker,
+
+		refunded,
+		timestamp: Clock::get()?.unix_timestamp,
+	});
+
+	Ok(())
+}
+
+/// Частично исполненная или не начатая корзина после `expires_at` больше не исполняется:
+/// неизрасходованный эскроу возвращается мейкеру
+pub fn expire_basket(ctx: Context<ExpireBasketOrder>) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	require!(now > ctx.accounts.basket_order.expires_at, ErrorCode::OrderNotExpired);
+
+	let refunded = ctx.accounts.escrow_account.amount;
+
+	finalize(
+		&mut ctx.accounts.basket_order,
+		OrderStatus::Expired,
+		&ctx.accounts.maker,
+		&ctx.accounts.token_program.to_account_info(),
+		ctx.bumps.basket_order,
+		(&ctx.accounts.escrow_account, &ctx.accounts.refund_account, refunded),
+	)?;
+
+	let order = &ctx.accounts.basket_order;
+	emit!(BasketOrderExpired {
+		id: order.id,
+		maker: order.maker,
+
+		refunded,
+		timestamp: now,
+	});
+
+	Ok(())
+}
+
+/// Переводит корзину в терминальный статус: возвращает мейкеру остаток эскроу,
+/// закрывает эскроу и сам ордер
+fn finalize<'info>(
+	order: &mut Account<'info, BasketOrder>,
+	status: OrderStatus,
+	maker: &AccountInfo<'info>,
+	token_program: &AccountInfo<'info>,
+	order_bump: u8,
+	(escrow_account, refund_account, refund): (&Account<'info, TokenAccount>, &Account<'info, TokenAccount>, u64),
+) -> Result<()> {
+	require!(status.is_terminal(), ErrorCode::InvalidStatusTransition);
+	order.transition(status)?;
+
+	let order_id = order.id.to_le_bytes();
+	let signer_seeds: [&[u8]; 4] = [
+		b"basket",
+		order.maker.as_ref(),
+		&order_id,
+		&[order_bump],
+	];
+	let signer: &[&[&[u8]]] = &[&signer_seeds];
+
+	release(escrow_account, refund_account, maker, &order.to_account_info(), token_program, signer, refund)?;
+
+	order.close(maker.clone())
+}
+
+/// Аккаунт программы по PDA из remaining_accounts
+fn load_pda<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+	info: &'info AccountInfo<'info>,
+	seeds: &[&[u8]],
+) -> Result<Account<'info, T>> {
+	let (expected, _) = Pubkey::find_program_address(seeds, &crate::ID);
+	require!(info.key() == expected, ErrorCode::InvalidPDA);
+
+	Account::<T>::try_from(info)
+}
+
+/// Пул `['pool', ticker_mint, payment_mint]` позиции: создаётся при открытии рынка пары
+fn check_pool<'info>(pool: &'info AccountInfo<'info>, ticker_mint: &Pubkey, payment_mint: &Pubkey) -> Result<()> {
+	let (expected, _) = Pubkey::find_program_address(
+		&[b"pool", ticker_mint.as_ref(), payment_mint.as_ref()],
+		&crate::ID,
+	);
+	require!(pool.key() == expected, ErrorCode::InvalidPDA);
+
+	let account = Account::<TokenAccount>::try_from(pool)?;
+	require!(account.mint == *payment_mint, ErrorCode::InvalidPaymentMint);
+
+	Ok(())
+}
@@ -3,8 +3,9 @@ use anchor_spl::{
 	token::{self, TokenAccount, Token}
 };
 use crate::{
+	Registry,
 	errors::ErrorCode,
-	order::{types::*, state::*},
+	order::{types::*, state::*, close::finalize},
 };
 
 #[derive(Accounts)]
@@ -16,11 +17,15 @@ pub struct CancelOrder<'info> {
 	)]
     pub payer: Signer<'info>,
 
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
 	#[account(
 		mut,
         seeds = [b"order", payer.key().as_ref(), &id.to_le_bytes()],
         bump,
 		constraint = order.maker == payer.key() @ ErrorCode::Unauthorized, 
+		constraint = order.status.can_transition_to(&OrderStatus::Canceled) @ ErrorCode::OrderAlreadyProcessed,
     )]
     pub order: Account<'info, Order>,
 
@@ -42,88 +47,64 @@ pub struct CancelOrder<'info> {
 }
 
 pub fn cancel(ctx: Context<CancelOrder>) -> Result<()> {
-	let order = &ctx.accounts.order;
-	require!(order.status == OrderStatus::Pending, ErrorCode::OrderAlreadyProcessed);
+	let order = &mut ctx.accounts.order;
 
+	let refunded = ctx.accounts.escrow_account.amount;
 	refund(
 		order,
 		&ctx.accounts.escrow_account,
 		&ctx.accounts.refund_account,
 		&ctx.accounts.token_program,
 		ctx.bumps.order,
+		refunded,
 	)?;
 
-	close(
+	finalize(
 		order,
+		OrderStatus::Canceled,
 		&ctx.accounts.payer.to_account_info(),
 		&ctx.accounts.escrow_account,
 		&ctx.accounts.token_program,
 		ctx.bumps.order,
+		ctx.accounts.registry.order_retention,
 	)?;
 
 	emit!(OrderCanceled {
 		id: order.id,
 		maker: order.maker,
+
+		mint: ctx.accounts.escrow_account.mint,
+		refunded,
+
 		timestamp: Clock::get()?.unix_timestamp,
 	});
 
 	Ok(())
 }
 
-pub fn close<'info>(
-	order: &Account<'info, Order>,
-	maker_account: &AccountInfo<'info>,
-	escrow_account: &Account<'info, TokenAccount>,
-	token_program: &Program<'info, Token>,
-	order_bump: u8,
-) -> Result<()> {
-	let signer_seeds: [&[u8]; 4] = [
-		b"order",
-		order.maker.as_ref(),
-		&order.id.to_le_bytes(),
-		&[order_bump],
-	];
-	let signer: &[&[&[u8]]] = &[&signer_seeds];
-
-	let cpi_ctx_close = CpiContext::new_with_signer(
-		token_program.to_account_info(),
-		token::CloseAccount {
-			account: escrow_account.to_account_info(),
-			destination: maker_account.to_account_info(),
-			authority: order.to_account_info(),
-		},
-		signer,
-	);
-	token::close_account(cpi_ctx_close)?;
-
-	order.close(maker_account.to_account_info())
-}
-
 pub fn refund<'info>(
 	order: &Account<'info, Order>,
 	escrow_account: &Account<'info, TokenAccount>,
 	refund_account: &Account<'info, TokenAccount>,
 	token_program: &Program<'info, Token>,
 	order_bump: u8,
+	amount: u64,
 ) -> Result<()> {
-	let refund_amount: u64;
-
 	match order.side {
 		OrderSide::Buy => {
-			refund_amount = order.amount
-				.checked_mul(order.price).ok_or(ErrorCode::Overflow)?
-				.checked_add(order.fee).ok_or(ErrorCode::Overflow)?;
-
 			require!(escrow_account.mint == order.payment_mint, ErrorCode::InvalidEscrowMint);
 			require!(refund_account.mint == order.payment_mint, ErrorCode::InvalidRefundMint);
 		},
 		OrderSide::Sell => {
-			refund_amount = order.amount;
 			require!(escrow_account.mint == order.ticker_mint, ErrorCode::InvalidEscrowMint);
 			require!(refund_account.mint == order.ticker_mint, ErrorCode::InvalidRefundMint);
 		},
 	}
 
+	if amount == 0 {
+		return Ok(());
+	}
+
 	let signer_seeds: [&[u8]; 4] = [
 		b"order",
 		order.maker.as_ref(),
@@ -142,5 +123,5 @@ pub fn refund<'info>(
 		signer,
 	);
 
-	token::transfer(cpi_ctx, refund_amount)
-}
\ No newline at end of file
+	token::transfer(cpi_ctx, amount)
+}
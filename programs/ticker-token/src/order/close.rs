@@ -0,0 +1,247 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+	token::{self, TokenAccount, Token}
+};
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	order::{types::*, state::*, cancel::refund},
+};
+
+#[derive(Accounts)]
+pub struct RejectOrder<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+        seeds = [b"order", order.maker.key().as_ref(), &order.id.to_le_bytes()],
+        bump,
+		constraint = order.status.can_transition_to(&OrderStatus::Rejected) @ ErrorCode::OrderAlreadyProcessed,
+    )]
+    pub order: Account<'info, Order>,
+
+	/// CHECK: checked via constraint order.maker == maker.key()
+	#[account(
+		mut,
+		constraint = order.maker == maker.key() @ ErrorCode::InvalidMaker,
+	)]
+	pub maker: AccountInfo<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", order.key().as_ref()],
+		bump,
+		constraint = escrow_account.owner == order.key() @ ErrorCode::InvalidEscrowOwner
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	#[account(
+		mut,
+		constraint = refund_account.owner == maker.key() @ ErrorCode::InvalidRefundOwner
+	)]
+	pub refund_account: Account<'info, TokenAccount>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+/// Истечение ордера — permissionless, вызвать может кто угодно
+#[derive(Accounts)]
+pub struct ExpireOrder<'info> {
+	#[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+        seeds = [b"order", order.maker.key().as_ref(), &order.id.to_le_bytes()],
+        bump,
+		constraint = order.status.can_transition_to(&OrderStatus::Expired) @ ErrorCode::OrderAlreadyProcessed,
+    )]
+    pub order: Account<'info, Order>,
+
+	/// CHECK: checked via constraint order.maker == maker.key()
+	#[account(
+		mut,
+		constraint = order.maker == maker.key() @ ErrorCode::InvalidMaker,
+	)]
+	pub maker: AccountInfo<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", order.key().as_ref()],
+		bump,
+		constraint = escrow_account.owner == order.key() @ ErrorCode::InvalidEscrowOwner
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	#[account(
+		mut,
+		constraint = refund_account.owner == maker.key() @ ErrorCode::InvalidRefundOwner
+	)]
+	pub refund_account: Account<'info, TokenAccount>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+/// Удаление сохранённой терминальной записи — permissionless, рента возвращается мейкеру
+#[derive(Accounts)]
+pub struct CleanupOrder<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"registry"], bump)]
+    pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+        seeds = [b"order", order.maker.key().as_ref(), &order.id.to_le_bytes()],
+        bump,
+		constraint = order.status.is_terminal() @ ErrorCode::InvalidStatusTransition,
+    )]
+    pub order: Account<'info, Order>,
+
+	/// CHECK: checked via constraint order.maker == maker.key()
+	#[account(
+		mut,
+		constraint = order.maker == maker.key() @ ErrorCode::InvalidMaker,
+	)]
+	pub maker: AccountInfo<'info>,
+}
+
+/// Переводит ордер в терминальный статус: закрывает пустой эскроу и, если
+/// `retention == 0`, сам ордер. Иначе ордер остаётся записью до `cleanup_order`.
+pub fn finalize<'info>(
+	order: &mut Account<'info, Order>,
+	status: OrderStatus,
+	maker_account: &AccountInfo<'info>,
+	escrow_account: &Account<'info, TokenAccount>,
+	token_program: &Program<'info, Token>,
+	order_bump: u8,
+	retention: i64,
+) -> Result<()> {
+	require!(status.is_terminal(), ErrorCode::InvalidStatusTransition);
+
+	order.transition(status)?;
+	order.closed_at = Clock::get()?.unix_timestamp;
+	order.retain_until = order.closed_at.checked_add(retention).ok_or(ErrorCode::Overflow)?;
+
+	let signer_seeds: [&[u8]; 4] = [
+		b"order",
+		order.maker.as_ref(),
+		&order.id.to_le_bytes(),
+		&[order_bump],
+	];
+	let signer: &[&[&[u8]]] = &[&signer_seeds];
+
+	let cpi_ctx_close = CpiContext::new_with_signer(
+		token_program.to_account_info(),
+		token::CloseAccount {
+			account: escrow_account.to_account_info(),
+			destination: maker_account.to_account_info(),
+			authority: order.to_account_info(),
+		},
+		signer,
+	);
+	token::close_account(cpi_ctx_close)?;
+
+	if retention == 0 {
+		order.close(maker_account.to_account_info())?;
+	}
+
+	Ok(())
+}
+
+pub fn reject(ctx: Context<RejectOrder>) -> Result<()> {
+	let order = &mut ctx.accounts.order;
+	let refunded = ctx.accounts.escrow_account.amount;
+
+	refund(
+		order,
+		&ctx.accounts.escrow_account,
+		&ctx.accounts.refund_account,
+		&ctx.accounts.token_program,
+		ctx.bumps.order,
+		refunded,
+	)?;
+
+	finalize(
+		order,
+		OrderStatus::Rejected,
+		&ctx.accounts.maker,
+		&ctx.accounts.escrow_account,
+		&ctx.accounts.token_program,
+		ctx.bumps.order,
+		ctx.accounts.registry.order_retention,
+	)?;
+
+	emit!(OrderRejected {
+		id: order.id,
+		maker: order.maker,
+
+		mint: ctx.accounts.escrow_account.mint,
+		refunded,
+
+		timestamp: order.closed_at,
+	});
+
+	Ok(())
+}
+
+pub fn expire(ctx: Context<ExpireOrder>) -> Result<()> {
+	let order = &mut ctx.accounts.order;
+	let now = Clock::get()?.unix_timestamp;
+
+	// допустимость статуса проверяется таблицей переходов в ExpireOrder,
+	// частично исполненный ордер (в т.ч. TWAP) истекает с возвратом остатка эскроу
+	require!(now > order.expires_at, ErrorCode::OrderNotExpired);
+
+	let refunded = ctx.accounts.escrow_account.amount;
+	refund(
+		order,
+		&ctx.accounts.escrow_account,
+		&ctx.accounts.refund_account,
+		&ctx.accounts.token_program,
+		ctx.bumps.order,
+		refunded,
+	)?;
+
+	finalize(
+		order,
+		OrderStatus::Expired,
+		&ctx.accounts.maker,
+		&ctx.accounts.escrow_account,
+		&ctx.accounts.token_program,
+		ctx.bumps.order,
+		ctx.accounts.registry.order_retention,
+	)?;
+
+	emit!(OrderExpired {
+		id: order.id,
+		maker: order.maker,
+
+		mint: ctx.accounts.escrow_account.mint,
+		refunded,
+
+		timestamp: now,
+	});
+
+	Ok(())
+}
+
+pub fn cleanup(ctx: Context<CleanupOrder>) -> Result<()> {
+	let order = &ctx.accounts.order;
+	let now = Clock::get()?.unix_timestamp;
+
+	// срок хранения фиксируется при закрытии ордера, смена registry.order_retention на него не влияет
+	require!(now >= order.retain_until, ErrorCode::RetentionNotElapsed);
+
+	order.close(ctx.accounts.maker.to_account_info())
+}
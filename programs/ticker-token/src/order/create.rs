@@ -5,7 +5,12 @@ use anchor_spl::{
 use crate::{
 	Registry,
 	errors::ErrorCode,
-	utils::{verify_ed25519_ix},
+	utils::{verify_oracle_quorum, SignedMessage},
+	feed::PriceFeed,
+	config::TickerConfig,
+	calendar::MarketCalendar,
+	market::Market,
+	ticker::{TickerRecord, TickerStatus},
 	order::{types::*, state::*},
 };
 
@@ -67,6 +72,31 @@ pub struct CreateBuyOrder<'info> {
     )]
     pub escrow_account: Account<'info, TokenAccount>,	
 
+	/// Цена тикера от оракулов, лимитная цена payload сверяется с ней
+	#[account(seeds = [b"feed", ticker_mint_account.key().as_ref()], bump = price_feed.bump)]
+	pub price_feed: Box<Account<'info, PriceFeed>>,
+
+	#[account(seeds = [b"config", ticker_mint_account.key().as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Account<'info, TickerConfig>,
+
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Account<'info, MarketCalendar>,
+
+	/// Открытый рынок пары (тикер, платёжный токен)
+	#[account(
+		seeds = [b"market", payload.ticker_mint.as_ref(), payload.payment_mint.as_ref()],
+		bump = market.bump,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	/// Тикер не делистингован
+	#[account(
+		seeds = [b"ticker", payload.ticker_mint.as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
 	pub rent: Sysvar<'info, Rent>,
 
 	/// CHECK: instruction sysvar, used for verifying oracle signature
@@ -129,6 +159,31 @@ pub struct CreateSellOrder<'info> {
 	)]
 	pub escrow_account: Account<'info, TokenAccount>,
 
+	/// Цена тикера от оракулов, лимитная цена payload сверяется с ней
+	#[account(seeds = [b"feed", ticker_mint_account.key().as_ref()], bump = price_feed.bump)]
+	pub price_feed: Box<Account<'info, PriceFeed>>,
+
+	#[account(seeds = [b"config", ticker_mint_account.key().as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Account<'info, TickerConfig>,
+
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Account<'info, MarketCalendar>,
+
+	/// Открытый рынок пары (тикер, платёжный токен)
+	#[account(
+		seeds = [b"market", payload.ticker_mint.as_ref(), payload.payment_mint.as_ref()],
+		bump = market.bump,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	/// Тикер не делистингован
+	#[account(
+		seeds = [b"ticker", payload.ticker_mint.as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
 	pub rent: Sysvar<'info, Rent>,
 
 	/// CHECK: instruction sysvar, used for verifying oracle signature
@@ -148,6 +203,8 @@ pub fn create<'info>(
 
 	order: &mut Account<'info, Order>,
 	amount: u64,
+	price_scale: u64,
+	calendar: &MarketCalendar,
 
 	maker_token_account: AccountInfo<'info>,
 	escrow_token_account: AccountInfo<'info>,
@@ -158,55 +215,99 @@ pub fn create<'info>(
 	// Payload не устарел
 	let now = Clock::get()?.unix_timestamp;
 	require!(now <= payload.expires_at, ErrorCode::PayloadExpired);
-	
-	// Проверка подписи от оракула
-	let mut serialized = vec![];
-	payload.serialize(&mut serialized)?;
 
-	verify_ed25519_ix(&instruction_sysvar, &registry.authority, &serialized)?;
+	// рыночные заявки — только в торговую сессию
+	require!(!payload.market || calendar.is_open(now), ErrorCode::MarketClosed);
+	
+	// Проверка подписей оракулов (k из n)
+	let serialized = payload.message()?;
 
-	let cpi_ctx = CpiContext::new(
-		token_program.to_account_info(),
-		token::Transfer {
-			from: maker_token_account.to_account_info(),
-			to: escrow_token_account.to_account_info(),
-			authority: payer.to_account_info(),
-		},
-	);
-	token::transfer(cpi_ctx, amount)?;
+	verify_oracle_quorum(&instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
 
 	// Записываем данные в OrderState
 	order.id = payload.id;
 
 	order.side = side;
 	order.market = payload.market;
+	order.mode = payload.mode;
 
 	order.maker = payer.key();
 	
 	order.ticker_mint = payload.ticker_mint;
-	order.amount = payload.amount;
+	order.amount = amount;
 	
 	order.payment_mint = payload.payment_mint;
 	order.price = payload.price;
+	order.price_scale = price_scale;
 	order.fee = payload.fee;
+
+	// stop исполняется по рынку, stop-limit — по лимитной цене ордера
+	if let Some(trigger) = payload.trigger {
+		match trigger.kind {
+			TriggerType::Stop => require!(payload.market, ErrorCode::InvalidTrigger),
+			TriggerType::StopLimit => require!(!payload.market, ErrorCode::InvalidTrigger),
+			TriggerType::TakeProfit => {},
+		}
+		require!(trigger.price > 0, ErrorCode::InvalidTrigger);
+	}
+	order.trigger = payload.trigger;
 	
 	order.status = OrderStatus::Pending;
-	order.expires_at = payload.expires_at;
+	order.expires_at = match payload.time_in_force {
+		TimeInForce::Gtc => payload.expires_at,
+		TimeInForce::Day => {
+			require!(payload.twap.is_none(), ErrorCode::InvalidTimeInForce);
+			calendar.next_close(now)?.min(payload.expires_at)
+		}
+	};
+
+	// TWAP-ордер живёт до конца графика
+	if let Some(twap) = payload.twap {
+		require!(
+			twap.slices > 0 && twap.start_at < twap.end_at && now < twap.end_at,
+			ErrorCode::InvalidTwapSchedule
+		);
+		order.expires_at = twap.end_at;
+	}
+	order.twap = payload.twap;
+	order.allow_partial = payload.allow_partial;
+
+	// Блокируем средства мейкера в эскроу
+	let cpi_ctx = CpiContext::new(
+		token_program.to_account_info(),
+		token::Transfer {
+			from: maker_token_account.to_account_info(),
+			to: escrow_token_account.to_account_info(),
+			authority: payer.to_account_info(),
+		},
+	);
+	token::transfer(cpi_ctx, order.escrow_amount()?)?;
 
 	emit!(OrderCreated {
 		id: payload.id,
 		maker: order.maker,
 		timestamp: now,
-		expires_at: payload.expires_at,
+		expires_at: order.expires_at,
 	});
 
 	Ok(())
 }
 
+/// Отклонение цены и коридор проверяются только для лимитного ордера и только если заданы:
+/// без них фид не читается, и ордер создаётся без свежей цены
+fn check_feed(feed: &PriceFeed, config: &TickerConfig, payload: &OrderPayload, payment_decimals: u8) -> Result<()> {
+	if payload.market {
+		return Ok(());
+	}
+	config.check_price(feed, payload.price, payment_decimals, Clock::get()?.unix_timestamp)
+}
+
 pub fn buy(ctx: Context<CreateBuyOrder>, payload: OrderPayload) -> Result<()> {
-	let amount = payload.amount
-		.checked_mul(payload.price).ok_or(ErrorCode::Overflow)?
-		.checked_add(payload.fee).ok_or(ErrorCode::Overflow)?;
+	require!(payload.mode != OrderMode::All, ErrorCode::InvalidOrderMode);
+	check_feed(&ctx.accounts.price_feed, &ctx.accounts.ticker_config, &payload, ctx.accounts.payment_mint_account.decimals)?;
+	let price_scale = ctx.accounts.market.price_scale;
+	ctx.accounts.market.check_order(&payload, payload.amount)?;
+	ctx.accounts.ticker_config.check_order(&payload, payload.amount, price_scale)?;
 
 	create(
 		&payload, OrderSide::Buy,
@@ -215,7 +316,9 @@ pub fn buy(ctx: Context<CreateBuyOrder>, payload: OrderPayload) -> Result<()> {
 		&ctx.accounts.registry,
 
 		&mut ctx.accounts.order,
-		amount,
+		payload.amount,
+		price_scale,
+		&ctx.accounts.calendar,
 
 		ctx.accounts.maker_payment_account.to_account_info(),
 		ctx.accounts.escrow_account.to_account_info(),
@@ -226,6 +329,18 @@ pub fn buy(ctx: Context<CreateBuyOrder>, payload: OrderPayload) -> Result<()> {
 }
 
 pub fn sell(ctx: Context<CreateSellOrder>, payload: OrderPayload) -> Result<()> {
+	check_feed(&ctx.accounts.price_feed, &ctx.accounts.ticker_config, &payload, ctx.accounts.payment_mint_account.decimals)?;
+	let price_scale = ctx.accounts.market.price_scale;
+
+	// «продать всё» — блокируем весь текущий баланс тикера мейкера
+	let amount = match payload.mode {
+		OrderMode::All => ctx.accounts.maker_ticker_account.amount,
+		_ => payload.amount,
+	};
+	require!(amount > 0, ErrorCode::InvalidSellAmount);
+	ctx.accounts.market.check_order(&payload, amount)?;
+	ctx.accounts.ticker_config.check_order(&payload, amount, price_scale)?;
+
 	create(
 		&payload, OrderSide::Sell,
 		
@@ -233,7 +348,9 @@ pub fn sell(ctx: Context<CreateSellOrder>, payload: OrderPayload) -> Result<()>
 		&ctx.accounts.registry,
 
 		&mut ctx.accounts.order,
-		payload.amount,
+		amount,
+		price_scale,
+		&ctx.accounts.calendar,
 		
 		ctx.accounts.maker_ticker_account.to_account_info(),
 		ctx.accounts.escrow_account.to_account_info(),
@@ -0,0 +1,343 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+	token::{self, TokenAccount, Token, Mint},
+};
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	price::{self, Rounding},
+	utils::{verify_oracle_quorum, SignedMessage},
+	feed::PriceFeed,
+	calendar::MarketCalendar,
+	config::TickerConfig,
+	market::{Market, MarketStatus},
+	ticker::{TickerRecord, TickerStatus},
+	order::{types::*, state::*, close::finalize, execute::MAX_ATTESTATION_AGE},
+};
+
+#[derive(Accounts)]
+pub struct CrossOrders<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+		seeds = [b"order", buy_order.maker.as_ref(), &buy_order.id.to_le_bytes()],
+		bump,
+		constraint = buy_order.side == OrderSide::Buy @ ErrorCode::OrdersNotCrossable,
+		constraint = buy_order.status.can_transition_to(&OrderStatus::Filled) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub buy_order: Box<Account<'info, Order>>,
+
+	#[account(
+		mut,
+		seeds = [b"order", sell_order.maker.as_ref(), &sell_order.id.to_le_bytes()],
+		bump,
+		constraint = sell_order.side == OrderSide::Sell @ ErrorCode::OrdersNotCrossable,
+		constraint = sell_order.status.can_transition_to(&OrderStatus::Filled) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub sell_order: Box<Account<'info, Order>>,
+
+	/// CHECK: checked via constraint buy_order.maker == buyer.key()
+	#[account(mut, constraint = buy_order.maker == buyer.key() @ ErrorCode::InvalidMaker)]
+	pub buyer: AccountInfo<'info>,
+
+	/// CHECK: checked via constraint sell_order.maker == seller.key()
+	#[account(mut, constraint = sell_order.maker == seller.key() @ ErrorCode::InvalidMaker)]
+	pub seller: AccountInfo<'info>,
+
+	#[account(mut, seeds = [b"escrow", buy_order.key().as_ref()], bump)]
+	pub buy_escrow: Box<Account<'info, TokenAccount>>,
+
+	#[account(mut, seeds = [b"escrow", sell_order.key().as_ref()], bump)]
+	pub sell_escrow: Box<Account<'info, TokenAccount>>,
+
+	/// Куда покупатель получает тикер
+	#[account(
+		mut,
+		constraint = buyer_ticker_account.owner == buyer.key() @ ErrorCode::InvalidMakerAccount,
+		constraint = buyer_ticker_account.mint == ticker_mint.key() @ ErrorCode::InvalidMakerMint,
+	)]
+	pub buyer_ticker_account: Box<Account<'info, TokenAccount>>,
+
+	/// Куда возвращается сдача покупателю при завершении
+	#[account(
+		mut,
+		constraint = buyer_refund_account.owner == buyer.key() @ ErrorCode::InvalidRefundAccount,
+		constraint = buyer_refund_account.mint == payment_mint.key() @ ErrorCode::InvalidRefundMint,
+	)]
+	pub buyer_refund_account: Box<Account<'info, TokenAccount>>,
+
+	/// Куда продавец получает оплату
+	#[account(
+		mut,
+		constraint = seller_payment_account.owner == seller.key() @ ErrorCode::InvalidMakerAccount,
+		constraint = seller_payment_account.mint == payment_mint.key() @ ErrorCode::InvalidMakerMint,
+	)]
+	pub seller_payment_account: Box<Account<'info, TokenAccount>>,
+
+	/// Куда продавцу возвращается непроданный тикер, когда цель продажи на сумму достигнута
+	#[account(
+		mut,
+		constraint = seller_refund_account.owner == seller.key() @ ErrorCode::InvalidRefundAccount,
+		constraint = seller_refund_account.mint == ticker_mint.key() @ ErrorCode::InvalidRefundMint,
+	)]
+	pub seller_refund_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(constraint = ticker_mint.key() == buy_order.ticker_mint @ ErrorCode::InvalidTickerMint)]
+	pub ticker_mint: Box<Account<'info, Mint>>,
+
+	#[account(constraint = payment_mint.key() == buy_order.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint: Box<Account<'info, Mint>>,
+
+	/// Открытый рынок пары (тикер, платёжный токен)
+	#[account(
+		seeds = [b"market", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump = market.bump,
+		constraint = market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	/// Тикер не делистингован
+	#[account(
+		seeds = [b"ticker", ticker_mint.key().as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
+	#[account(seeds = [b"config", ticker_mint.key().as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Box<Account<'info, TickerConfig>>,
+
+	/// Цена тикера от оракулов; нужна, если у тикера задан коридор цен
+	#[account(seeds = [b"feed", ticker_mint.key().as_ref()], bump = price_feed.bump)]
+	pub price_feed: Option<Box<Account<'info, PriceFeed>>>,
+
+	/// Торговая сессия: рыночные заявки создаются и исполняются только при открытой бирже
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Box<Account<'info, MarketCalendar>>,
+
+	/// Пул получает только комиссии обеих сторон
+	#[account(
+		init_if_needed,
+		payer = payer,
+		seeds = [b"pool", ticker_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		token::mint = payment_mint,
+		token::authority = payer,
+	)]
+	pub pool: Box<Account<'info, TokenAccount>>,
+
+	/// CHECK: instruction sysvar, used for verifying oracle price attestation
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+	pub token_program: Program<'info, Token>,
+	pub system_program: Program<'info, System>,
+}
+
+/// Сводит встречные ордера на покупку и продажу одного тикера без брокера:
+/// оплата из эскроу покупателя уходит продавцу, тикер из эскроу продавца — покупателю,
+/// без минта и сжигания. `price` должна быть в пределах лимитов обоих ордеров,
+/// а если хотя бы одна сторона рыночная — совпадать со свежей ценой от кворума оракулов.
+pub fn cross<'info>(
+	ctx: Context<'_, '_, '_, 'info, CrossOrders<'info>>,
+	quantity: u64,
+	price: u64,
+	attestation: Option<PriceAttestation>,
+) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let buy = &ctx.accounts.buy_order;
+	let sell = &ctx.accounts.sell_order;
+
+	require!(
+		buy.ticker_mint == sell.ticker_mint && buy.payment_mint == sell.payment_mint,
+		ErrorCode::OrdersNotCrossable
+	);
+	require!(buy.maker != sell.maker, ErrorCode::SelfCrossNotAllowed);
+	require!(now <= buy.expires_at && now <= sell.expires_at, ErrorCode::OrderExpired);
+	// условные заявки сводятся только после срабатывания
+	require!(buy.trigger.is_none() || buy.status != OrderStatus::Pending, ErrorCode::TriggerNotReached);
+	require!(sell.trigger.is_none() || sell.status != OrderStatus::Pending, ErrorCode::TriggerNotReached);
+	require!(quantity > 0 && quantity <= ctx.accounts.sell_escrow.amount, ErrorCode::InvalidFillAmount);
+
+	// цена в пределах обоих лимитов
+	require!(buy.market || price <= buy.price, ErrorCode::LimitPriceViolated);
+	require!(sell.market || price >= sell.price, ErrorCode::LimitPriceViolated);
+
+	// у рыночной стороны нет лимита: цену сведения подтверждает кворум оракулов
+	if buy.market || sell.market {
+		require!(ctx.accounts.calendar.is_open(now), ErrorCode::MarketClosed);
+
+		let attestation = attestation.ok_or(ErrorCode::InvalidPriceAttestation)?;
+
+		let serialized = attestation.message()?;
+
+		let registry = &ctx.accounts.registry;
+		verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+		require!(
+			attestation.ticker_mint == buy.ticker_mint && attestation.payment_mint == buy.payment_mint,
+			ErrorCode::InvalidPriceAttestation
+		);
+		require!(
+			attestation.timestamp <= now && now - attestation.timestamp <= MAX_ATTESTATION_AGE,
+			ErrorCode::InvalidPriceAttestation
+		);
+		require!(price == attestation.price, ErrorCode::InvalidPriceAttestation);
+	}
+
+	// цена сведения в коридоре limit-up/limit-down
+	ctx.accounts.ticker_config.check_band(ctx.accounts.price_feed.as_deref().map(|feed| &**feed), price, ctx.accounts.payment_mint.decimals, now)?;
+
+	let mut cost = price::notional(quantity, price, buy.price_scale, Rounding::Up)?;
+
+	// объём покупки: количество или сумма
+	match buy.mode {
+		OrderMode::Quantity => require!(
+			buy.filled.checked_add(quantity).ok_or(ErrorCode::Overflow)? <= buy.amount,
+			ErrorCode::InvalidFillAmount
+		),
+		OrderMode::Notional => require!(
+			buy.spent.checked_add(cost).ok_or(ErrorCode::Overflow)? <= buy.amount,
+			ErrorCode::InsufficientEscrowBalance
+		),
+		OrderMode::All => return err!(ErrorCode::InvalidOrderMode),
+	}
+
+	// TWAP: накопленное исполнение не опережает график
+	for (order, filled, spent) in [(buy, quantity, cost), (sell, quantity, cost)] {
+		if let Some(twap) = order.twap {
+			let progress = order.progress(filled, spent)?;
+			require!(progress <= twap.allowed(order.amount, now)?, ErrorCode::TwapScheduleExceeded);
+		}
+	}
+
+	let buy_complete = match buy.mode {
+		OrderMode::Quantity => buy.filled + quantity == buy.amount,
+		// на сумму: завершён, когда остатка не хватает даже на один шаг количества
+		_ => {
+			let step = ctx.accounts.ticker_config.quantity_step.max(1);
+			let step_cost = price::notional(step, price, buy.price_scale, Rounding::Up)?;
+			buy.amount - (buy.spent + cost) < step_cost
+		}
+	};
+
+	// цель продажи: выручка не меньше amount для Notional, продан весь эскроу для остальных
+	let sell_escrowed = ctx.accounts.sell_escrow.amount;
+	let sell_complete = match sell.mode {
+		OrderMode::Notional => sell.spent.checked_add(cost).ok_or(ErrorCode::Overflow)? >= sell.amount,
+		_ => quantity == sell_escrowed,
+	};
+	// без явного разрешения в payload (и вне TWAP) продажа исполняется только целиком
+	require!(
+		sell_complete || sell.allow_partial || sell.twap.is_some(),
+		ErrorCode::PartialFillNotAllowed
+	);
+
+	// комиссии: покупатель платит из эскроу сверх стоимости, продавец — из выручки
+	let buyer_fee = buy.fee.checked_sub(buy.fee_paid).ok_or(ErrorCode::Overflow)?;
+	let buy_escrowed = ctx.accounts.buy_escrow.amount;
+
+	// завершающее сведение: округление вверх каждой частичной стоимости
+	// не должно вывести накопленную сумму за пределы эскроу покупателя
+	if buy_complete {
+		cost = cost.min(buy_escrowed.saturating_sub(buyer_fee));
+	}
+	let seller_fee = sell.fee.checked_sub(sell.fee_paid).ok_or(ErrorCode::Overflow)?.min(cost);
+
+	let charged = cost.checked_add(buyer_fee).ok_or(ErrorCode::Overflow)?;
+	require!(charged <= buy_escrowed, ErrorCode::InsufficientEscrowBalance);
+
+	let buy_id = buy.id.to_le_bytes();
+	let buy_seeds: [&[u8]; 4] = [b"order", buy.maker.as_ref(), &buy_id, &[ctx.bumps.buy_order]];
+	let sell_id = sell.id.to_le_bytes();
+	let sell_seeds: [&[u8]; 4] = [b"order", sell.maker.as_ref(), &sell_id, &[ctx.bumps.sell_order]];
+
+	let token_program = ctx.accounts.token_program.to_account_info();
+	let buy_info = buy.to_account_info();
+	let sell_info = sell.to_account_info();
+
+	let escrow_transfer = |from: &Account<'info, TokenAccount>, to: AccountInfo<'info>, authority: &AccountInfo<'info>, seeds: &[&[u8]], amount: u64| -> Result<()> {
+		if amount == 0 {
+			return Ok(());
+		}
+		token::transfer(
+			CpiContext::new_with_signer(
+				token_program.clone(),
+				token::Transfer {
+					from: from.to_account_info(),
+					to,
+					authority: authority.clone(),
+				},
+				&[seeds],
+			),
+			amount,
+		)
+	};
+
+	// оплата продавцу за вычетом его комиссии, комиссии обеих сторон — в пул
+	escrow_transfer(&ctx.accounts.buy_escrow, ctx.accounts.seller_payment_account.to_account_info(), &buy_info, &buy_seeds, cost - seller_fee)?;
+	escrow_transfer(&ctx.accounts.buy_escrow, ctx.accounts.pool.to_account_info(), &buy_info, &buy_seeds, buyer_fee + seller_fee)?;
+	// тикер покупателю
+	escrow_transfer(&ctx.accounts.sell_escrow, ctx.accounts.buyer_ticker_account.to_account_info(), &sell_info, &sell_seeds, quantity)?;
+
+	// сдача покупателю и непроданный тикер продавцу при завершении
+	let buy_refund = if buy_complete { buy_escrowed - charged } else { 0 };
+	escrow_transfer(&ctx.accounts.buy_escrow, ctx.accounts.buyer_refund_account.to_account_info(), &buy_info, &buy_seeds, buy_refund)?;
+	let sell_refund = if sell_complete { sell_escrowed - quantity } else { 0 };
+	escrow_transfer(&ctx.accounts.sell_escrow, ctx.accounts.seller_refund_account.to_account_info(), &sell_info, &sell_seeds, sell_refund)?;
+
+	let retention = ctx.accounts.registry.order_retention;
+
+	let buy = &mut ctx.accounts.buy_order;
+	buy.filled = buy.filled.checked_add(quantity).ok_or(ErrorCode::Overflow)?;
+	buy.spent = buy.spent.checked_add(cost).ok_or(ErrorCode::Overflow)?;
+	buy.fee_paid = buy.fee_paid.checked_add(buyer_fee).ok_or(ErrorCode::Overflow)?;
+	if buy_complete {
+		finalize(buy, OrderStatus::Filled, &ctx.accounts.buyer, &ctx.accounts.buy_escrow, &ctx.accounts.token_program, ctx.bumps.buy_order, retention)?;
+	} else {
+		buy.transition(OrderStatus::PartiallyFilled)?;
+	}
+
+	let sell = &mut ctx.accounts.sell_order;
+	sell.filled = sell.filled.checked_add(quantity).ok_or(ErrorCode::Overflow)?;
+	sell.spent = sell.spent.checked_add(cost).ok_or(ErrorCode::Overflow)?;
+	sell.fee_paid = sell.fee_paid.checked_add(seller_fee).ok_or(ErrorCode::Overflow)?;
+	if sell_complete {
+		finalize(sell, OrderStatus::Filled, &ctx.accounts.seller, &ctx.accounts.sell_escrow, &ctx.accounts.token_program, ctx.bumps.sell_order, retention)?;
+	} else {
+		sell.transition(OrderStatus::PartiallyFilled)?;
+	}
+
+	emit!(OrdersCrossed {
+		buy_id: ctx.accounts.buy_order.id,
+		buyer: ctx.accounts.buyer.key(),
+		sell_id: ctx.accounts.sell_order.id,
+		seller: ctx.accounts.seller.key(),
+
+		ticker_mint: ctx.accounts.ticker_mint.key(),
+		quantity,
+
+		payment_mint: ctx.accounts.payment_mint.key(),
+		price,
+		cost,
+		buyer_fee,
+		seller_fee,
+		buyer_refund: buy_refund,
+		seller_refund: sell_refund,
+
+		buy_status: ctx.accounts.buy_order.status,
+		sell_status: ctx.accounts.sell_order.status,
+		executor: ctx.accounts.payer.key(),
+
+		timestamp: now,
+	});
+
+	Ok(())
+}
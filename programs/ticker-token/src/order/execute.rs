@@ -4,8 +4,15 @@ use anchor_spl::{
 };
 use crate::{
 	Registry,
+	OracleKey,
 	errors::ErrorCode,
-	order::{types::*, state::*, cancel::close},
+	price::{self, Rounding},
+	utils::{verify_oracle_quorum, assert_cid, SignedMessage},
+	feed::PriceFeed,
+	config::TickerConfig,
+	calendar::MarketCalendar,
+	ticker::{TickerRecord, TickerStatus},
+	order::{types::*, state::*, close::finalize},
 };
 
 #[derive(Accounts)]
@@ -23,13 +30,17 @@ pub struct ProcessOrder<'info> {
 		mut,
         seeds = [b"order", order.maker.key().as_ref(), &order.id.to_le_bytes()],
         bump,
-		constraint = order.status == OrderStatus::Pending @ ErrorCode::OrderAlreadyProcessed,
+		constraint = order.status.can_transition_to(&OrderStatus::Processing) @ ErrorCode::OrderAlreadyProcessed,
     )]
     pub order: Account<'info, Order>,
+
+	/// CHECK: instruction sysvar, used for verifying oracle price attestation
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(order_id: u64)]
+#[instruction(report: ExecutionReport)]
 pub struct ExecuteOrder<'info> {
 	#[account(
 		mut,
@@ -42,11 +53,10 @@ pub struct ExecuteOrder<'info> {
 
 	#[account(
 		mut,
-		seeds = [b"order", maker.key().as_ref(), &order_id.to_le_bytes()],
+		seeds = [b"order", maker.key().as_ref(), &report.order_id.to_le_bytes()],
 		bump,
-		// order status must be Processing or Pending
-		constraint = order.status == OrderStatus::Processing || order.status == OrderStatus::Pending @ ErrorCode::OrderAlreadyProcessed,
-		close = payer,
+		// order status must be Pending, Processing or PartiallyFilled
+		constraint = order.status.can_transition_to(&OrderStatus::Filled) @ ErrorCode::OrderAlreadyProcessed,
 	)]
 	pub order: Account<'info, Order>,
 
@@ -66,9 +76,9 @@ pub struct ExecuteOrder<'info> {
 	#[account(
 		mut,
 		constraint = refund_account.owner == maker.key() @ ErrorCode::InvalidRefundAccount,
-		constraint = refund_account.mint == order.payment_mint @ ErrorCode::InvalidRefundMint,
+		constraint = refund_account.mint == escrow_account.mint @ ErrorCode::InvalidRefundMint,
 	)]
-	pub refund_account: Account<'info, TokenAccount>, // Куда возвращать сдачу (если осталась)
+	pub refund_account: Account<'info, TokenAccount>, // Куда возвращать остаток эскроу (сдачу или непроданный тикер)
 
 	#[account(
 		mut,
@@ -87,6 +97,44 @@ pub struct ExecuteOrder<'info> {
 	)]
 	pub ticker_mint: Account<'info, Mint>,
 
+	/// Запись об использованном идентификаторе сделки брокера, не даёт учесть её повторно
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"fill", report.broker_exec_id.as_ref()],
+		bump,
+		space = 8 + std::mem::size_of::<Fill>(),
+	)]
+	pub fill: Account<'info, Fill>,
+
+	/// Опциональное хранение proof CID on-chain
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"proof", report.broker_exec_id.as_ref()],
+		bump,
+		space = ExecutionProof::SPACE,
+	)]
+	pub execution_proof: Option<Account<'info, ExecutionProof>>,
+
+	#[account(seeds = [b"config", ticker_mint.key().as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Box<Account<'info, TickerConfig>>,
+
+	/// Тикер не делистингован
+	#[account(
+		seeds = [b"ticker", ticker_mint.key().as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Box<Account<'info, MarketCalendar>>,
+
+	/// Цена тикера от оракулов; нужна, если у тикера задан коридор цен
+	#[account(seeds = [b"feed", ticker_mint.key().as_ref()], bump = price_feed.bump)]
+	pub price_feed: Option<Box<Account<'info, PriceFeed>>>,
+
 	#[account(
     	init_if_needed,
     	payer = payer,
@@ -97,27 +145,121 @@ pub struct ExecuteOrder<'info> {
 	)]
 	pub pool: Account<'info, TokenAccount>,
 
-	/// CHECK: instruction sysvar, used for verifying oracle signature
+	/// CHECK: instruction sysvar, used for verifying broker signature
 	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
     pub instruction_sysvar: AccountInfo<'info>,
 	pub token_program: Program<'info, Token>,
 	pub system_program: Program<'info, System>,
 }
 
-pub fn process(ctx: Context<ProcessOrder>) -> Result<()> {
-	ctx.accounts.order.status = OrderStatus::Processing;
+/// Максимальный возраст аттестации цены для срабатывания условной заявки, секунд
+pub const MAX_ATTESTATION_AGE: i64 = 60;
+
+pub fn process(ctx: Context<ProcessOrder>, attestation: Option<PriceAttestation>) -> Result<()> {
+	let order = &ctx.accounts.order;
+	let now = Clock::get()?.unix_timestamp;
+
+	// условная заявка: нужна свежая цена от кворума оракулов, пересёкшая уровень
+	if let Some(trigger) = order.trigger {
+		let attestation = attestation.ok_or(ErrorCode::InvalidPriceAttestation)?;
+
+		let serialized = attestation.message()?;
+
+		let registry = &ctx.accounts.registry;
+		verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+		require!(
+			attestation.ticker_mint == order.ticker_mint && attestation.payment_mint == order.payment_mint,
+			ErrorCode::InvalidPriceAttestation
+		);
+		require!(
+			attestation.timestamp <= now && now - attestation.timestamp <= MAX_ATTESTATION_AGE,
+			ErrorCode::InvalidPriceAttestation
+		);
+		require!(trigger.is_crossed(order.side, attestation.price), ErrorCode::TriggerNotReached);
+
+		emit!(OrderTriggered {
+			id: order.id,
+			maker: order.maker,
+
+			trigger,
+			price: attestation.price,
+
+			timestamp: now,
+		});
+	}
+
+	ctx.accounts.order.transition(OrderStatus::Processing)?;
 
 	emit!(OrderProcessing {
 		id: ctx.accounts.order.id,
 		maker: ctx.accounts.order.maker,
-		timestamp: Clock::get()?.unix_timestamp,
+		timestamp: now,
 	});
 
 	Ok(())
 }
 
-pub fn execute<'info>(ctx: Context<ExecuteOrder>, spent: u64, proof_cid: Vec<u8>) -> Result<()> {
+pub fn execute(ctx: Context<ExecuteOrder>, report: ExecutionReport) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+
+	// Проверка подписи брокера под отчётом об исполнении
+	let serialized = report.message()?;
+
+	verify_oracle_quorum(
+		&ctx.accounts.instruction_sysvar,
+		&[OracleKey::Ed25519(ctx.accounts.registry.broker)], 1,
+		&serialized,
+	)?;
+
 	let order = &ctx.accounts.order;
+
+	require!(report.order_id == order.id && report.maker == order.maker, ErrorCode::InvalidExecutionReport);
+	require!(report.timestamp <= now, ErrorCode::InvalidReportTimestamp);
+	// после `expires_at` остаток возвращается только через `expireOrder`
+	require!(now <= order.expires_at, ErrorCode::OrderExpired);
+	// пустой отчёт допустим только как завершающий для частично исполненного ордера
+	require!(report.filled > 0 || (report.complete && order.filled > 0), ErrorCode::InvalidFillAmount);
+	assert_cid(&report.proof_cid)?;
+
+	// рыночная заявка исполняется только в торговую сессию
+	require!(!order.market || ctx.accounts.calendar.is_open(now), ErrorCode::MarketClosed);
+
+	// фактическая средняя цена исполнения (spent / filled) в коридоре limit-up/limit-down,
+	// заявленная брокером `report.price` не проверяется
+	if report.filled > 0 {
+		let effective = price::mul_div(report.spent, order.price_scale, report.filled, Rounding::Down)?;
+		ctx.accounts.ticker_config.check_band(ctx.accounts.price_feed.as_deref().map(|feed| &**feed), effective, ctx.accounts.payment_mint.decimals, now)?;
+	}
+
+	// условная заявка исполняется только после срабатывания в process_order
+	require!(order.trigger.is_none() || order.status != OrderStatus::Pending, ErrorCode::TriggerNotReached);
+
+	let spent = report.spent;
+
+	// TWAP-ордер не завершается по отчёту брокера до конца графика
+	let report_complete = match order.twap {
+		Some(twap) => report.complete && now >= twap.end_at,
+		None => report.complete,
+	};
+
+	let fill = &mut ctx.accounts.fill;
+	fill.broker_exec_id = report.broker_exec_id;
+	fill.order_id = order.id;
+	fill.maker = order.maker;
+	fill.filled = report.filled;
+	fill.spent = report.spent;
+	fill.timestamp = now;
+
+	if let Some(proof) = ctx.accounts.execution_proof.as_mut() {
+		proof.broker_exec_id = report.broker_exec_id;
+		proof.order_id = order.id;
+		proof.maker = order.maker;
+		proof.ticker_mint = order.ticker_mint;
+		proof.cid = report.proof_cid.clone();
+		proof.timestamp = now;
+	}
+
 	let signer_seeds: [&[u8]; 4] = [
 		b"order",
 		order.maker.as_ref(),
@@ -126,117 +268,217 @@ pub fn execute<'info>(ctx: Context<ExecuteOrder>, spent: u64, proof_cid: Vec<u8>
 	];
 	let signer: &[&[&[u8]]] = &[&signer_seeds];
 
-	
-	match order.side {
+	let escrowed = ctx.accounts.escrow_account.amount;
+	// неудержанная часть комиссии
+	let fee_due = order.fee.checked_sub(order.fee_paid).ok_or(ErrorCode::Overflow)?;
+
+	// (исполнено в этом отчёте, удержанная комиссия, возврат из эскроу, ордер завершён)
+	let (filled, fee, refund, complete) = match order.side {
 		OrderSide::Buy => {
 			require!(ctx.accounts.escrow_account.mint == order.payment_mint, ErrorCode::InvalidEscrowMint);
 			require!(ctx.accounts.maker_account.mint == order.ticker_mint, ErrorCode::InvalidMakerMint);
 
-			// сумма которую потртил брокер + наша комиссия (уже включает комиссию брокера)
-			let amount = spent.checked_add(order.fee).ok_or(ErrorCode::Overflow)?;	
-			require!(amount <= ctx.accounts.escrow_account.amount, ErrorCode::InsufficientEscrowBalance);
-
-			// перевод пдатежа из эскроу на пулл
-			let cpi_ctx = CpiContext::new_with_signer(
-				ctx.accounts.token_program.to_account_info(),
-				token::Transfer {
-					from: ctx.accounts.escrow_account.to_account_info(),
-					to: ctx.accounts.pool.to_account_info(),
-					authority: order.to_account_info(),
-				},
-				signer,
-			);
-			token::transfer(cpi_ctx, amount)?;
+			// сколько тикера минтим мейкеру
+			let minted = match order.mode {
+				OrderMode::Quantity => {
+					let total = order.filled.checked_add(report.filled).ok_or(ErrorCode::Overflow)?;
+					require!(total <= order.amount, ErrorCode::InvalidFillAmount);
+					report.filled
+				}
+				OrderMode::All => return err!(ErrorCode::InvalidOrderMode),
+				// на сумму: дробное количество по средней цене исполнения, не больше отчёта брокера
+				OrderMode::Notional => {
+					let total = order.spent.checked_add(spent).ok_or(ErrorCode::Overflow)?;
+					require!(total <= order.amount, ErrorCode::InsufficientEscrowBalance);
+
+					let shares = if spent > 0 {
+						price::quantity(spent, report.price, order.price_scale, Rounding::Down)?
+					} else {
+						0
+					};
+					require!(shares <= report.filled, ErrorCode::InvalidFillAmount);
+					shares
+				}
+			};
+
+			// лимитная заявка: брокер не мог потратить больше, чем по цене ордера
+			if !order.market {
+				let max_spent = price::notional(minted, order.price, order.price_scale, Rounding::Up)?;
+				require!(spent <= max_spent, ErrorCode::LimitPriceViolated);
+			}
 
-			// возвращаем сдачу мейкеру
-			let refund = ctx.accounts.escrow_account.amount
-				.checked_sub(amount).ok_or(ErrorCode::Overflow)?;
+			// сумма которую потртил брокер + наша комиссия (уже включает комиссию брокера),
+			// комиссия удерживается целиком с первого отчёта
+			let amount = spent.checked_add(fee_due).ok_or(ErrorCode::Overflow)?;
+			require!(amount <= escrowed, ErrorCode::InsufficientEscrowBalance);
 
-			if refund > 0 {
-				let cpi_ctx_refund = CpiContext::new_with_signer(
+			// перевод пдатежа из эскроу на пулл
+			if amount > 0 {
+				let cpi_ctx = CpiContext::new_with_signer(
 					ctx.accounts.token_program.to_account_info(),
 					token::Transfer {
 						from: ctx.accounts.escrow_account.to_account_info(),
-						to: ctx.accounts.refund_account.to_account_info(),
+						to: ctx.accounts.pool.to_account_info(),
 						authority: order.to_account_info(),
 					},
 					signer,
 				);
-				token::transfer(cpi_ctx_refund, refund)?;
+				token::transfer(cpi_ctx, amount)?;
 			}
 
 			// Минтим токены тикера на аккаунт мейкера
-			require!(ctx.accounts.maker_account.mint == order.ticker_mint, ErrorCode::InvalidMakerMint);
-			let cpi_ctx_mint = CpiContext::new(
-				ctx.accounts.token_program.to_account_info(),
-				token::MintTo {
-					mint: ctx.accounts.ticker_mint.to_account_info(),
-					to: ctx.accounts.maker_account.to_account_info(),
-					authority: ctx.accounts.payer.to_account_info()
-				},
-			);
-			token::mint_to(cpi_ctx_mint, order.amount)?;
+			if minted > 0 {
+				let cpi_ctx_mint = CpiContext::new(
+					ctx.accounts.token_program.to_account_info(),
+					token::MintTo {
+						mint: ctx.accounts.ticker_mint.to_account_info(),
+						to: ctx.accounts.maker_account.to_account_info(),
+						authority: ctx.accounts.payer.to_account_info()
+					},
+				);
+				token::mint_to(cpi_ctx_mint, minted)?;
+			}
+
+			let complete = report_complete
+				|| (order.mode == OrderMode::Quantity && order.filled + minted == order.amount);
+
+			// возвращаем сдачу мейкеру
+			let refund = if complete { escrowed - amount } else { 0 };
+
+			(minted, fee_due, refund, complete)
 		}
 
 		OrderSide::Sell => {
 			require!(ctx.accounts.maker_account.mint == order.payment_mint, ErrorCode::InvalidMakerMint);
 			require!(ctx.accounts.escrow_account.mint == order.ticker_mint, ErrorCode::InvalidEscrowMint);
 
-			// сумма которую получил брокер - наша комиссия (уже включает комиссию брокера)
-			let amount = spent.checked_sub(order.fee).ok_or(ErrorCode::Overflow)?;
+			// брокер может продать не всё, что лежит в эскроу
+			require!(report.filled <= escrowed, ErrorCode::InvalidFillAmount);
+
+			// лимитная заявка: брокер не мог получить меньше, чем по цене ордера
+			if !order.market {
+				let min_proceeds = price::notional(report.filled, order.price, order.price_scale, Rounding::Down)?;
+				require!(spent >= min_proceeds, ErrorCode::LimitPriceViolated);
+			}
+
+			// сумма которую получил брокер - наша комиссия (уже включает комиссию брокера),
+			// комиссия удерживается из выручки, пока не будет удержана целиком
+			let fee = fee_due.min(spent);
+			let amount = spent - fee;
 			require!(ctx.accounts.pool.amount >= amount, ErrorCode::InsufficientPoolBalance);
-			
+
 			// перевод токенов из пулла на аккаунт мейкера
-			let cpi_ctx = CpiContext::new(
-				ctx.accounts.token_program.to_account_info(),
-				token::Transfer {
-					from: ctx.accounts.pool.to_account_info(),
-					to: ctx.accounts.maker_account.to_account_info(),
-					authority: ctx.accounts.payer.to_account_info(),
-				},
-			);
-			token::transfer(cpi_ctx, amount)?;
+			if amount > 0 {
+				let cpi_ctx = CpiContext::new(
+					ctx.accounts.token_program.to_account_info(),
+					token::Transfer {
+						from: ctx.accounts.pool.to_account_info(),
+						to: ctx.accounts.maker_account.to_account_info(),
+						authority: ctx.accounts.payer.to_account_info(),
+					},
+				);
+				token::transfer(cpi_ctx, amount)?;
+			}
 
 			// сжигаем токены тикера из эскроу-аккаунта
-			let cpi_ctx_burn = CpiContext::new_with_signer(
-				ctx.accounts.token_program.to_account_info(),
-				token::Burn {
-					mint: ctx.accounts.ticker_mint.to_account_info(),
-					from: ctx.accounts.escrow_account.to_account_info(),
-					authority: ctx.accounts.order.to_account_info(),
-				},
-				signer
+			if report.filled > 0 {
+				let cpi_ctx_burn = CpiContext::new_with_signer(
+					ctx.accounts.token_program.to_account_info(),
+					token::Burn {
+						mint: ctx.accounts.ticker_mint.to_account_info(),
+						from: ctx.accounts.escrow_account.to_account_info(),
+						authority: ctx.accounts.order.to_account_info(),
+					},
+					signer
+				);
+				token::burn(cpi_ctx_burn, report.filled)?;
+			}
+
+			// цель ордера: выручка не меньше amount для Notional, продан весь эскроу для остальных
+			let reached = match order.mode {
+				OrderMode::Notional => order.spent.checked_add(spent).ok_or(ErrorCode::Overflow)? >= order.amount,
+				_ => report.filled == escrowed,
+			};
+			// без явного разрешения в payload (и вне TWAP) продажа исполняется только целиком
+			require!(
+				reached || order.allow_partial || order.twap.is_some(),
+				ErrorCode::PartialFillNotAllowed
 			);
-			token::burn(cpi_ctx_burn, order.amount)?;
+
+			let complete = report_complete || reached;
+
+			// непроданные токены тикера возвращаются мейкеру
+			let refund = if complete { escrowed - report.filled } else { 0 };
+
+			(report.filled, fee, refund, complete)
 		}
+	};
+
+	// TWAP: накопленное исполнение не опережает график
+	if let Some(twap) = order.twap {
+		let progress = order.progress(filled, spent)?;
+		require!(progress <= twap.allowed(order.amount, now)?, ErrorCode::TwapScheduleExceeded);
 	}
 
-	// закрываем ПДАшки
-	close(
-		&ctx.accounts.order,
-		&ctx.accounts.maker, // лампорты юзеру
-		&ctx.accounts.escrow_account,
-		&ctx.accounts.token_program,
-		ctx.bumps.order,
-	)?;
+	if refund > 0 {
+		let cpi_ctx_refund = CpiContext::new_with_signer(
+			ctx.accounts.token_program.to_account_info(),
+			token::Transfer {
+				from: ctx.accounts.escrow_account.to_account_info(),
+				to: ctx.accounts.refund_account.to_account_info(),
+				authority: order.to_account_info(),
+			},
+			signer,
+		);
+		token::transfer(cpi_ctx_refund, refund)?;
+	}
+
+	let order = &mut ctx.accounts.order;
+	order.filled = order.filled.checked_add(filled).ok_or(ErrorCode::Overflow)?;
+	order.spent = order.spent.checked_add(spent).ok_or(ErrorCode::Overflow)?;
+	order.fee_paid = order.fee_paid.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+
+	if complete {
+		// закрываем ПДАшки (или оставляем запись ордера, если так настроен registry)
+		finalize(
+			order,
+			OrderStatus::Filled,
+			&ctx.accounts.maker, // лампорты юзеру
+			&ctx.accounts.escrow_account,
+			&ctx.accounts.token_program,
+			ctx.bumps.order,
+			ctx.accounts.registry.order_retention,
+		)?;
+	} else {
+		order.transition(OrderStatus::PartiallyFilled)?;
+	}
 
 	emit!(OrderExecuted {
 		id: order.id,
 
 		side: order.side,
 		market: order.market,
+		mode: order.mode,
 		maker: order.maker,
 
 		ticker_mint: order.ticker_mint,
 		amount: order.amount,
+		filled,
 
 		payment_mint: order.payment_mint,
 		price: order.price,
-		fee: order.fee,
+		fill_price: report.price,
+		spent,
+		fee,
+		refund,
+
+		executor: ctx.accounts.payer.key(),
+		status: order.status,
 
-		proof_cid,
+		proof_cid: report.proof_cid,
+		broker_exec_id: report.broker_exec_id,
 
-		timestamp: Clock::get()?.unix_timestamp,
+		timestamp: now,
 	});
 
 	Ok(())
@@ -10,4 +10,15 @@ pub mod cancel;
 pub use cancel::*;
 
 pub mod execute;
-pub use execute::*;
\ No newline at end of file
+pub use execute::*;
+
+pub mod close;
+pub use close::*;
+pub mod switch;
+pub use switch::*;
+
+pub mod basket;
+pub use basket::*;
+
+pub mod cross;
+pub use cross::*;
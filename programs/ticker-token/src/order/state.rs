@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
 use super::types::*;
+use crate::{
+    errors::ErrorCode,
+    price::{self, Rounding},
+    utils::MAX_PROOF_CID_LEN,
+};
 
 #[event]
 pub struct OrderCreated {
@@ -14,9 +19,188 @@ pub struct OrderCreated {
 pub struct OrderCanceled {
 	pub id: u64,
 	pub maker: Pubkey,
+
+	pub mint: Pubkey, // mint возвращённых из эскроу токенов
+	pub refunded: u64,
+
+	pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderRejected {
+	pub id: u64,
+	pub maker: Pubkey,
+
+	pub mint: Pubkey,
+	pub refunded: u64,
+
+	pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderExpired {
+	pub id: u64,
+	pub maker: Pubkey,
+
+	pub mint: Pubkey,
+	pub refunded: u64,
+
 	pub timestamp: i64,
 }
 
+#[event]
+pub struct OrderTriggered {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub trigger: Trigger,
+    pub price: u64, // цена из аттестации оракулов
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwitchOrderCreated {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub from_mint: Pubkey,
+    pub to_mint: Pubkey,
+    pub amount: u64,
+    pub top_up: u64,
+
+    pub timestamp: i64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SwitchOrderExecuted {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub from_mint: Pubkey,
+    pub sold: u64, // сожжено тикера A
+    pub proceeds: u64,
+
+    pub to_mint: Pubkey,
+    pub bought: u64, // заминчено тикера B
+    pub cost: u64,
+
+    pub payment_mint: Pubkey,
+    pub fee: u64,
+    pub credited: u64, // излишек выручки, выплаченный мейкеру
+    pub debited: u64, // доплата мейкера из эскроу
+
+    pub refund: u64, // непроданный тикер A, возвращённый мейкеру
+    pub executor: Pubkey,
+
+    pub proof_cid: Vec<u8>,
+    pub broker_exec_id: [u8; 32],
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwitchOrderCanceled {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub refunded: u64, // тикер A
+    pub refunded_top_up: u64, // платёжный токен
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BasketOrderCreated {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub payment_mint: Pubkey,
+    pub legs: u8,
+    pub escrowed: u64,
+    pub all_or_none: bool,
+
+    pub timestamp: i64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct BasketLegExecuted {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub leg: u8,
+    pub ticker_mint: Pubkey,
+    pub filled: u64, // заминчено в этом отчёте
+    pub spent: u64,
+    pub fill_price: u64,
+
+    pub broker_exec_id: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BasketOrderExecuted {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub spent: u64, // потрачено в этом отчёте без комиссии
+    pub fee: u64,
+    pub refund: u64, // возврат неиспользованных средств при завершении
+
+    pub status: OrderStatus,
+    pub executor: Pubkey,
+
+    pub proof_cid: Vec<u8>,
+    pub broker_exec_id: [u8; 32],
+
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BasketOrderCanceled {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub refunded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BasketOrderExpired {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub refunded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrdersCrossed {
+    pub buy_id: u64,
+    pub buyer: Pubkey,
+    pub sell_id: u64,
+    pub seller: Pubkey,
+
+    pub ticker_mint: Pubkey,
+    pub quantity: u64, // переведено тикера из эскроу продавца покупателю
+
+    pub payment_mint: Pubkey,
+    pub price: u64,
+    pub cost: u64, // переведено из эскроу покупателя (без комиссии покупателя)
+    pub buyer_fee: u64,
+    pub seller_fee: u64,
+    pub buyer_refund: u64,
+    pub seller_refund: u64, // непроданный тикер, возвращённый продавцу
+
+    pub buy_status: OrderStatus,
+    pub sell_status: OrderStatus,
+    pub executor: Pubkey,
+
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct OrderProcessing {
     pub id: u64,
@@ -30,16 +214,25 @@ pub struct OrderExecuted {
 
     pub side: OrderSide,
     pub market: bool,
+    pub mode: OrderMode,
     pub maker: Pubkey,
 
     pub ticker_mint: Pubkey,
-    pub amount: u64,
+    pub amount: u64, // объём заявки (в единицах mode)
+    pub filled: u64, // фактически куплено (заминчено) / продано (сожжено) тикера
 
     pub payment_mint: Pubkey,
-    pub price: u64,
-    pub fee: u64,
+    pub price: u64, // цена из котировки оракула
+    pub fill_price: u64, // средняя цена исполнения у брокера
+    pub spent: u64, // потрачено (BUY) / выручено (SELL) брокером, без нашей комиссии
+    pub fee: u64, // удержанная комиссия
+    pub refund: u64, // возвращено мейкеру из эскроу (платёжный токен для BUY, тикер для SELL)
+
+    pub executor: Pubkey,
+    pub status: OrderStatus, // PartiallyFilled или Filled
 
     pub proof_cid: Vec<u8>,
+    pub broker_exec_id: [u8; 32],
 
     pub timestamp: i64,
 }
@@ -50,15 +243,215 @@ pub struct Order {
     
     pub side: OrderSide, // сторона заявки (Buy, Sell)
     pub market: bool, // является ли заявка рыночной
+    pub mode: OrderMode, // в чём выражен amount: количество тикера, сумма оплаты или весь баланс
     pub maker: Pubkey, // адрес создателя ордера
     
     pub ticker_mint: Pubkey, // адрес тиккер токена
     pub amount: u64,
     
     pub payment_mint: Pubkey, // адрес токена для оплаты
-	pub price: u64,
+	pub price: u64, // цена в платёжном токене за price_scale базовых единиц тикера
+	pub price_scale: u64,
 	pub fee: u64,
+
+    pub trigger: Option<Trigger>, // условие перевода в Processing
+    pub twap: Option<TwapSchedule>, // график исполнения частями
+    pub allow_partial: bool, // SELL: допускается завершение с непроданным остатком
     
     pub status: OrderStatus, // текущий статус заявки
     pub expires_at: i64, 
+
+    pub filled: u64, // исполнено тикера по всем отчётам брокера
+    pub spent: u64, // потрачено / выручено брокером по всем отчётам
+    pub fee_paid: u64, // уже удержанная часть комиссии
+
+    pub closed_at: i64, // время перехода в терминальный статус
+    pub retain_until: i64, // до какого времени хранится терминальная запись (closed_at + order_retention на момент закрытия)
+}
+
+impl Order {
+    /// Переход в новый статус с проверкой допустимости
+    pub fn transition(&mut self, next: OrderStatus) -> Result<()> {
+        require!(self.status.can_transition_to(&next), ErrorCode::InvalidStatusTransition);
+        self.status = next;
+        Ok(())
+    }
+
+    /// Накопленное исполнение в единицах `amount`:
+    /// сумма платёжного токена для Notional, количество тикера для остальных
+    pub fn progress(&self, filled: u64, spent: u64) -> Result<u64> {
+        match self.mode {
+            OrderMode::Notional => self.spent.checked_add(spent),
+            _ => self.filled.checked_add(filled),
+        }.ok_or(error!(ErrorCode::Overflow))
+    }
+
+    /// Сумма, блокируемая в эскроу при создании и возвращаемая при отмене
+    pub fn escrow_amount(&self) -> Result<u64> {
+        match (self.side, self.mode) {
+            (OrderSide::Buy, OrderMode::Quantity) => {
+                price::notional(self.amount, self.price, self.price_scale, Rounding::Up)?
+                    .checked_add(self.fee).ok_or(error!(ErrorCode::Overflow))
+            }
+            (OrderSide::Buy, OrderMode::Notional) => {
+                self.amount.checked_add(self.fee).ok_or(error!(ErrorCode::Overflow))
+            }
+            (OrderSide::Buy, OrderMode::All) => err!(ErrorCode::InvalidOrderMode),
+            // количество тикера, нужное чтобы выручить amount по цене ордера
+            (OrderSide::Sell, OrderMode::Notional) => {
+                price::quantity(self.amount, self.price, self.price_scale, Rounding::Up)
+            }
+            (OrderSide::Sell, _) => Ok(self.amount),
+        }
+    }
+}
+
+/// Ордер на замену тикера (PDA `['switch', maker, id]`)
+#[account]
+pub struct SwitchOrder {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub from_mint: Pubkey,
+    pub to_mint: Pubkey,
+    pub amount: u64,
+
+    pub payment_mint: Pubkey,
+    pub top_up: u64,
+    pub fee: u64,
+
+    pub min_sell_price: u64,
+    pub max_buy_price: u64,
+
+    pub status: OrderStatus,
+    pub expires_at: i64,
+}
+
+impl SwitchOrder {
+    pub const SPACE: usize = 8 // discriminator
+        + 8 // id
+        + 32 // maker
+        + 32 // from_mint
+        + 32 // to_mint
+        + 8 // amount
+        + 32 // payment_mint
+        + 8 // top_up
+        + 8 // fee
+        + 8 // min_sell_price
+        + 8 // max_buy_price
+        + 1 // status
+        + 8; // expires_at
+
+    /// Переход в новый статус с проверкой допустимости
+    pub fn transition(&mut self, next: OrderStatus) -> Result<()> {
+        require!(self.status.can_transition_to(&next), ErrorCode::InvalidStatusTransition);
+        self.status = next;
+        Ok(())
+    }
+}
+
+/// Состояние позиции корзины
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BasketLegState {
+    pub ticker_mint: Pubkey,
+    pub amount: u64,
+    pub price: u64,
+    pub price_scale: u64,
+
+    pub filled: u64,
+    pub spent: u64,
+}
+
+impl BasketLegState {
+    pub const SIZE: usize = 32 + 8 * 5;
+}
+
+/// Ордер-корзина (PDA `['basket', maker, id]`), один эскроу платёжного токена на все позиции
+#[account]
+pub struct BasketOrder {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub market: bool,
+
+    pub payment_mint: Pubkey,
+    pub legs: Vec<BasketLegState>,
+
+    pub fee: u64,
+    pub fee_paid: u64,
+    pub all_or_none: bool,
+
+    pub status: OrderStatus,
+    pub expires_at: i64,
+}
+
+impl BasketOrder {
+    pub fn space(legs: usize) -> usize {
+        8 // discriminator
+            + 8 // id
+            + 32 // maker
+            + 1 // market
+            + 32 // payment_mint
+            + 4 + BasketLegState::SIZE * legs // legs
+            + 8 // fee
+            + 8 // fee_paid
+            + 1 // all_or_none
+            + 1 // status
+            + 8 // expires_at
+    }
+
+    /// Залог: стоимость всех позиций по ценам ордера + комиссия
+    pub fn escrow_amount(&self) -> Result<u64> {
+        self.legs.iter().try_fold(self.fee, |total, leg| {
+            price::notional(leg.amount, leg.price, leg.price_scale, Rounding::Up)?
+                .checked_add(total).ok_or(error!(ErrorCode::Overflow))
+        })
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.legs.iter().all(|leg| leg.filled == leg.amount)
+    }
+
+    /// Переход в новый статус с проверкой допустимости
+    pub fn transition(&mut self, next: OrderStatus) -> Result<()> {
+        require!(self.status.can_transition_to(&next), ErrorCode::InvalidStatusTransition);
+        self.status = next;
+        Ok(())
+    }
+}
+
+/// Использованный идентификатор сделки брокера (PDA `['fill', broker_exec_id]`)
+#[account]
+pub struct Fill {
+    pub broker_exec_id: [u8; 32],
+
+    pub order_id: u64,
+    pub maker: Pubkey,
+
+    pub filled: u64,
+    pub spent: u64,
+
+    pub timestamp: i64,
+}
+
+/// Подтверждение сделки брокера для аудита (PDA `['proof', broker_exec_id]`)
+#[account]
+pub struct ExecutionProof {
+    pub broker_exec_id: [u8; 32],
+
+    pub order_id: u64,
+    pub maker: Pubkey,
+    pub ticker_mint: Pubkey,
+
+    pub cid: Vec<u8>,
+    pub timestamp: i64,
+}
+
+impl ExecutionProof {
+    pub const SPACE: usize = 8   // дискриминатор
+        + 32                     // broker_exec_id
+        + 8                      // order_id
+        + 32                     // maker
+        + 32                     // ticker_mint
+        + 4 + MAX_PROOF_CID_LEN  // cid
+        + 8;                     // timestamp
 }
@@ -0,0 +1,693 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+	token::{self, TokenAccount, Token, Mint},
+};
+use crate::{
+	Registry,
+	OracleKey,
+	errors::ErrorCode,
+	price::{self, Rounding},
+	utils::{verify_oracle_quorum, assert_cid, SignedMessage},
+	feed::PriceFeed,
+	calendar::MarketCalendar,
+	market::{Market, MarketStatus},
+	ticker::{TickerRecord, TickerStatus},
+	config::TickerConfig,
+	order::{types::*, state::*},
+};
+
+#[derive(Accounts)]
+#[instruction(payload: SwitchPayload)]
+pub struct CreateSwitchOrder<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == payload.maker @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"switch", payer.key().as_ref(), &payload.id.to_le_bytes()],
+		bump,
+		space = SwitchOrder::SPACE,
+	)]
+	pub switch_order: Account<'info, SwitchOrder>,
+
+	/// Тикер A, который мейкер продаёт
+	#[account(constraint = from_mint_account.key() == payload.from_mint @ ErrorCode::InvalidTickerMint)]
+	pub from_mint_account: Box<Account<'info, Mint>>,
+
+	/// Тикер B, который мейкер получает
+	#[account(constraint = to_mint_account.key() == payload.to_mint @ ErrorCode::InvalidTickerMint)]
+	pub to_mint_account: Box<Account<'info, Mint>>,
+
+	#[account(constraint = payment_mint_account.key() == payload.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint_account: Box<Account<'info, Mint>>,
+
+	/// Ограничения размера заявки тикера A
+	#[account(seeds = [b"config", from_mint_account.key().as_ref()], bump = from_config.bump)]
+	pub from_config: Box<Account<'info, TickerConfig>>,
+
+	/// Открытые рынки обоих тикеров за платёжный токен замены
+	#[account(
+		seeds = [b"market", payload.from_mint.as_ref(), payload.payment_mint.as_ref()],
+		bump = from_market.bump,
+		constraint = from_market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub from_market: Box<Account<'info, Market>>,
+
+	#[account(
+		seeds = [b"market", payload.to_mint.as_ref(), payload.payment_mint.as_ref()],
+		bump = to_market.bump,
+		constraint = to_market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub to_market: Box<Account<'info, Market>>,
+
+	/// Оба тикера не делистингованы
+	#[account(
+		seeds = [b"ticker", payload.from_mint.as_ref()],
+		bump = from_record.bump,
+		constraint = from_record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub from_record: Box<Account<'info, TickerRecord>>,
+
+	#[account(
+		seeds = [b"ticker", payload.to_mint.as_ref()],
+		bump = to_record.bump,
+		constraint = to_record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub to_record: Box<Account<'info, TickerRecord>>,
+
+	/// Торговая сессия: рыночные заявки создаются и исполняются только при открытой бирже
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Box<Account<'info, MarketCalendar>>,
+
+	#[account(
+		mut,
+		constraint = maker_from_account.owner == payer.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_from_account.mint == payload.from_mint @ ErrorCode::InvalidTickerMint,
+	)]
+	pub maker_from_account: Box<Account<'info, TokenAccount>>,
+
+	/// АТА мейкера под тикер B, создаётся за счёт мейкера
+	#[account(
+		init_if_needed,
+		payer = payer,
+		associated_token::mint = to_mint_account,
+		associated_token::authority = payer,
+	)]
+	pub maker_to_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == payer.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_payment_account.mint == payload.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub maker_payment_account: Box<Account<'info, TokenAccount>>,
+
+	/// Эскроу тикера A
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"escrow", switch_order.key().as_ref()],
+		bump,
+		token::mint = from_mint_account,
+		token::authority = switch_order,
+	)]
+	pub escrow_account: Box<Account<'info, TokenAccount>>,
+
+	/// Эскроу доплаты в платёжном токене
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"escrow_payment", switch_order.key().as_ref()],
+		bump,
+		token::mint = payment_mint_account,
+		token::authority = switch_order,
+	)]
+	pub payment_escrow_account: Box<Account<'info, TokenAccount>>,
+
+	/// CHECK: instruction sysvar, used for verifying oracle signature
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+	pub system_program: Program<'info, System>,
+	pub token_program: Program<'info, Token>,
+	pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(report: SwitchReport)]
+pub struct ExecuteSwitchOrder<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+		seeds = [b"switch", maker.key().as_ref(), &report.order_id.to_le_bytes()],
+		bump,
+		constraint = switch_order.status.can_transition_to(&OrderStatus::Filled) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub switch_order: Box<Account<'info, SwitchOrder>>,
+
+	/// CHECK: checked manually via constraint switch_order.maker == maker.key()
+	#[account(
+		mut,
+		constraint = switch_order.maker == maker.key() @ ErrorCode::InvalidMaker,
+	)]
+	pub maker: AccountInfo<'info>,
+
+	#[account(mut, constraint = from_mint.key() == switch_order.from_mint @ ErrorCode::InvalidTickerMint)]
+	pub from_mint: Box<Account<'info, Mint>>,
+
+	#[account(mut, constraint = to_mint.key() == switch_order.to_mint @ ErrorCode::InvalidTickerMint)]
+	pub to_mint: Box<Account<'info, Mint>>,
+
+	#[account(constraint = payment_mint.key() == switch_order.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint: Box<Account<'info, Mint>>,
+
+	#[account(seeds = [b"config", from_mint.key().as_ref()], bump = from_config.bump)]
+	pub from_config: Box<Account<'info, TickerConfig>>,
+
+	#[account(seeds = [b"config", to_mint.key().as_ref()], bump = to_config.bump)]
+	pub to_config: Box<Account<'info, TickerConfig>>,
+
+	/// Цены тикеров от оракулов: коридор и допустимое отклонение цен отчёта
+	#[account(seeds = [b"feed", from_mint.key().as_ref()], bump = from_feed.bump)]
+	pub from_feed: Box<Account<'info, PriceFeed>>,
+
+	#[account(seeds = [b"feed", to_mint.key().as_ref()], bump = to_feed.bump)]
+	pub to_feed: Box<Account<'info, PriceFeed>>,
+
+	/// Оба тикера не делистингованы
+	#[account(
+		seeds = [b"ticker", from_mint.key().as_ref()],
+		bump = from_record.bump,
+		constraint = from_record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub from_record: Box<Account<'info, TickerRecord>>,
+
+	#[account(
+		seeds = [b"ticker", to_mint.key().as_ref()],
+		bump = to_record.bump,
+		constraint = to_record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub to_record: Box<Account<'info, TickerRecord>>,
+
+	/// Торговая сессия: рыночные заявки создаются и исполняются только при открытой бирже
+	#[account(seeds = [b"calendar"], bump = calendar.bump)]
+	pub calendar: Box<Account<'info, MarketCalendar>>,
+
+	/// Куда минтится тикер B
+	#[account(
+		mut,
+		constraint = maker_to_account.owner == maker.key() @ ErrorCode::InvalidMakerAccount,
+		constraint = maker_to_account.mint == switch_order.to_mint @ ErrorCode::InvalidMakerMint,
+	)]
+	pub maker_to_account: Box<Account<'info, TokenAccount>>,
+
+	/// Куда выплачивается излишек выручки и возвращается доплата
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == maker.key() @ ErrorCode::InvalidMakerAccount,
+		constraint = maker_payment_account.mint == switch_order.payment_mint @ ErrorCode::InvalidMakerMint,
+	)]
+	pub maker_payment_account: Box<Account<'info, TokenAccount>>,
+
+	/// Куда возвращается непроданный тикер A
+	#[account(
+		mut,
+		constraint = refund_account.owner == maker.key() @ ErrorCode::InvalidRefundAccount,
+		constraint = refund_account.mint == switch_order.from_mint @ ErrorCode::InvalidRefundMint,
+	)]
+	pub refund_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", switch_order.key().as_ref()],
+		bump,
+	)]
+	pub escrow_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow_payment", switch_order.key().as_ref()],
+		bump,
+	)]
+	pub payment_escrow_account: Box<Account<'info, TokenAccount>>,
+
+	/// Пул тикера A — выплачивает выручку за A
+	#[account(
+		mut,
+		seeds = [b"pool", from_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+	)]
+	pub from_pool: Box<Account<'info, TokenAccount>>,
+
+	/// Пул тикера B — получает стоимость B и комиссию
+	#[account(
+		init_if_needed,
+		payer = payer,
+		seeds = [b"pool", to_mint.key().as_ref(), payment_mint.key().as_ref()],
+		bump,
+		token::mint = payment_mint,
+		token::authority = payer,
+	)]
+	pub to_pool: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"fill", report.broker_exec_id.as_ref()],
+		bump,
+		space = 8 + std::mem::size_of::<Fill>(),
+	)]
+	pub fill: Box<Account<'info, Fill>>,
+
+	/// CHECK: instruction sysvar, used for verifying broker signature
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+	pub token_program: Program<'info, Token>,
+	pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CancelSwitchOrder<'info> {
+	#[account(mut)]
+	pub payer: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"switch", payer.key().as_ref(), &id.to_le_bytes()],
+		bump,
+		constraint = switch_order.maker == payer.key() @ ErrorCode::Unauthorized,
+		constraint = switch_order.status.can_transition_to(&OrderStatus::Canceled) @ ErrorCode::OrderAlreadyProcessed,
+	)]
+	pub switch_order: Box<Account<'info, SwitchOrder>>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow", switch_order.key().as_ref()],
+		bump,
+	)]
+	pub escrow_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		seeds = [b"escrow_payment", switch_order.key().as_ref()],
+		bump,
+	)]
+	pub payment_escrow_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		constraint = refund_account.owner == payer.key() @ ErrorCode::InvalidRefundOwner,
+		constraint = refund_account.mint == switch_order.from_mint @ ErrorCode::InvalidRefundMint,
+	)]
+	pub refund_account: Box<Account<'info, TokenAccount>>,
+
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == payer.key() @ ErrorCode::InvalidRefundOwner,
+		constraint = maker_payment_account.mint == switch_order.payment_mint @ ErrorCode::InvalidRefundMint,
+	)]
+	pub maker_payment_account: Box<Account<'info, TokenAccount>>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+pub fn create_switch(ctx: Context<CreateSwitchOrder>, payload: SwitchPayload) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	require!(now <= payload.expires_at, ErrorCode::PayloadExpired);
+
+	let serialized = payload.message()?;
+
+	let registry = &ctx.accounts.registry;
+	verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+	require!(payload.from_mint != payload.to_mint, ErrorCode::InvalidTickerMint);
+	require!(payload.amount > 0, ErrorCode::InvalidSellAmount);
+	require!(payload.max_buy_price > 0, ErrorCode::InvalidPrice);
+	ctx.accounts.from_config.check_quantity(payload.amount)?;
+	ctx.accounts.from_market.check_quantity(payload.amount)?;
+	// обе ноги замены исполняются по рынку
+	require!(ctx.accounts.calendar.is_open(now), ErrorCode::MarketClosed);
+
+	let order = &mut ctx.accounts.switch_order;
+	order.id = payload.id;
+	order.maker = payload.maker;
+
+	order.from_mint = payload.from_mint;
+	order.to_mint = payload.to_mint;
+	order.amount = payload.amount;
+
+	order.payment_mint = payload.payment_mint;
+	order.top_up = payload.top_up;
+	order.fee = payload.fee;
+
+	order.min_sell_price = payload.min_sell_price;
+	order.max_buy_price = payload.max_buy_price;
+
+	order.status = OrderStatus::Pending;
+	order.expires_at = payload.expires_at;
+
+	// Блокируем тикер A
+	let cpi_ctx = CpiContext::new(
+		ctx.accounts.token_program.to_account_info(),
+		token::Transfer {
+			from: ctx.accounts.maker_from_account.to_account_info(),
+			to: ctx.accounts.escrow_account.to_account_info(),
+			authority: ctx.accounts.payer.to_account_info(),
+		},
+	);
+	token::transfer(cpi_ctx, payload.amount)?;
+
+	// Блокируем возможную доплату
+	if payload.top_up > 0 {
+		let cpi_ctx = CpiContext::new(
+			ctx.accounts.token_program.to_account_info(),
+			token::Transfer {
+				from: ctx.accounts.maker_payment_account.to_account_info(),
+				to: ctx.accounts.payment_escrow_account.to_account_info(),
+				authority: ctx.accounts.payer.to_account_info(),
+			},
+		);
+		token::transfer(cpi_ctx, payload.top_up)?;
+	}
+
+	emit!(SwitchOrderCreated {
+		id: order.id,
+		maker: order.maker,
+
+		from_mint: order.from_mint,
+		to_mint: order.to_mint,
+		amount: order.amount,
+		top_up: order.top_up,
+
+		timestamp: now,
+		expires_at: order.expires_at,
+	});
+
+	Ok(())
+}
+
+pub fn execute_switch(ctx: Context<ExecuteSwitchOrder>, report: SwitchReport) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+
+	// Проверка подписи брокера под отчётом
+	let serialized = report.message()?;
+
+	verify_oracle_quorum(
+		&ctx.accounts.instruction_sysvar,
+		&[OracleKey::Ed25519(ctx.accounts.registry.broker)], 1,
+		&serialized,
+	)?;
+
+	let order = &ctx.accounts.switch_order;
+	let escrowed = ctx.accounts.escrow_account.amount;
+	let top_up = ctx.accounts.payment_escrow_account.amount;
+
+	require!(report.order_id == order.id && report.maker == order.maker, ErrorCode::InvalidExecutionReport);
+	require!(report.timestamp <= now, ErrorCode::InvalidReportTimestamp);
+	require!(now <= order.expires_at, ErrorCode::OrderExpired);
+	require!(ctx.accounts.calendar.is_open(now), ErrorCode::MarketClosed);
+	require!(report.sold > 0 && report.sold <= escrowed, ErrorCode::InvalidFillAmount);
+	require!(report.bought > 0, ErrorCode::InvalidFillAmount);
+	assert_cid(&report.proof_cid)?;
+
+	// цены сделок: в пределах, подписанных мейкером, в коридоре и рядом с ценой фида
+	require!(
+		report.sell_price >= order.min_sell_price && report.buy_price <= order.max_buy_price,
+		ErrorCode::LimitPriceViolated
+	);
+	let payment_decimals = ctx.accounts.payment_mint.decimals;
+	ctx.accounts.from_config.check_price(&ctx.accounts.from_feed, report.sell_price, payment_decimals, now)?;
+	ctx.accounts.to_config.check_price(&ctx.accounts.to_feed, report.buy_price, payment_decimals, now)?;
+	// тикер B покупается по ограничениям его заявок
+	ctx.accounts.to_config.check_quantity(report.bought)?;
+
+	// суммы отчёта согласованы с ценами: выручка не меньше, стоимость не больше
+	let min_proceeds = price::notional(
+		report.sold, report.sell_price, price::price_scale(ctx.accounts.from_mint.decimals)?, Rounding::Down
+	)?;
+	let max_cost = price::notional(
+		report.bought, report.buy_price, price::price_scale(ctx.accounts.to_mint.decimals)?, Rounding::Up
+	)?;
+	require!(report.proceeds >= min_proceeds && report.cost <= max_cost, ErrorCode::LimitPriceViolated);
+
+	let fill = &mut ctx.accounts.fill;
+	fill.broker_exec_id = report.broker_exec_id;
+	fill.order_id = order.id;
+	fill.maker = order.maker;
+	fill.filled = report.bought;
+	fill.spent = report.cost;
+	fill.timestamp = now;
+
+	// Расчёт: выручка за A идёт на покупку B и комиссию,
+	// излишек выплачивается мейкеру, недостача берётся из доплаты
+	let due = report.cost.checked_add(order.fee).ok_or(ErrorCode::Overflow)?;
+	let from_proceeds = report.proceeds.min(due);
+	let credited = report.proceeds - from_proceeds;
+	let debited = due - from_proceeds;
+
+	require!(debited <= top_up, ErrorCode::InsufficientEscrowBalance);
+	require!(ctx.accounts.from_pool.amount >= report.proceeds, ErrorCode::InsufficientPoolBalance);
+
+	let maker = order.maker;
+	let order_id = order.id.to_le_bytes();
+	let signer_seeds: [&[u8]; 4] = [
+		b"switch",
+		maker.as_ref(),
+		&order_id,
+		&[ctx.bumps.switch_order],
+	];
+	let signer: &[&[&[u8]]] = &[&signer_seeds];
+
+	let token_program = ctx.accounts.token_program.to_account_info();
+	let payer = ctx.accounts.payer.to_account_info();
+	let order_info = ctx.accounts.switch_order.to_account_info();
+
+	// сжигаем проданный тикер A
+	token::burn(
+		CpiContext::new_with_signer(
+			token_program.clone(),
+			token::Burn {
+				mint: ctx.accounts.from_mint.to_account_info(),
+				from: ctx.accounts.escrow_account.to_account_info(),
+				authority: order_info.clone(),
+			},
+			signer,
+		),
+		report.sold,
+	)?;
+
+	// минтим купленный тикер B
+	token::mint_to(
+		CpiContext::new(
+			token_program.clone(),
+			token::MintTo {
+				mint: ctx.accounts.to_mint.to_account_info(),
+				to: ctx.accounts.maker_to_account.to_account_info(),
+				authority: payer.clone(),
+			},
+		),
+		report.bought,
+	)?;
+
+	// пул A → пул B: выручка в счёт стоимости B и комиссии
+	if from_proceeds > 0 {
+		token::transfer(
+			CpiContext::new(
+				token_program.clone(),
+				token::Transfer {
+					from: ctx.accounts.from_pool.to_account_info(),
+					to: ctx.accounts.to_pool.to_account_info(),
+					authority: payer.clone(),
+				},
+			),
+			from_proceeds,
+		)?;
+	}
+
+	// пул A → мейкер: излишек выручки
+	if credited > 0 {
+		token::transfer(
+			CpiContext::new(
+				token_program.clone(),
+				token::Transfer {
+					from: ctx.accounts.from_pool.to_account_info(),
+					to: ctx.accounts.maker_payment_account.to_account_info(),
+					authority: payer.clone(),
+				},
+			),
+			credited,
+		)?;
+	}
+
+	// эскроу доплаты → пул B: недостача
+	if debited > 0 {
+		token::transfer(
+			CpiContext::new_with_signer(
+				token_program.clone(),
+				token::Transfer {
+					from: ctx.accounts.payment_escrow_account.to_account_info(),
+					to: ctx.accounts.to_pool.to_account_info(),
+					authority: order_info.clone(),
+				},
+				signer,
+			),
+			debited,
+		)?;
+	}
+
+	// возвращаем непроданный A и неиспользованную доплату, закрываем эскроу и ордер
+	let refund = escrowed - report.sold;
+	finalize(
+		&mut ctx.accounts.switch_order,
+		OrderStatus::Filled,
+		&ctx.accounts.maker,
+		&token_program,
+		ctx.bumps.switch_order,
+		[
+			(&ctx.accounts.escrow_account, &ctx.accounts.refund_account, refund),
+			(&ctx.accounts.payment_escrow_account, &ctx.accounts.maker_payment_account, top_up - debited),
+		],
+	)?;
+
+	let order = &ctx.accounts.switch_order;
+	emit!(SwitchOrderExecuted {
+		id: order.id,
+		maker: order.maker,
+
+		from_mint: order.from_mint,
+		sold: report.sold,
+		proceeds: report.proceeds,
+
+		to_mint: order.to_mint,
+		bought: report.bought,
+		cost: report.cost,
+
+		payment_mint: order.payment_mint,
+		fee: order.fee,
+		credited,
+		debited,
+
+		refund,
+		executor: ctx.accounts.payer.key(),
+
+		proof_cid: report.proof_cid,
+		broker_exec_id: report.broker_exec_id,
+
+		timestamp: now,
+	});
+
+	Ok(())
+}
+
+pub fn cancel_switch(ctx: Context<CancelSwitchOrder>) -> Result<()> {
+	let refunded = ctx.accounts.escrow_account.amount;
+	let refunded_top_up = ctx.accounts.payment_escrow_account.amount;
+	let token_program = ctx.accounts.token_program.to_account_info();
+	let payer = ctx.accounts.payer.to_account_info();
+
+	finalize(
+		&mut ctx.accounts.switch_order,
+		OrderStatus::Canceled,
+		&payer,
+		&token_program,
+		ctx.bumps.switch_order,
+		[
+			(&ctx.accounts.escrow_account, &ctx.accounts.refund_account, refunded),
+			(&ctx.accounts.payment_escrow_account, &ctx.accounts.maker_payment_account, refunded_top_up),
+		],
+	)?;
+
+	let order = &ctx.accounts.switch_order;
+	emit!(SwitchOrderCanceled {
+		id: order.id,
+		maker: order.maker,
+
+		refunded,
+		refunded_top_up,
+
+		timestamp: Clock::get()?.unix_timestamp,
+	});
+
+	Ok(())
+}
+
+/// Переводит замену в терминальный статус: возвращает мейкеру остатки обоих эскроу
+/// (тикер A и доплату), закрывает эскроу и сам ордер
+fn finalize<'info>(
+	order: &mut Account<'info, SwitchOrder>,
+	status: OrderStatus,
+	maker: &AccountInfo<'info>,
+	token_program: &AccountInfo<'info>,
+	order_bump: u8,
+	escrows: [(&Account<'info, TokenAccount>, &Account<'info, TokenAccount>, u64); 2],
+) -> Result<()> {
+	require!(status.is_terminal(), ErrorCode::InvalidStatusTransition);
+	order.transition(status)?;
+
+	let order_id = order.id.to_le_bytes();
+	let signer_seeds: [&[u8]; 4] = [
+		b"switch",
+		order.maker.as_ref(),
+		&order_id,
+		&[order_bump],
+	];
+	let signer: &[&[&[u8]]] = &[&signer_seeds];
+
+	let order_info = order.to_account_info();
+	for (escrow_account, to, amount) in escrows {
+		release(escrow_account, to, maker, &order_info, token_program, signer, amount)?;
+	}
+
+	order.close(maker.clone())
+}
+
+/// Возвращает остаток эскроу мейкеру и закрывает эскроу (рента — мейкеру)
+pub fn release<'info>(
+	escrow_account: &Account<'info, TokenAccount>,
+	to: &Account<'info, TokenAccount>,
+	maker: &AccountInfo<'info>,
+	order: &AccountInfo<'info>,
+	token_program: &AccountInfo<'info>,
+	signer: &[&[&[u8]]],
+	amount: u64,
+) -> Result<()> {
+	if amount > 0 {
+		token::transfer(
+			CpiContext::new_with_signer(
+				token_program.clone(),
+				token::Transfer {
+					from: escrow_account.to_account_info(),
+					to: to.to_account_info(),
+					authority: order.clone(),
+				},
+				signer,
+			),
+			amount,
+		)?;
+	}
+
+	token::close_account(CpiContext::new_with_signer(
+		token_program.clone(),
+		token::CloseAccount {
+			account: escrow_account.to_account_info(),
+			destination: maker.clone(),
+			authority: order.clone(),
+		},
+		signer,
+	))
+}
@@ -1,4 +1,8 @@
 use anchor_lang::prelude::*;
+use crate::{
+    price::{self, Rounding},
+    utils::{SignedMessage, MessageKind},
+};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum OrderType {
@@ -12,10 +16,106 @@ pub enum OrderSide {
     Sell,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+/// Режим объёма заявки
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderMode {
+    Quantity, // amount — количество тикера
+    Notional, // amount — сумма в платёжном токене
+    All, // продать весь баланс тикера мейкера (только SELL), amount игнорируется
+}
+
+/// Срок действия заявки
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    Gtc, // до `expires_at`
+    Day, // до закрытия текущей (или ближайшей) торговой сессии, но не дольше `expires_at`
+}
+
+/// Тип условия срабатывания заявки
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerType {
+    Stop, // после срабатывания — рыночная заявка
+    StopLimit, // после срабатывания — лимитная заявка по price
+    TakeProfit,
+}
+
+/// Условие, при котором ордер можно взять в работу (`process_order`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Trigger {
+    pub kind: TriggerType,
+    pub price: u64, // в тех же единицах, что и цена ордера
+}
+
+impl Trigger {
+    /// Пересекла ли цена уровень срабатывания.
+    /// Stop/StopLimit: SELL — цена упала до уровня, BUY — выросла до уровня.
+    /// TakeProfit: SELL — цена выросла до уровня, BUY — упала до уровня.
+    pub fn is_crossed(&self, side: OrderSide, price: u64) -> bool {
+        match (self.kind, side) {
+            (TriggerType::Stop | TriggerType::StopLimit, OrderSide::Sell) => price <= self.price,
+            (TriggerType::Stop | TriggerType::StopLimit, OrderSide::Buy) => price >= self.price,
+            (TriggerType::TakeProfit, OrderSide::Sell) => price >= self.price,
+            (TriggerType::TakeProfit, OrderSide::Buy) => price <= self.price,
+        }
+    }
+}
+
+/// График TWAP: объём ордера делится на `slices` равных частей,
+/// каждая открывается в начале своего интервала в [start_at, end_at)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TwapSchedule {
+    pub start_at: i64,
+    pub end_at: i64,
+    pub slices: u16,
+}
+
+impl TwapSchedule {
+    /// Сколько из `total` разрешено исполнить к моменту `now` (накопленно)
+    pub fn allowed(&self, total: u64, now: i64) -> Result<u64> {
+        if now < self.start_at {
+            return Ok(0);
+        }
+        if now >= self.end_at {
+            return Ok(total);
+        }
+
+        let elapsed = (now - self.start_at) as u64;
+        let duration = (self.end_at - self.start_at) as u64;
+        let opened = price::mul_div(elapsed, self.slices as u64, duration, Rounding::Down)? + 1;
+
+        price::mul_div(total, opened, self.slices as u64, Rounding::Down)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum OrderStatus {
     Pending,
     Processing,
+    PartiallyFilled,
+
+    // терминальные статусы
+    Filled,
+    Canceled,
+    Rejected,
+    Expired,
+}
+
+impl OrderStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Filled | Self::Canceled | Self::Rejected | Self::Expired)
+    }
+
+    /// Единственное место, где описаны допустимые переходы статусов ордера
+    pub fn can_transition_to(&self, next: &OrderStatus) -> bool {
+        use OrderStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Processing | PartiallyFilled | Filled | Canceled | Rejected | Expired)
+                | (Processing, PartiallyFilled | Filled | Rejected)
+                | (PartiallyFilled, PartiallyFilled | Filled | Rejected | Expired)
+        )
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -24,6 +124,7 @@ pub struct OrderPayload {
     
     pub maker: Pubkey,
     pub market: bool,
+    pub mode: OrderMode,
 
     pub ticker_mint: Pubkey,
     pub amount: u64,
@@ -32,5 +133,157 @@ pub struct OrderPayload {
     pub price: u64,
     pub fee: u64,
 
+    pub trigger: Option<Trigger>, // условная заявка (stop / stop-limit / take-profit)
+    pub twap: Option<TwapSchedule>, // исполнение частями по графику
+    pub allow_partial: bool, // SELL: брокер может завершить ордер, продав не всё
+    pub time_in_force: TimeInForce,
+
+    pub expires_at: i64,
+}
+
+/// Цена тикера, подписанная кворумом оракулов (для срабатывания условных заявок)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceAttestation {
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub price: u64, // в платёжном токене за 10^decimals базовых единиц тикера
+    pub timestamp: i64,
+}
+
+/// Отчёт брокера об исполнении ордера, подписывается ключом `registry.broker`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExecutionReport {
+    pub order_id: u64,
+    pub maker: Pubkey,
+
+    pub filled: u64, // исполненное количество тикера
+    pub spent: u64, // потрачено (BUY) / получено (SELL) брокером в платёжном токене
+    pub price: u64, // средняя цена исполнения
+    pub complete: bool, // брокер закончил работу с ордером, остаток эскроу возвращается мейкеру
+
+    pub proof_cid: Vec<u8>,
+    pub broker_exec_id: [u8; 32], // идентификатор сделки у брокера
+
+    pub timestamp: i64,
+}
+
+/// Payload ордера на замену тикера A на тикер B, подписывается оракулами
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwitchPayload {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub from_mint: Pubkey, // тикер A, который продаётся
+    pub to_mint: Pubkey, // тикер B, который покупается
+    pub amount: u64, // количество тикера A
+
+    pub payment_mint: Pubkey, // расчётный токен пулов A и B
+    pub top_up: u64, // сколько мейкер готов доплатить, если выручки от A не хватит на B
+    pub fee: u64,
+
+    pub min_sell_price: u64, // нижняя граница цены продажи A
+    pub max_buy_price: u64, // верхняя граница цены покупки B
+
     pub expires_at: i64,
-}
\ No newline at end of file
+}
+
+/// Отчёт брокера об исполнении замены, подписывается ключом `registry.broker`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwitchReport {
+    pub order_id: u64,
+    pub maker: Pubkey,
+
+    pub sold: u64, // продано тикера A
+    pub proceeds: u64, // выручка за A в платёжном токене
+    pub sell_price: u64,
+
+    pub bought: u64, // куплено тикера B
+    pub cost: u64, // стоимость B в платёжном токене
+    pub buy_price: u64,
+
+    pub proof_cid: Vec<u8>,
+    pub broker_exec_id: [u8; 32],
+
+    pub timestamp: i64,
+}
+
+/// Максимальное количество позиций в корзине: исполнение «всё или ничего» одним отчётом
+/// должно уложиться в предел аккаунтов транзакции (см. `order::basket`)
+pub const MAX_BASKET_LEGS: usize = 8;
+
+/// Позиция корзины: сколько и по какой цене купить тикера
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BasketLeg {
+    pub ticker_mint: Pubkey,
+    pub amount: u64,
+    pub price: u64,
+}
+
+/// Payload ордера-корзины на покупку нескольких тикеров, подписывается оракулами один раз
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BasketPayload {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub market: bool,
+
+    pub payment_mint: Pubkey,
+    pub legs: Vec<BasketLeg>,
+    pub fee: u64, // комиссия за всю корзину
+
+    pub all_or_none: bool, // исполнить все позиции целиком одним отчётом или ничего
+
+    pub expires_at: i64,
+}
+
+/// Исполнение одной позиции корзины
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BasketFill {
+    pub leg: u8, // индекс позиции
+    pub filled: u64,
+    pub spent: u64,
+    pub price: u64,
+}
+
+/// Отчёт брокера по корзине, подписывается ключом `registry.broker`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BasketReport {
+    pub order_id: u64,
+    pub maker: Pubkey,
+
+    pub fills: Vec<BasketFill>,
+    pub complete: bool, // брокер закончил работу с корзиной, остаток эскроу возвращается мейкеру
+
+    pub proof_cid: Vec<u8>,
+    pub broker_exec_id: [u8; 32],
+
+    pub timestamp: i64,
+}
+
+impl SignedMessage for OrderPayload {
+    const KIND: MessageKind = MessageKind::OrderPayload;
+}
+
+impl SignedMessage for PriceAttestation {
+    const KIND: MessageKind = MessageKind::PriceAttestation;
+}
+
+impl SignedMessage for ExecutionReport {
+    const KIND: MessageKind = MessageKind::ExecutionReport;
+}
+
+impl SignedMessage for SwitchPayload {
+    const KIND: MessageKind = MessageKind::SwitchPayload;
+}
+
+impl SignedMessage for SwitchReport {
+    const KIND: MessageKind = MessageKind::SwitchReport;
+}
+
+impl SignedMessage for BasketPayload {
+    const KIND: MessageKind = MessageKind::BasketPayload;
+}
+
+impl SignedMessage for BasketReport {
+    const KIND: MessageKind = MessageKind::BasketReport;
+}
@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::{
+	token::{self, TokenAccount, Token, Mint}
+};
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	market::{Market, MarketStatus},
+	utils::{verify_oracle_quorum, SignedMessage},
+	plan::state::*,
+};
+
+#[derive(Accounts)]
+#[instruction(payload: PlanPayload)]
+pub struct CreatePlan<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == payload.maker @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"plan", payer.key().as_ref(), &payload.id.to_le_bytes()],
+		bump,
+		space = RecurringPlan::SPACE,
+	)]
+	pub plan: Account<'info, RecurringPlan>,
+
+	#[account(constraint = ticker_mint_account.key() == payload.ticker_mint)]
+	pub ticker_mint_account: Account<'info, Mint>,
+
+	#[account(constraint = payment_mint_account.key() == payload.payment_mint)]
+	pub payment_mint_account: Account<'info, Mint>,
+
+	/// Открытый рынок пары (тикер, платёжный токен)
+	#[account(
+		seeds = [b"market", payload.ticker_mint.as_ref(), payload.payment_mint.as_ref()],
+		bump = market.bump,
+		constraint = market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	/// АТА мейкера для платежного токена, делегируется плану
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == payer.key(),
+		constraint = maker_payment_account.mint == payload.payment_mint,
+	)]
+	pub maker_payment_account: Account<'info, TokenAccount>,
+
+	/// CHECK: PDA-делегат платёжного ATA мейкера, общий для всех его планов
+	#[account(seeds = [b"plan_delegate", maker_payment_account.key().as_ref()], bump)]
+	pub plan_delegate: UncheckedAccount<'info>,
+
+	/// АТА мейкера под тикер, куда будут минтиться купленные акции
+	#[account(
+		init_if_needed,
+		payer = payer,
+		associated_token::mint = ticker_mint_account,
+		associated_token::authority = payer,
+	)]
+	pub maker_ticker_account: Account<'info, TokenAccount>,
+
+	/// CHECK: instruction sysvar, used for verifying oracle signature
+	#[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+	pub instruction_sysvar: AccountInfo<'info>,
+	pub system_program: Program<'info, System>,
+	pub token_program: Program<'info, Token>,
+	pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+}
+
+pub fn create(ctx: Context<CreatePlan>, payload: PlanPayload) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	require!(now <= payload.expires_at, ErrorCode::PayloadExpired);
+
+	let serialized = payload.message()?;
+
+	let registry = &ctx.accounts.registry;
+	verify_oracle_quorum(&ctx.accounts.instruction_sysvar, &registry.oracles, registry.threshold, &serialized)?;
+
+	require!(payload.amount > 0, ErrorCode::InvalidPlan);
+	require!(payload.interval > 0, ErrorCode::InvalidPlan);
+	require!(payload.end_at >= payload.start_at && payload.end_at > now, ErrorCode::InvalidPlan);
+	ctx.accounts.market.check_notional(payload.amount)?;
+
+	let plan = &mut ctx.accounts.plan;
+	plan.id = payload.id;
+	plan.maker = payload.maker;
+
+	plan.ticker_mint = payload.ticker_mint;
+	plan.payment_mint = payload.payment_mint;
+
+	plan.amount = payload.amount;
+	plan.fee = payload.fee;
+
+	plan.interval = payload.interval;
+	plan.next_at = payload.start_at.max(now);
+	plan.end_at = payload.end_at;
+
+	plan.status = PlanStatus::Active;
+	plan.spawned = 0;
+
+	// Сумма на все оставшиеся периоды плана
+	let allowance = plan.amount
+		.checked_add(plan.fee)
+		.and_then(|per_period| per_period.checked_mul(plan.periods_left(plan.next_at)))
+		.ok_or(ErrorCode::Overflow)?;
+	plan.allowance = allowance;
+
+	// У SPL-аккаунта один делегат, поэтому он общий для всех планов мейкера на этом ATA:
+	// одобрение увеличивается на сумму нового плана, а не заменяется
+	let delegate = ctx.accounts.plan_delegate.key();
+	let payment_account = &ctx.accounts.maker_payment_account;
+	let approved = if payment_account.delegate == COption::Some(delegate) {
+		payment_account.delegated_amount.checked_add(allowance).ok_or(ErrorCode::Overflow)?
+	} else {
+		allowance
+	};
+
+	let cpi_ctx = CpiContext::new(
+		ctx.accounts.token_program.to_account_info(),
+		token::Approve {
+			to: ctx.accounts.maker_payment_account.to_account_info(),
+			delegate: ctx.accounts.plan_delegate.to_account_info(),
+			authority: ctx.accounts.payer.to_account_info(),
+		},
+	);
+	token::approve(cpi_ctx, approved)?;
+
+	emit!(PlanCreated {
+		id: plan.id,
+		maker: plan.maker,
+
+		ticker_mint: plan.ticker_mint,
+		payment_mint: plan.payment_mint,
+
+		amount: plan.amount,
+		fee: plan.fee,
+
+		interval: plan.interval,
+		next_at: plan.next_at,
+		end_at: plan.end_at,
+
+		allowance,
+	});
+
+	Ok(())
+}
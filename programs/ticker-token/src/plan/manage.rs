@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::{
+	token::{self, TokenAccount, Token}
+};
+use crate::{
+	errors::ErrorCode,
+	plan::state::*,
+};
+
+#[derive(Accounts)]
+pub struct ManagePlan<'info> {
+	#[account(
+		constraint = payer.key() == plan.maker @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"plan", plan.maker.as_ref(), &plan.id.to_le_bytes()],
+		bump,
+	)]
+	pub plan: Account<'info, RecurringPlan>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPlan<'info> {
+	#[account(
+		mut,
+		constraint = payer.key() == plan.maker @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(
+		mut,
+		seeds = [b"plan", plan.maker.as_ref(), &plan.id.to_le_bytes()],
+		bump,
+		close = payer,
+	)]
+	pub plan: Account<'info, RecurringPlan>,
+
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == payer.key() @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_payment_account.mint == plan.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub maker_payment_account: Account<'info, TokenAccount>,
+
+	/// CHECK: PDA-делегат платёжного ATA мейкера, общий для всех его планов
+	#[account(seeds = [b"plan_delegate", maker_payment_account.key().as_ref()], bump)]
+	pub plan_delegate: UncheckedAccount<'info>,
+
+	pub token_program: Program<'info, Token>,
+}
+
+fn set_status(plan: &mut RecurringPlan, status: PlanStatus) -> Result<()> {
+	plan.status = status;
+
+	emit!(PlanStatusChanged {
+		id: plan.id,
+		maker: plan.maker,
+
+		status,
+		timestamp: Clock::get()?.unix_timestamp,
+	});
+
+	Ok(())
+}
+
+pub fn pause(ctx: Context<ManagePlan>) -> Result<()> {
+	let plan = &mut ctx.accounts.plan;
+	require!(plan.status == PlanStatus::Active, ErrorCode::PlanNotActive);
+
+	set_status(plan, PlanStatus::Paused)
+}
+
+pub fn resume(ctx: Context<ManagePlan>) -> Result<()> {
+	let plan = &mut ctx.accounts.plan;
+	require!(plan.status == PlanStatus::Paused, ErrorCode::InvalidStatusTransition);
+
+	// пропущенные за время паузы периоды не догоняем
+	let now = Clock::get()?.unix_timestamp;
+	plan.next_at = plan.next_at.max(now);
+
+	set_status(plan, PlanStatus::Active)
+}
+
+pub fn cancel(ctx: Context<CancelPlan>) -> Result<()> {
+	// уменьшаем общее одобрение на остаток этого плана, не трогая остальные планы;
+	// если делегат уже другой, одобрение мейкер сменил сам
+	let payment_account = &ctx.accounts.maker_payment_account;
+	if payment_account.delegate == COption::Some(ctx.accounts.plan_delegate.key()) {
+		let remaining = payment_account.delegated_amount.saturating_sub(ctx.accounts.plan.allowance);
+
+		if remaining == 0 {
+			let cpi_ctx = CpiContext::new(
+				ctx.accounts.token_program.to_account_info(),
+				token::Revoke {
+					source: ctx.accounts.maker_payment_account.to_account_info(),
+					authority: ctx.accounts.payer.to_account_info(),
+				},
+			);
+			token::revoke(cpi_ctx)?;
+		} else {
+			let cpi_ctx = CpiContext::new(
+				ctx.accounts.token_program.to_account_info(),
+				token::Approve {
+					to: ctx.accounts.maker_payment_account.to_account_info(),
+					delegate: ctx.accounts.plan_delegate.to_account_info(),
+					authority: ctx.accounts.payer.to_account_info(),
+				},
+			);
+			token::approve(cpi_ctx, remaining)?;
+		}
+	}
+
+	set_status(&mut ctx.accounts.plan, PlanStatus::Canceled)
+}
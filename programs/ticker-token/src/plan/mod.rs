@@ -0,0 +1,11 @@
+pub mod state;
+pub use state::*;
+
+pub mod create;
+pub use create::*;
+
+pub mod manage;
+pub use manage::*;
+
+pub mod spawn;
+pub use spawn::*;
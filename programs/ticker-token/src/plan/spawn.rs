@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+	token::{self, TokenAccount, Token, Mint}
+};
+use crate::{
+	Registry,
+	errors::ErrorCode,
+	config::TickerConfig,
+	market::{Market, MarketStatus},
+	ticker::{TickerRecord, TickerStatus},
+	price,
+	order::{types::*, state::*},
+	plan::state::*,
+};
+
+#[derive(Accounts)]
+pub struct SpawnPlanOrder<'info> {
+	/// Исполнитель, создаёт ордер по плану и оплачивает ренту
+	#[account(
+		mut,
+		constraint = payer.key() == registry.authority @ ErrorCode::Unauthorized,
+	)]
+	pub payer: Signer<'info>,
+
+	#[account(seeds = [b"registry"], bump)]
+	pub registry: Account<'info, Registry>,
+
+	#[account(
+		mut,
+		seeds = [b"plan", plan.maker.as_ref(), &plan.id.to_le_bytes()],
+		bump,
+	)]
+	pub plan: Account<'info, RecurringPlan>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"order", plan.maker.as_ref(), &plan.order_id(plan.spawned).to_le_bytes()],
+		bump,
+		space = 8 + std::mem::size_of::<Order>(),
+	)]
+	pub order: Account<'info, Order>,
+
+	#[account(constraint = ticker_mint_account.key() == plan.ticker_mint @ ErrorCode::InvalidTickerMint)]
+	pub ticker_mint_account: Account<'info, Mint>,
+
+	#[account(constraint = payment_mint_account.key() == plan.payment_mint @ ErrorCode::InvalidPaymentMint)]
+	pub payment_mint_account: Account<'info, Mint>,
+
+	#[account(seeds = [b"config", plan.ticker_mint.as_ref()], bump = ticker_config.bump)]
+	pub ticker_config: Box<Account<'info, TickerConfig>>,
+
+	/// Открытый рынок пары (тикер, платёжный токен)
+	#[account(
+		seeds = [b"market", plan.ticker_mint.as_ref(), plan.payment_mint.as_ref()],
+		bump = market.bump,
+		constraint = market.status == MarketStatus::Open @ ErrorCode::MarketNotOpen,
+	)]
+	pub market: Box<Account<'info, Market>>,
+
+	/// Тикер не делистингован
+	#[account(
+		seeds = [b"ticker", plan.ticker_mint.as_ref()],
+		bump = record.bump,
+		constraint = record.status == TickerStatus::Listed @ ErrorCode::TickerAlreadyDelisted,
+	)]
+	pub record: Box<Account<'info, TickerRecord>>,
+
+	/// АТА мейкера для платежного токена, делегат — общий PDA планов мейкера
+	#[account(
+		mut,
+		constraint = maker_payment_account.owner == plan.maker @ ErrorCode::InvalidUserTokenAccount,
+		constraint = maker_payment_account.mint == plan.payment_mint @ ErrorCode::InvalidPaymentMint,
+	)]
+	pub maker_payment_account: Account<'info, TokenAccount>,
+
+	/// CHECK: PDA-делегат платёжного ATA мейкера
+	#[account(seeds = [b"plan_delegate", maker_payment_account.key().as_ref()], bump)]
+	pub plan_delegate: UncheckedAccount<'info>,
+
+	#[account(
+		init,
+		payer = payer,
+		seeds = [b"escrow", order.key().as_ref()],
+		bump,
+		token::mint = payment_mint_account,
+		token::authority = order,
+	)]
+	pub escrow_account: Account<'info, TokenAccount>,
+
+	pub system_program: Program<'info, System>,
+	pub token_program: Program<'info, Token>,
+}
+
+/// Создаёт рыночный Notional-ордер на покупку за очередной период плана.
+/// Средства списываются с ATA мейкера по одобрению, выданному плану.
+pub fn spawn(ctx: Context<SpawnPlanOrder>) -> Result<()> {
+	let now = Clock::get()?.unix_timestamp;
+	let plan = &mut ctx.accounts.plan;
+
+	require!(plan.status == PlanStatus::Active, ErrorCode::PlanNotActive);
+	require!(now >= plan.next_at, ErrorCode::PlanNotDue);
+	require!(plan.next_at <= plan.end_at, ErrorCode::PlanEnded);
+	// сумма периода — рыночная Notional-заявка по текущим ограничениям тикера
+	ctx.accounts.ticker_config.check_notional(plan.amount)?;
+	ctx.accounts.market.check_notional(plan.amount)?;
+
+	let order = &mut ctx.accounts.order;
+	order.id = plan.order_id(plan.spawned);
+
+	order.side = OrderSide::Buy;
+	order.market = true;
+	order.mode = OrderMode::Notional;
+
+	order.maker = plan.maker;
+
+	order.ticker_mint = plan.ticker_mint;
+	order.amount = plan.amount;
+
+	order.payment_mint = plan.payment_mint;
+	order.price = 0;
+	order.price_scale = price::price_scale(ctx.accounts.ticker_mint_account.decimals)?;
+	order.fee = plan.fee;
+	order.trigger = None;
+	order.twap = None;
+	order.allow_partial = false;
+
+	order.status = OrderStatus::Pending;
+	order.expires_at = now + plan.interval; // ордер периода действует до следующего периода
+
+	// Сумма периода списывается с остатка одобрения этого плана
+	let escrowed = order.escrow_amount()?;
+	plan.allowance = plan.allowance.checked_sub(escrowed).ok_or(ErrorCode::PlanAllowanceExhausted)?;
+
+	// Переводим сумму периода в эскроу от имени общего делегата
+	let payment_account = ctx.accounts.maker_payment_account.key();
+	let signer_seeds: [&[u8]; 3] = [
+		b"plan_delegate",
+		payment_account.as_ref(),
+		&[ctx.bumps.plan_delegate],
+	];
+	let signer: &[&[&[u8]]] = &[&signer_seeds];
+
+	let cpi_ctx = CpiContext::new_with_signer(
+		ctx.accounts.token_program.to_account_info(),
+		token::Transfer {
+			from: ctx.accounts.maker_payment_account.to_account_info(),
+			to: ctx.accounts.escrow_account.to_account_info(),
+			authority: ctx.accounts.plan_delegate.to_account_info(),
+		},
+		signer,
+	);
+	token::transfer(cpi_ctx, escrowed)?;
+
+	// Следующий период; пропущенные периоды не накапливаются
+	plan.next_at = plan.next_period(now)?;
+	plan.spawned += 1;
+
+	emit!(OrderCreated {
+		id: order.id,
+		maker: order.maker,
+		timestamp: now,
+		expires_at: order.expires_at,
+	});
+
+	emit!(PlanOrderSpawned {
+		plan_id: plan.id,
+		maker: plan.maker,
+
+		order_id: order.id,
+		amount: plan.amount,
+		fee: plan.fee,
+
+		next_at: plan.next_at,
+		timestamp: now,
+	});
+
+	Ok(())
+}
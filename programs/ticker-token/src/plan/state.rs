@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use crate::utils::{SignedMessage, MessageKind};
+
+/// Payload регулярной покупки (DCA), подписывается оракулами один раз при создании плана
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlanPayload {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub amount: u64, // сумма покупки за период в платёжном токене
+    pub fee: u64, // комиссия за период
+
+    pub interval: i64, // период в секундах
+    pub start_at: i64, // время первой покупки
+    pub end_at: i64, // после этого времени ордера не создаются
+
+    pub expires_at: i64, // срок действия payload
+}
+
+impl SignedMessage for PlanPayload {
+	const KIND: MessageKind = MessageKind::PlanPayload;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PlanStatus {
+    Active,
+    Paused,
+    Canceled,
+}
+
+/// План регулярной покупки, PDA ['plan', maker, id].
+/// Делегат платёжного ATA мейкера — общий для всех его планов PDA ['plan_delegate', ata]:
+/// одобрение делегату равно сумме остатков `allowance` активных планов, из него исполнитель
+/// создаёт Notional-ордер на покупку каждый период без новой подписи мейкера.
+#[account]
+pub struct RecurringPlan {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub amount: u64,
+    pub fee: u64,
+
+    pub interval: i64,
+    pub next_at: i64, // когда можно создать следующий ордер
+    pub end_at: i64,
+
+    pub status: PlanStatus,
+    pub spawned: u64, // сколько ордеров создано по плану
+    pub allowance: u64, // остаток одобренной под этот план суммы
+}
+
+impl RecurringPlan {
+    pub const SPACE: usize = 8 // discriminator
+        + 8 // id
+        + 32 // maker
+        + 32 // ticker_mint
+        + 32 // payment_mint
+        + 8 // amount
+        + 8 // fee
+        + 8 // interval
+        + 8 // next_at
+        + 8 // end_at
+        + 1 // status
+        + 8 // spawned
+        + 8; // allowance
+
+    /// Сколько периодов (ордеров) осталось с `from` до конца плана включительно
+    pub fn periods_left(&self, from: i64) -> u64 {
+        if from > self.end_at {
+            return 0;
+        }
+        ((self.end_at - from) / self.interval) as u64 + 1
+    }
+
+    /// Id ордера периода `index`: детерминирован планом и не пересекается с id,
+    /// которые выдают оракулы для обычных ордеров мейкера
+    pub fn order_id(&self, index: u64) -> u64 {
+        let hash = keccak::hashv(&[
+            b"plan_order",
+            self.maker.as_ref(),
+            &self.id.to_le_bytes(),
+            &index.to_le_bytes(),
+        ]);
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&hash.as_ref()[..8]);
+        u64::from_le_bytes(id)
+    }
+
+    /// Первый период строго после `now`, пропущенные периоды не накапливаются
+    pub fn next_period(&self, now: i64) -> Result<i64> {
+        if self.next_at > now {
+            return Ok(self.next_at);
+        }
+        let skipped = (now - self.next_at) / self.interval + 1;
+        skipped.checked_mul(self.interval)
+            .and_then(|offset| self.next_at.checked_add(offset))
+            .ok_or(error!(crate::errors::ErrorCode::Overflow))
+    }
+}
+
+#[event]
+pub struct PlanCreated {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub ticker_mint: Pubkey,
+    pub payment_mint: Pubkey,
+
+    pub amount: u64,
+    pub fee: u64,
+
+    pub interval: i64,
+    pub next_at: i64,
+    pub end_at: i64,
+
+    pub allowance: u64, // одобренная делегату сумма
+}
+
+#[event]
+pub struct PlanStatusChanged {
+    pub id: u64,
+    pub maker: Pubkey,
+
+    pub status: PlanStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlanOrderSpawned {
+    pub plan_id: u64,
+    pub maker: Pubkey,
+
+    pub order_id: u64,
+    pub amount: u64,
+    pub fee: u64,
+
+    pub next_at: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+
+/// Политика округления при пересчёте количества тикера в сумму платёжного токена и обратно.
+/// Суммы, которые платит пользователь (залог, списание), округляются вверх,
+/// суммы, которые пользователь получает (выплата, минт), — вниз.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Масштаб цены по умолчанию: цена указывается за одну целую акцию (`10^decimals` базовых единиц тикера)
+pub fn price_scale(ticker_decimals: u8) -> Result<u64> {
+    10u64.checked_pow(ticker_decimals as u32).ok_or(error!(ErrorCode::Overflow))
+}
+
+/// `a * b / c` в u128 с заданным округлением
+pub fn mul_div(a: u64, b: u64, c: u64, rounding: Rounding) -> Result<u64> {
+    require!(c > 0, ErrorCode::Overflow);
+
+    let product = (a as u128) * (b as u128);
+    let mut result = product / c as u128;
+
+    if rounding == Rounding::Up && result * c as u128 != product {
+        result += 1;
+    }
+
+    u64::try_from(result).map_err(|_| error!(ErrorCode::Overflow))
+}
+
+/// Стоимость `amount` базовых единиц тикера по цене `price`
+/// (в базовых единицах платёжного токена за `price_scale` базовых единиц тикера)
+pub fn notional(amount: u64, price: u64, price_scale: u64, rounding: Rounding) -> Result<u64> {
+    mul_div(amount, price, price_scale, rounding)
+}
+
+/// Количество базовых единиц тикера, которое стоит `notional` по цене `price`
+pub fn quantity(notional: u64, price: u64, price_scale: u64, rounding: Rounding) -> Result<u64> {
+    require!(price > 0, ErrorCode::InvalidPrice);
+    mul_div(notional, price_scale, price, rounding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(result: Result<u64>) -> u32 {
+        match result.unwrap_err() {
+            Error::AnchorError(error) => error.error_code_number,
+            Error::ProgramError(error) => panic!("unexpected program error {error:?}"),
+        }
+    }
+
+    #[test]
+    fn mul_div_rounds_only_inexact_results() {
+        assert_eq!(mul_div(10, 3, 4, Rounding::Down).unwrap(), 7);
+        assert_eq!(mul_div(10, 3, 4, Rounding::Up).unwrap(), 8);
+
+        // точный результат не округляется
+        assert_eq!(mul_div(12, 3, 4, Rounding::Down).unwrap(), 9);
+        assert_eq!(mul_div(12, 3, 4, Rounding::Up).unwrap(), 9);
+
+        assert_eq!(mul_div(0, 3, 4, Rounding::Up).unwrap(), 0);
+        assert_eq!(mul_div(1, 1, u64::MAX, Rounding::Up).unwrap(), 1);
+    }
+
+    #[test]
+    fn mul_div_uses_wide_intermediate() {
+        // произведение не помещается в u64, частное — помещается
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Down).unwrap(), u64::MAX);
+        assert_eq!(mul_div(u64::MAX, 10, 20, Rounding::Up).unwrap(), u64::MAX / 2 + 1);
+    }
+
+    #[test]
+    fn mul_div_rejects_overflow_and_zero_divisor() {
+        let overflow = ErrorCode::Overflow as u32 + anchor_lang::error::ERROR_CODE_OFFSET;
+
+        assert_eq!(code(mul_div(u64::MAX, 2, 1, Rounding::Down)), overflow);
+
+        // (2^65 - 1) / 2: вниз — ровно u64::MAX, вверх — уже за пределами u64
+        let a = ((1u128 << 65) - 1) / 31;
+        assert_eq!(mul_div(a as u64, 31, 2, Rounding::Down).unwrap(), u64::MAX);
+        assert_eq!(code(mul_div(a as u64, 31, 2, Rounding::Up)), overflow);
+        assert_eq!(code(mul_div(1, 1, 0, Rounding::Down)), overflow);
+    }
+
+    #[test]
+    fn notional_and_quantity_round_by_policy() {
+        let scale = price_scale(6).unwrap();
+        assert_eq!(scale, 1_000_000);
+
+        // 1.5 акции по 3 за акцию
+        assert_eq!(notional(1_500_000, 3, scale, Rounding::Down).unwrap(), 4);
+        assert_eq!(notional(1_500_000, 3, scale, Rounding::Up).unwrap(), 5);
+
+        // на 10 единиц платёжного токена по цене 3 — 3.333333(3) акции
+        assert_eq!(quantity(10, 3, scale, Rounding::Down).unwrap(), 3_333_333);
+        assert_eq!(quantity(10, 3, scale, Rounding::Up).unwrap(), 3_333_334);
+    }
+
+    #[test]
+    fn quantity_rejects_zero_price() {
+        let invalid = ErrorCode::InvalidPrice as u32 + anchor_lang::error::ERROR_CODE_OFFSET;
+        assert_eq!(code(quantity(10, 0, 1, Rounding::Down)), invalid);
+    }
+
+    #[test]
+    fn price_scale_rejects_overflow() {
+        assert_eq!(price_scale(0).unwrap(), 1);
+        assert_eq!(price_scale(19).unwrap(), 10u64.pow(19));
+        assert!(price_scale(20).is_err());
+    }
+}
@@ -1,16 +1,96 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     token::{Token, Mint},
 };
 
 use crate::{
     Registry, 
-    errors::TickerError
+    errors::{TickerError, ErrorCode},
+    feed::PriceFeed,
+    config::TickerConfig,
 };
 
+/// Seed PDA mint тикера. Вызывается в `seeds`, поэтому длина проверяется до создания аккаунтов инструкции.
+pub fn symbol_seed(symbol: &str) -> Result<&[u8]> {
+    require!(symbol.len() <= MAX_SYMBOL_LEN, TickerError::TickerTooLong);
+    Ok(symbol.as_bytes())
+}
+
+/// Тикеров на одной странице индекса
+pub const TICKERS_PER_PAGE: usize = 64;
+
+/// Максимальная длина символа — ограничение длины seed PDA mint
+pub const MAX_SYMBOL_LEN: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TickerEntry {
+    pub mint: Pubkey,
+    pub symbol: String,
+}
+
+/// Страница списка тикеров, PDA ['index', page_le]. Тикеры дописываются по порядку создания,
+/// `registry.ticker_count` указывает на следующую свободную позицию.
+#[account]
+pub struct TickerIndex {
+    pub page: u32,
+    pub tickers: Vec<TickerEntry>,
+    pub bump: u8,
+}
+
+impl TickerIndex {
+    pub const SPACE: usize = 8 // discriminator
+        + 4 // page
+        + 4 + (32 + 4 + MAX_SYMBOL_LEN) * TICKERS_PER_PAGE // tickers
+        + 1; // bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TickerStatus {
+    Listed, // торгуется
+    Delisted, // выведен с биржи, держатели погашают токены по финальной цене
+}
+
+/// Обратный поиск тикера по mint, PDA ['ticker', mint]
+#[account]
+pub struct TickerRecord {
+    pub mint: Pubkey,
+    pub symbol: String,
+    pub decimals: u8,
+
+    pub index: u64, // порядковый номер в индексе: страница index / TICKERS_PER_PAGE
+    pub created_at: i64,
+
+    pub status: TickerStatus,
+
+    // финальный расчёт, заполняется при делистинге
+    pub payment_mint: Pubkey,
+    pub final_price: u64,
+    pub price_scale: u64,
+    pub delisted_at: i64,
+
+    pub bump: u8,
+}
+
+impl TickerRecord {
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // mint
+        + 4 + MAX_SYMBOL_LEN // symbol
+        + 1 // decimals
+        + 8 // index
+        + 8 // created_at
+        + 1 // status
+        + 32 // payment_mint
+        + 8 // final_price
+        + 8 // price_scale
+        + 8 // delisted_at
+        + 1; // bump
+}
+
 #[event]
 pub struct TickerCreated {
-    pub ticker: String
+    pub ticker: String,
+    pub mint: Pubkey,
+    pub index: u64,
 }
 // TODO: Metaplex support
 
@@ -23,13 +103,18 @@ pub struct CreateTicker<'info> {
     )]
     pub payer: Signer<'info>,
 
-    #[account(seeds = [b"registry"], bump)]
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump,
+        constraint = registry.to_account_info().data_len() == Registry::SPACE @ ErrorCode::RegistryNotMigrated,
+    )]
     pub registry: Account<'info, Registry>,
 
     /// CHECK: mint account is created in this instruction and its validity is ensured by context
     #[account(
         init,
-        seeds = [b"mint", ticker.as_bytes()],
+        seeds = [b"mint", symbol_seed(&ticker)?],
         bump,
         payer = payer,
         mint::decimals = decimals,
@@ -38,7 +123,177 @@ pub struct CreateTicker<'info> {
     )]
     pub mint: Account<'info, Mint>,
 
+    #[account(
+        init,
+        seeds = [b"feed", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PriceFeed::SPACE,
+    )]
+    pub feed: Account<'info, PriceFeed>,
+
+    #[account(
+        init,
+        seeds = [b"config", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = TickerConfig::SPACE,
+    )]
+    pub config: Account<'info, TickerConfig>,
+
+    /// Текущая страница индекса, новая создаётся при заполнении предыдущей
+    #[account(
+        init_if_needed,
+        seeds = [b"index", registry.index_page().to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = TickerIndex::SPACE,
+    )]
+    pub index: Box<Account<'info, TickerIndex>>,
+
+    #[account(
+        init,
+        seeds = [b"ticker", mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = TickerRecord::SPACE,
+    )]
+    pub record: Box<Account<'info, TickerRecord>>,
+
     pub rent: Sysvar<'info, Rent>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
+
+pub fn initialize(ctx: Context<CreateTicker>, symbol: String) -> Result<()> {
+    let mint = ctx.accounts.mint.key();
+    ctx.accounts.feed.reset(mint, ctx.bumps.feed);
+    ctx.accounts.config.reset(mint, ctx.bumps.config);
+
+    register(
+        (&mut ctx.accounts.registry, &mut ctx.accounts.index, &mut ctx.accounts.record),
+        (ctx.bumps.index, ctx.bumps.record),
+        &ctx.accounts.mint,
+        symbol,
+    )
+}
+
+/// Дописывает тикер в индекс и создаёт его `TickerRecord`
+fn register(
+    (registry, index, record): (&mut Registry, &mut TickerIndex, &mut TickerRecord),
+    (index_bump, record_bump): (u8, u8),
+    mint: &Account<Mint>,
+    symbol: String,
+) -> Result<()> {
+    let position = registry.ticker_count;
+
+    index.page = registry.index_page();
+    index.bump = index_bump;
+    index.tickers.push(TickerEntry { mint: mint.key(), symbol: symbol.clone() });
+
+    record.mint = mint.key();
+    record.symbol = symbol.clone();
+    record.decimals = mint.decimals;
+    record.index = position;
+    record.created_at = Clock::get()?.unix_timestamp;
+    record.status = TickerStatus::Listed;
+    record.bump = record_bump;
+
+    registry.ticker_count += 1;
+
+    emit!(TickerCreated { ticker: symbol, mint: mint.key(), index: position });
+    Ok(())
+}
+
+/// Перенос `Registry` прежней раскладки в текущую
+#[derive(Accounts)]
+pub struct MigrateRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: прежняя раскладка не читается как `Registry`, она и authority проверяются в `extend_registry`
+    #[account(mut, seeds = [b"registry"], bump, owner = crate::ID @ ErrorCode::InvalidLegacyAccount)]
+    pub registry: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Увеличивает `Registry` любой прежней раскладки до текущего размера. Оракулы, порог,
+/// брокер и срок хранения ордеров переносятся, если уже были; счётчик тикеров обнуляется:
+/// существующие тикеры затем по одному дописываются в индекс через `index_ticker`
+pub fn extend_registry(ctx: Context<MigrateRegistry>) -> Result<()> {
+    let info = ctx.accounts.registry.to_account_info();
+
+    let registry = Registry::from_legacy(&info.try_borrow_data()?)?;
+    require!(registry.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+
+    let rent = Rent::get()?.minimum_balance(Registry::SPACE).saturating_sub(info.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    info.resize(Registry::SPACE)?;
+
+    let mut data = info.try_borrow_mut_data()?;
+    registry.try_serialize(&mut &mut data[..])
+}
+
+/// Индекс и `TickerRecord` для тикера, созданного до их появления
+#[derive(Accounts)]
+#[instruction(symbol: String)]
+pub struct IndexTicker<'info> {
+    #[account(
+        mut,
+        constraint = authority.key() == registry.authority @ ErrorCode::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump,
+        constraint = registry.to_account_info().data_len() == Registry::SPACE @ ErrorCode::RegistryNotMigrated,
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(seeds = [b"mint", symbol_seed(&symbol)?], bump)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"index", registry.index_page().to_le_bytes().as_ref()],
+        bump,
+        payer = authority,
+        space = TickerIndex::SPACE,
+    )]
+    pub index: Box<Account<'info, TickerIndex>>,
+
+    /// Уже проиндексированный тикер повторно не добавляется: запись существует
+    #[account(
+        init,
+        seeds = [b"ticker", mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = TickerRecord::SPACE,
+    )]
+    pub record: Box<Account<'info, TickerRecord>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn index(ctx: Context<IndexTicker>, symbol: String) -> Result<()> {
+    register(
+        (&mut ctx.accounts.registry, &mut ctx.accounts.index, &mut ctx.accounts.record),
+        (ctx.bumps.index, ctx.bumps.record),
+        &ctx.accounts.mint,
+        symbol,
+    )
+}
@@ -1,35 +1,283 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
-    sysvar::instructions::load_instruction_at_checked,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
     ed25519_program::ID as ED25519_PROGRAM_ID,
+    secp256k1_program::ID as SECP256K1_PROGRAM_ID,
     keccak,
 };
 use anchor_spl::associated_token::get_associated_token_address;
 
-use crate::errors::ErrorCode;
+use crate::{OracleKey, errors::ErrorCode};
 
-pub fn verify_ed25519_ix(
+/// Размер заголовка ed25519-инструкции (кол-во подписей + padding)
+const ED25519_HEADER_LEN: usize = 2;
+/// Размер структуры Ed25519SignatureOffsets
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Размер заголовка secp256k1-инструкции (кол-во подписей)
+const SECP256K1_HEADER_LEN: usize = 1;
+/// Размер структуры SecpSignatureOffsets
+const SECP256K1_OFFSETS_LEN: usize = 11;
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8> {
+    data.get(offset).copied().ok_or(error!(ErrorCode::InvalidSignatureInstruction))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data.get(offset..offset + 2).ok_or(ErrorCode::InvalidSignatureInstruction)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    Ok(data.get(offset..offset + len).ok_or(ErrorCode::InvalidSignatureInstruction)?)
+}
+
+/// Возвращает пары (ключ, сообщение) всех подписей из данных ed25519-инструкции.
+/// Принимаются только подписи, данные которых лежат в самой инструкции.
+fn ed25519_signatures(data: &[u8]) -> Result<Vec<(OracleKey, &[u8])>> {
+    let count = read_u8(data, 0)? as usize;
+    let mut signatures = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let start = ED25519_HEADER_LEN + i * ED25519_OFFSETS_LEN;
+
+        let sig_ix = read_u16(data, start + 2)?;
+        let pubkey_off = read_u16(data, start + 4)? as usize;
+        let pubkey_ix = read_u16(data, start + 6)?;
+        let msg_off = read_u16(data, start + 8)? as usize;
+        let msg_len = read_u16(data, start + 10)? as usize;
+        let msg_ix = read_u16(data, start + 12)?;
+
+        require!(
+            sig_ix == u16::MAX && pubkey_ix == u16::MAX && msg_ix == u16::MAX,
+            ErrorCode::InvalidSignatureInstruction
+        );
+
+        let mut pubkey = [0u8; 32];
+        pubkey.copy_from_slice(read_slice(data, pubkey_off, 32)?);
+        let msg = read_slice(data, msg_off, msg_len)?;
+        signatures.push((OracleKey::Ed25519(Pubkey::new_from_array(pubkey)), msg));
+    }
+
+    Ok(signatures)
+}
+
+/// Возвращает пары (ключ, сообщение) всех подписей из данных secp256k1-инструкции,
+/// расположенной в транзакции на позиции `index`.
+fn secp256k1_signatures(data: &[u8], index: usize) -> Result<Vec<(OracleKey, &[u8])>> {
+    let count = read_u8(data, 0)? as usize;
+    let mut signatures = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let start = SECP256K1_HEADER_LEN + i * SECP256K1_OFFSETS_LEN;
+
+        let sig_ix = read_u8(data, start + 2)? as usize;
+        let address_off = read_u16(data, start + 3)? as usize;
+        let address_ix = read_u8(data, start + 5)? as usize;
+        let msg_off = read_u16(data, start + 6)? as usize;
+        let msg_len = read_u16(data, start + 8)? as usize;
+        let msg_ix = read_u8(data, start + 10)? as usize;
+
+        require!(
+            sig_ix == index && address_ix == index && msg_ix == index,
+            ErrorCode::InvalidSignatureInstruction
+        );
+
+        let mut address = [0u8; 20];
+        address.copy_from_slice(read_slice(data, address_off, 20)?);
+        let msg = read_slice(data, msg_off, msg_len)?;
+        signatures.push((OracleKey::Secp256k1(address), msg));
+    }
+
+    Ok(signatures)
+}
+
+/// Префикс домена подписи для всех сообщений программы
+pub const MESSAGE_DOMAIN: &[u8] = b"ticker-token";
+
+/// Тип подписанного сообщения. Входит в подписываемые данные, чтобы подпись
+/// одной структуры нельзя было выдать за подпись другой.
+#[derive(Clone, Copy)]
+#[repr(u8)]
+pub enum MessageKind {
+    OrderPayload = 1,
+    ExecutionReport = 2,
+    PriceAttestation = 3,
+    PriceFeedUpdate = 4,
+    PlanPayload = 5,
+    SwitchPayload = 6,
+    SwitchReport = 7,
+    BasketPayload = 8,
+    BasketReport = 9,
+}
+
+/// Подписываемая структура: сообщение = домен || тип || program id || borsh(self)
+pub trait SignedMessage: AnchorSerialize {
+    const KIND: MessageKind;
+
+    fn message(&self) -> Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(MESSAGE_DOMAIN.len() + 1 + 32);
+        message.extend_from_slice(MESSAGE_DOMAIN);
+        message.push(Self::KIND as u8);
+        message.extend_from_slice(crate::ID.as_ref());
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Проверяет, что keccak(serialized_data) подписан как минимум `threshold`
+/// различными ключами из `oracles`. Подписи берутся из всех ed25519- и
+/// secp256k1-инструкций, расположенных в транзакции перед текущей.
+pub fn verify_oracle_quorum(
     instruction_sysvar: &AccountInfo,
-    expected_pubkey: &Pubkey,
+    oracles: &[OracleKey],
+    threshold: u8,
     serialized_data: &[u8],
 ) -> Result<()> {
-    let hash = keccak::hash(&serialized_data);
+    require!(threshold > 0 && !oracles.is_empty(), ErrorCode::InvalidOracle);
+
+    let hash = keccak::hash(serialized_data);
     let expected_msg = hash.as_ref();
 
-    let ix = load_instruction_at_checked(0, instruction_sysvar)?;
-    require!(ix.program_id == ED25519_PROGRAM_ID, ErrorCode::InvalidSignatureInstruction);
+    let current = load_current_index_checked(instruction_sysvar)? as usize;
+    let mut signers: Vec<OracleKey> = Vec::with_capacity(oracles.len());
+
+    for index in 0..current {
+        let ix = load_instruction_at_checked(index, instruction_sysvar)?;
+        let signatures = if ix.program_id == ED25519_PROGRAM_ID {
+            ed25519_signatures(&ix.data)?
+        } else if ix.program_id == SECP256K1_PROGRAM_ID {
+            secp256k1_signatures(&ix.data, index)?
+        } else {
+            continue;
+        };
+
+        for (key, msg) in signatures {
+            if msg == expected_msg && oracles.contains(&key) && !signers.contains(&key) {
+                signers.push(key);
+            }
+        }
+    }
+
+    require!(signers.len() >= threshold as usize, ErrorCode::OracleQuorumNotReached);
+    Ok(())
+}
+
+/// Максимальный размер proof CID в байтах
+pub const MAX_PROOF_CID_LEN: usize = 96;
+/// Максимальный размер digest в multihash
+const MAX_MULTIHASH_DIGEST_LEN: u64 = 64;
+
+/// multicodec: identity (multibase-префикс бинарного CID и identity-multihash)
+const IDENTITY_CODE: u64 = 0x00;
+/// multicodec: sha2-256
+const SHA2_256_CODE: u8 = 0x12;
+
+fn read_varint(data: &[u8], offset: &mut usize) -> Result<u64> {
+    let mut value: u64 = 0;
+
+    for shift in (0..63).step_by(7) {
+        let byte = *data.get(*offset).ok_or(ErrorCode::InvalidProofCid)?;
+        *offset += 1;
+
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    err!(ErrorCode::InvalidProofCid)
+}
+
+/// Алфавит base32 (RFC 4648, нижний регистр) — multibase-префикс `b`
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+/// Алфавит base58btc — multibase-префикс `z` и текстовый CIDv0 `Qm…`
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn decode_base32(text: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+
+    for c in text {
+        let value = BASE32_ALPHABET.iter().position(|a| a == c).ok_or(ErrorCode::InvalidProofCid)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    // остаток без padding должен быть нулевым
+    require!(buffer == 0, ErrorCode::InvalidProofCid);
+    Ok(bytes)
+}
+
+fn decode_base58(text: &[u8]) -> Result<Vec<u8>> {
+    // big-endian число в base256, ведущие '1' — нулевые байты
+    let mut bytes: Vec<u8> = Vec::with_capacity(text.len());
+
+    for c in text {
+        let mut carry = BASE58_ALPHABET.iter().position(|a| a == c).ok_or(ErrorCode::InvalidProofCid)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let zeros = text.iter().take_while(|c| **c == b'1').count();
+    let mut decoded = vec![0u8; zeros];
+    decoded.extend(bytes);
+    Ok(decoded)
+}
+
+/// Приводит CID к бинарному виду. Бинарный CID начинается с `0x00`/`0x01`/`0x12`,
+/// текстовый — с multibase-префикса `b` (base32), `z` (base58btc) или `Qm` (CIDv0).
+fn decode_cid(cid: &[u8]) -> Result<Vec<u8>> {
+    match cid[0] {
+        b'b' => decode_base32(&cid[1..]),
+        b'z' => decode_base58(&cid[1..]),
+        b'Q' => decode_base58(cid),
+        _ => Ok(cid.to_vec()),
+    }
+}
+
+/// Проверяет, что `cid` — CIDv0 (sha2-256 multihash) или CIDv1 в бинарном виде
+/// (опционально с multibase-префиксом identity `0x00`) или в текстовом (см. `decode_cid`),
+/// и что multihash занимает ровно остаток данных.
+pub fn assert_cid(cid: &[u8]) -> Result<()> {
+    require!(!cid.is_empty() && cid.len() <= MAX_PROOF_CID_LEN, ErrorCode::InvalidProofCid);
+    let cid = decode_cid(cid)?;
+    require!(!cid.is_empty(), ErrorCode::InvalidProofCid);
+
+    // CIDv0: голый sha2-256 multihash
+    if cid.len() == 34 && cid[0] == SHA2_256_CODE && cid[1] == 32 {
+        return Ok(());
+    }
+
+    let mut offset = 0;
+    if cid[0] as u64 == IDENTITY_CODE {
+        offset += 1;
+    }
 
-    let data = &ix.data;
+    let version = read_varint(&cid, &mut offset)?;
+    require!(version == 1, ErrorCode::InvalidProofCid);
 
-    let pubkey_off = u16::from_le_bytes([data[6], data[7]]) as usize;
-    let msg_off = u16::from_le_bytes([data[10], data[11]]) as usize;
-    let msg_len = u16::from_le_bytes([data[12], data[13]]) as usize;
+    let _codec = read_varint(&cid, &mut offset)?;
 
-    let actual_pubkey = &data[pubkey_off..pubkey_off + 32];
-    let actual_msg = &data[msg_off..msg_off + msg_len];
+    let hash_code = read_varint(&cid, &mut offset)?;
+    let digest_len = read_varint(&cid, &mut offset)?;
 
-    require!(actual_pubkey == expected_pubkey.as_ref(), ErrorCode::InvalidOracleSig);
-    require!(actual_msg == expected_msg, ErrorCode::InvalidOracleSig);
+    require!(hash_code != IDENTITY_CODE, ErrorCode::InvalidProofCid);
+    require!(digest_len > 0 && digest_len <= MAX_MULTIHASH_DIGEST_LEN, ErrorCode::InvalidProofCid);
+    require!((cid.len() - offset) as u64 == digest_len, ErrorCode::InvalidProofCid);
 
     Ok(())
 }
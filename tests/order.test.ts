@@ -6,16 +6,23 @@ import { EventParser, web3  } from '@coral-xyz/anchor'
 
 import TickerToken, { Ticker } from '../lib/ticker-tocken.ts'
 
-import { Oracle, OrderSide } from '../lib/oracle.ts'
+import { Oracle, OrderSide, OrderMode, TriggerType, TimeInForce, MessageKind } from '../lib/oracle.ts'
 import { createUser, randomString } from '../lib/utils.ts'
-import { getAccount } from '@solana/spl-token'
+import { getAccount, getMint } from '@solana/spl-token'
+import { CID } from 'multiformats/cid'
 
 const oracle = new Oracle(TickerToken.signer.secretKey)
 
+// тикер с открытым рынком за платёжный токен оракула
+async function listTicker (symbol : string) {
+	await TickerToken.createTicker(symbol)
+	await TickerToken.openMarket(TickerToken.pda(['mint', symbol]), oracle.paymentMint)
+}
+
 test('[TickerToken] Order', async () => {
 	await test('fake oracle signature fails', async () => {
 		const symbol = randomString()
-		await TickerToken.createTicker(symbol)
+		await listTicker(symbol)
 		const fake = new Oracle(web3.Keypair.generate().secretKey)
 		const user = await createUser()
 		const { payload, message, signature } = await fake.payload(
@@ -27,7 +34,7 @@ test('[TickerToken] Order', async () => {
 
 	await test('Payer must be the order maker', async () => {
 		const symbol = randomString()
-		await TickerToken.createTicker(symbol)
+		await listTicker(symbol)
 		const user = await createUser()
 		const { payload, message, signature } = await oracle.payload(
 			TickerToken.program.programId, user.publicKey, symbol, 10
@@ -40,14 +47,48 @@ test('[TickerToken] Order', async () => {
 		)
 	})
 
+	await test('Oracle quorum', async () => {
+		const symbol = randomString()
+		await listTicker(symbol)
+
+		const oracles = [oracle, new Oracle(web3.Keypair.generate().secretKey), new Oracle(web3.Keypair.generate().secretKey)]
+		await TickerToken.setOracles(oracles.map(o => ({ ed25519: [o.publicKey] })), 2)
+
+		try {
+			const user = await createUser()
+			const { payload, encoded, _paymentToken, ...first } = await oracle.payload(
+				TickerToken.program.programId, user.publicKey, symbol, 10
+			)
+			await _paymentToken.mintTo(user.publicKey, 1e18, user)
+
+			await assert.rejects(
+				TickerToken.connect(user).buy(payload, first),
+				'Single oracle signature must not reach the quorum'
+			)
+			await assert.rejects(
+				TickerToken.connect(user).buy(payload, [first, first]),
+				'Duplicate oracle signatures must not reach the quorum'
+			)
+
+			const second = await oracles[2].sign(MessageKind.OrderPayload, encoded as Uint8Array)
+			await TickerToken.connect(user).buy(payload, [first, second])
+
+			const order = await TickerToken.order(user.publicKey, payload.id)
+			assert.equal(order.id.toString(), payload.id.toString(), 'Order ID mismatch')
+		} finally {
+			await TickerToken.setOracles([{ ed25519: [TickerToken.owner.publicKey] }], 1)
+		}
+	})
+
 	let buyOrderId
+	let buyExecId
 	let orderMaker = await createUser()
 	let token
 
 	const symbol = randomString()
 
 	await test('Buy order creating', async () => {
-		await TickerToken.createTicker(symbol)
+		await listTicker(symbol)
 
 		const { payload, message, signature, _paymentToken } = await oracle.payload(
 			TickerToken.program.programId, orderMaker.publicKey, symbol, 10
@@ -115,14 +156,33 @@ test('[TickerToken] Order', async () => {
 			assert.equal(Object.keys(order.status)[0], 'processing', 'Order status should be processing')
 		})
 
+		await test('Execution report signed by non-broker fails', async () => {
+			const proof = await oracle.cid(buyOrderId)
+			const order = await TickerToken.order(orderMaker.publicKey, buyOrderId)
+			const fake = new Oracle(web3.Keypair.generate().secretKey)
+			const { report, ...signed } = await fake.report(order, { spent: 1n, proofCid: proof })
+
+			await assert.rejects(TickerToken.execute(report, signed), 'Report must be signed by the broker')
+		})
+
 		await test('Executing buy order', async () => {
 			const proof = await oracle.cid(buyOrderId)
 			const order = await TickerToken.order(orderMaker.publicKey, buyOrderId)
 			const spent = BigInt(order.amount) * BigInt(order.price * .8 >> 0)
-			const tx = await TickerToken.execute(
-				orderMaker.publicKey, buyOrderId, spent, Array.from(proof)
+			const malformed = await oracle.report(order, { spent, proofCid: proof.slice(0, -1) })
+			await assert.rejects(
+				TickerToken.execute(malformed.report, malformed),
+				'Malformed proof CID must be rejected'
 			)
 
+			const { report, ...signed } = await oracle.report(order, { spent, proofCid: proof })
+			const tx = await TickerToken.execute(report, signed, { storeProof: true })
+			buyExecId = report.brokerExecId
+
+			const executionProof = await TickerToken.executionProof(report.brokerExecId)
+			assert.equal(Buffer.from(executionProof.cid).toString('hex'), Buffer.from(proof).toString('hex'), 'Stored proof CID mismatch')
+			assert.equal(executionProof.orderId.toString(), order.id.toString(), 'Stored proof order ID mismatch')
+
 			const parser = new EventParser(TickerToken.program.programId, TickerToken.program.coder)
 			const events = await parser.parseLogs(tx.meta.logMessages)
 
@@ -130,16 +190,18 @@ test('[TickerToken] Order', async () => {
 				if (event.name !== 'orderExecuted') continue
 
 				const data = event.data as any
-				assert.equal(Array.from(data.proofCid).toString(), proof.toString(), 'Proof CID mismatch')
-
-				delete data.proofCid
-				delete data.timestamp
-				delete order.status
-				delete order.expiresAt
-
-				//console.log({ data, order })
-
-				assert.deepEqual(data, order, 'Order data mismatch in event')
+				assert.equal(Array.from(data.proofCid).toString(), Array.from(proof).toString(), 'Proof CID mismatch')
+
+				assert.equal(data.id.toString(), order.id.toString(), 'Order ID mismatch in event')
+				assert.equal(data.filled.toString(), order.amount.toString(), 'Filled amount mismatch in event')
+				assert.equal(data.spent.toString(), spent.toString(), 'Spent mismatch in event')
+				assert.equal(data.fee.toString(), order.fee.toString(), 'Fee mismatch in event')
+				assert.equal(
+					BigInt(data.refund), BigInt(order.amount) * BigInt(order.price) - spent,
+					'Refund mismatch in event'
+				)
+				assert.equal(data.fillPrice.toString(), order.price.toString(), 'Fill price mismatch in event')
+				assert.equal(data.executor.toString(), TickerToken.signer.publicKey.toString(), 'Executor mismatch in event')
 			}
 
 			const poolPDA = TickerToken.pda(['pool', order.tickerMint.toBuffer(), order.paymentMint.toBuffer()])
@@ -197,7 +259,8 @@ test('[TickerToken] Order', async () => {
 
 		await test('Execute sell order', async () => {
 			await TickerToken.process(orderMaker.publicKey, sellOrderId)
-			const proof = await oracle.cid(sellOrderId)
+			// текстовый base32 CID, как его видят клиенты
+			const proof = new TextEncoder().encode(CID.decode(await oracle.cid(sellOrderId)).toString())
 			const order = await TickerToken.order(orderMaker.publicKey, sellOrderId)
 
 			const poolPDA = TickerToken.pda(['pool', order.tickerMint.toBuffer(), order.paymentMint.toBuffer()])
@@ -208,24 +271,27 @@ test('[TickerToken] Order', async () => {
 
 			const { amount: poolBalanceBefore } = await getAccount(TickerToken.provider.connection, poolPDA)
 
-			const tx = await TickerToken.execute(
-				orderMaker.publicKey, sellOrderId, spent, Array.from(proof)
+			const reused = await oracle.report(order, { spent, proofCid: proof, brokerExecId: Uint8Array.from(buyExecId) })
+			await assert.rejects(
+				TickerToken.execute(reused.report, reused),
+				'Broker execution ID can only be consumed once'
 			)
 
+			const { report, ...signed } = await oracle.report(order, { spent, proofCid: proof })
+			const tx = await TickerToken.execute(report, signed)
+
 			const parser = new EventParser(TickerToken.program.programId, TickerToken.program.coder)
 			const events = await parser.parseLogs(tx.meta.logMessages)
 
 			for (const event of events) {
 				if (event.name !== 'orderExecuted') continue
 				const data = event.data as any
-				assert.equal(Array.from(data.proofCid).toString(), proof.toString(), 'Proof CID mismatch')
-
-				delete data.proofCid
-				delete data.timestamp
-				delete order.status
-				delete order.expiresAt
+				assert.equal(Array.from(data.proofCid).toString(), Array.from(proof).toString(), 'Proof CID mismatch')
 
-				assert.deepEqual(data, order, 'Order data mismatch in event')
+				assert.equal(data.id.toString(), order.id.toString(), 'Order ID mismatch in event')
+				assert.equal(data.filled.toString(), order.amount.toString(), 'Filled amount mismatch in event')
+				assert.equal(data.spent.toString(), spent.toString(), 'Proceeds mismatch in event')
+				assert.equal(data.refund.toString(), '0', 'Nothing must be refunded for a full sell')
 			}
 
 			const { amount: poolBalanceAfter } = await getAccount(TickerToken.provider.connection, poolPDA)
@@ -248,6 +314,882 @@ test('[TickerToken] Order', async () => {
 		})
 	})
 
+	await test('Notional buy order', async () => {
+		const { payload, message, signature } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 1000, 7, { mode: OrderMode.Notional }
+		)
+		const { amount: makerPaymentBalanceBefore } = await token.account(orderMaker.publicKey)
+		const tickerBalanceBefore = await TickerToken.balance(symbol, orderMaker.publicKey)
+
+		await TickerToken.connect(orderMaker).buy(payload, { message, signature })
+
+		const { amount: makerPaymentBalanceLocked } = await token.account(orderMaker.publicKey)
+		assert.equal(
+			makerPaymentBalanceLocked, makerPaymentBalanceBefore - BigInt(payload.amount) - BigInt(payload.fee),
+			'Notional buy must escrow the payment amount plus fee'
+		)
+
+		const order = await TickerToken.order(orderMaker.publicKey, payload.id)
+		const spent = 900n
+		const shares = spent / 7n
+		const { report, ...signed } = await oracle.report(order, {
+			filled: shares, spent, price: 7n, proofCid: await oracle.cid(payload.id)
+		})
+		await TickerToken.execute(report, signed)
+
+		assert.equal(
+			await TickerToken.balance(symbol, orderMaker.publicKey), tickerBalanceBefore + shares,
+			'Notional buy must mint shares bought at the average fill price'
+		)
+
+		const { amount: makerPaymentBalanceAfter } = await token.account(orderMaker.publicKey)
+		assert.equal(
+			makerPaymentBalanceAfter, makerPaymentBalanceBefore - spent - BigInt(payload.fee),
+			'Unspent payment must be refunded'
+		)
+	})
+
+	await test('Sell order fills partially only with an opt-in', async () => {
+		const { payload, message, signature } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 2, 1
+		)
+		await TickerToken.connect(orderMaker).sell(payload, { message, signature })
+		const order = await TickerToken.order(orderMaker.publicKey, payload.id)
+
+		const partial = await oracle.report(order, { filled: 1n, spent: 1n, proofCid: await oracle.cid(payload.id) })
+		await assert.rejects(TickerToken.execute(partial.report, partial), 'Partial sell without allowPartial must be rejected')
+		await TickerToken.connect(orderMaker).cancel(payload.id)
+
+		const notional = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 3, 1, { mode: OrderMode.Notional }
+		)
+		await TickerToken.connect(orderMaker).sell(notional.payload, notional)
+		const notionalOrder = await TickerToken.order(orderMaker.publicKey, notional.payload.id)
+
+		const short = await oracle.report(notionalOrder, { filled: 2n, spent: 2n, proofCid: await oracle.cid(notional.payload.id) })
+		await assert.rejects(TickerToken.execute(short.report, short), 'Notional sell must raise the target amount')
+		await TickerToken.connect(orderMaker).cancel(notional.payload.id)
+	})
+
+	await test('Sell all order returns unsold shares', async () => {
+		const balanceBefore = await TickerToken.balance(symbol, orderMaker.publicKey)
+		const { payload, message, signature } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 0, 1, { mode: OrderMode.All, allowPartial: true }
+		)
+		await TickerToken.connect(orderMaker).sell(payload, { message, signature })
+
+		assert.equal(
+			await TickerToken.balance(symbol, orderMaker.publicKey), 0n,
+			'Sell all must escrow the whole ticker balance'
+		)
+
+		const order = await TickerToken.order(orderMaker.publicKey, payload.id)
+		assert.equal(BigInt(order.amount), balanceBefore, 'Sell all order amount must equal the maker balance')
+
+		const sold = balanceBefore / 2n
+		const supplyBefore = await TickerToken.supply(symbol)
+		const { report, ...signed } = await oracle.report(order, {
+			filled: sold, spent: sold + BigInt(order.fee), proofCid: await oracle.cid(payload.id)
+		})
+		await TickerToken.execute(report, signed)
+
+		assert.equal(
+			await TickerToken.balance(symbol, orderMaker.publicKey), balanceBefore - sold,
+			'Unsold shares must be returned to the maker'
+		)
+		assert.equal(await TickerToken.supply(symbol), supplyBefore - sold, 'Only sold shares must be burned')
+	})
+
+	await test('Partial fills and terminal records', async () => {
+		await TickerToken.setOrderRetention(2)
+
+		try {
+			const { payload, message, signature } = await oracle.payload(
+				TickerToken.program.programId, orderMaker.publicKey, symbol, 10, 5
+			)
+			await TickerToken.connect(orderMaker).buy(payload, { message, signature })
+			let order = await TickerToken.order(orderMaker.publicKey, payload.id)
+
+			const partial = await oracle.report(order, {
+				filled: 4n, spent: 20n, complete: false, proofCid: await oracle.cid(payload.id)
+			})
+			await TickerToken.execute(partial.report, partial)
+
+			order = await TickerToken.order(orderMaker.publicKey, payload.id)
+			assert.equal(Object.keys(order.status)[0], 'partiallyFilled', 'Order status should be partiallyFilled')
+			assert.equal(order.filled.toString(), '4', 'Cumulative filled mismatch')
+
+			await assert.rejects(
+				TickerToken.connect(orderMaker).cancel(payload.id),
+				'Partially filled order cannot be canceled by the maker'
+			)
+
+			await TickerToken.reject(orderMaker.publicKey, payload.id)
+
+			order = await TickerToken.order(orderMaker.publicKey, payload.id)
+			assert.equal(Object.keys(order.status)[0], 'rejected', 'Rejected order record must be retained')
+			assert.equal(order.retainUntil.toString(), order.closedAt.addn(2).toString(), 'Retention is fixed when the order closes')
+
+			await assert.rejects(
+				TickerToken.connect(orderMaker).cleanup(orderMaker.publicKey, payload.id),
+				'Record cannot be cleaned up before the retention period'
+			)
+		
+			await TickerToken.setOrderRetention(0)
+			await assert.rejects(
+				TickerToken.connect(orderMaker).cleanup(orderMaker.publicKey, payload.id),
+				'Lowering the global retention must not shorten it for closed orders'
+			)
+
+			await new Promise(resolve => setTimeout(resolve, 3000))
+			await TickerToken.connect(orderMaker).cleanup(orderMaker.publicKey, payload.id)
+
+			await assert.rejects(
+				TickerToken.order(orderMaker.publicKey, payload.id),
+				'Order record should not exist after cleanup'
+			)
+		} finally {
+			await TickerToken.setOrderRetention(0)
+		}
+	})
+
+	await test('Stop-loss order triggers on oracle price', async () => {
+		const { payload, message, signature } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 1, undefined,
+			{ trigger: { kind: TriggerType.Stop, price: 5 } }
+		)
+		await TickerToken.connect(orderMaker).sell(payload, { message, signature })
+		let order = await TickerToken.order(orderMaker.publicKey, payload.id)
+
+		const { report, ...signed } = await oracle.report(order, { spent: 10n, proofCid: await oracle.cid(payload.id) })
+		await assert.rejects(TickerToken.execute(report, signed), 'Conditional order cannot be executed before trigger')
+
+		await assert.rejects(
+			TickerToken.process(orderMaker.publicKey, payload.id),
+			'Conditional order requires a price attestation'
+		)
+
+		const above = await oracle.attest(payload.tickerMint, 6)
+		await assert.rejects(
+			TickerToken.process(orderMaker.publicKey, payload.id, { attestation: above.attestation, signatures: above }),
+			'Stop must not trigger above the stop price'
+		)
+
+		const stale = await oracle.attest(payload.tickerMint, 4, { age: 3600 })
+		await assert.rejects(
+			TickerToken.process(orderMaker.publicKey, payload.id, { attestation: stale.attestation, signatures: stale }),
+			'Stale price attestation must be rejected'
+		)
+
+		const below = await oracle.attest(payload.tickerMint, 4)
+		await TickerToken.process(orderMaker.publicKey, payload.id, { attestation: below.attestation, signatures: below })
+
+		order = await TickerToken.order(orderMaker.publicKey, payload.id)
+		assert.equal(Object.keys(order.status)[0], 'processing', 'Triggered order should be processing')
+
+		await TickerToken.execute(report, signed)
+	})
+
+	await test('TWAP order fills follow the schedule', async () => {
+		const now = Math.floor(Date.now() / 1000)
+		const { payload, message, signature } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 10, 5,
+			{ twap: { startAt: now - 10, endAt: now + 3590, slices: 10 } }
+		)
+		await TickerToken.connect(orderMaker).buy(payload, { message, signature })
+		let order = await TickerToken.order(orderMaker.publicKey, payload.id)
+		assert.equal(order.expiresAt.toString(), String(now + 3590), 'TWAP order must live until the schedule end')
+
+		const ahead = await oracle.report(order, {
+			filled: 2n, spent: 10n, complete: false, proofCid: await oracle.cid(payload.id)
+		})
+		await assert.rejects(TickerToken.execute(ahead.report, ahead), 'Fill ahead of the schedule must fail')
+
+		const slice = await oracle.report(order, {
+			filled: 1n, spent: 5n, proofCid: await oracle.cid(payload.id)
+		})
+		await TickerToken.execute(slice.report, slice)
+
+		order = await TickerToken.order(orderMaker.publicKey, payload.id)
+		assert.equal(Object.keys(order.status)[0], 'partiallyFilled', 'TWAP order stays open before the schedule end')
+		assert.equal(order.filled.toString(), '1', 'Cumulative filled mismatch')
+
+		await assert.rejects(
+			TickerToken.expire(orderMaker.publicKey, payload.id),
+			'TWAP order cannot expire before the schedule end'
+		)
+		await TickerToken.reject(orderMaker.publicKey, payload.id)
+	})
+
+	await test('Partially filled order expires with its escrow refunded', async () => {
+		const signed = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 10, 5, { ttl: 2 }
+		)
+		await TickerToken.connect(orderMaker).buy(signed.payload, signed)
+		const order = await TickerToken.order(orderMaker.publicKey, signed.payload.id)
+
+		const partial = await oracle.report(order, {
+			filled: 4n, spent: 20n, complete: false, proofCid: await oracle.cid(signed.payload.id)
+		})
+		await TickerToken.execute(partial.report, partial)
+
+		await new Promise(resolve => setTimeout(resolve, 3000))
+		const late = await oracle.report(order, {
+			filled: 1n, spent: 5n, complete: false, proofCid: await oracle.cid(signed.payload.id)
+		})
+		await assert.rejects(TickerToken.execute(late.report, late), 'Report after expiry must be rejected')
+
+		const { amount: balanceBefore } = await token.account(orderMaker.publicKey)
+		await TickerToken.expire(orderMaker.publicKey, signed.payload.id)
+
+		const { amount: balanceAfter } = await token.account(orderMaker.publicKey)
+		assert.equal(
+			balanceAfter - balanceBefore, BigInt(order.amount) * 5n + BigInt(order.fee) - 20n - BigInt(order.fee),
+			'Unspent escrow of a partially filled order must be refunded on expiry'
+		)
+	})
+
+	await test('Crossing opposing orders', async () => {
+		const buyer = await createUser()
+		await token.mintTo(buyer.publicKey, 1e18, buyer)
+
+		const buyPayload = await oracle.payload(TickerToken.program.programId, buyer.publicKey, symbol, 2, 5)
+		await TickerToken.connect(buyer).buy(buyPayload.payload, buyPayload)
+
+		const buy = { maker: buyer.publicKey, id: buyPayload.payload.id }
+
+		const strictPayload = await oracle.payload(TickerToken.program.programId, orderMaker.publicKey, symbol, 2, 4)
+		await TickerToken.connect(orderMaker).sell(strictPayload.payload, strictPayload)
+		await assert.rejects(
+			TickerToken.cross(buy, { maker: orderMaker.publicKey, id: strictPayload.payload.id }, 1, 5),
+			'Partial cross of a sell without allowPartial must fail'
+		)
+		await TickerToken.connect(orderMaker).cancel(strictPayload.payload.id)
+
+		const sellPayload = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 2, 4, { allowPartial: true }
+		)
+		await TickerToken.connect(orderMaker).sell(sellPayload.payload, sellPayload)
+		const sell = { maker: orderMaker.publicKey, id: sellPayload.payload.id }
+
+		await assert.rejects(TickerToken.cross(buy, sell, 2, 6), 'Price above the buy limit must fail')
+		await assert.rejects(TickerToken.cross(buy, sell, 2, 3), 'Price below the sell limit must fail')
+
+		const supplyBefore = await TickerToken.supply(symbol)
+		const { amount: sellerBefore } = await token.account(orderMaker.publicKey)
+
+		await TickerToken.cross(buy, sell, 1, 5)
+
+		assert.equal(await TickerToken.balance(symbol, buyer.publicKey), 1n, 'Buyer must receive ticker from the sell escrow')
+		assert.equal(await TickerToken.supply(symbol), supplyBefore, 'Crossing must not mint or burn')
+
+		const { amount: sellerAfter } = await token.account(orderMaker.publicKey)
+		assert.equal(
+			sellerAfter - sellerBefore, 5n - BigInt(sellPayload.payload.fee),
+			'Seller must receive the cost minus fee'
+		)
+
+		const buyOrder = await TickerToken.order(buyer.publicKey, buy.id)
+		assert.equal(Object.keys(buyOrder.status)[0], 'partiallyFilled', 'Buy order should be partially filled')
+
+		await TickerToken.cross(buy, sell, 1, 5)
+		await assert.rejects(TickerToken.order(buyer.publicKey, buy.id), 'Filled buy order should be closed')
+		await assert.rejects(TickerToken.order(orderMaker.publicKey, sell.id), 'Filled sell order should be closed')
+	})
+
+	await test('Crossing a market order requires an attested price', async () => {
+		const buyer = await createUser()
+		await token.mintTo(buyer.publicKey, 1e18, buyer)
+
+		const buyPayload = await oracle.payload(TickerToken.program.programId, buyer.publicKey, symbol, 10, undefined, { mode: OrderMode.Notional })
+		await TickerToken.connect(buyer).buy(buyPayload.payload, buyPayload)
+
+		const sellPayload = await oracle.payload(TickerToken.program.programId, orderMaker.publicKey, symbol, 2, 4)
+		await TickerToken.connect(orderMaker).sell(sellPayload.payload, sellPayload)
+
+		const buy = { maker: buyer.publicKey, id: buyPayload.payload.id }
+		const sell = { maker: orderMaker.publicKey, id: sellPayload.payload.id }
+
+		await assert.rejects(TickerToken.cross(buy, sell, 2, 4), 'Market order must not cross without an attestation')
+
+		const attested = await oracle.attest(buyPayload.payload.tickerMint, 4)
+		const attestation = { attestation: attested.attestation, signatures: attested }
+		await assert.rejects(
+			TickerToken.cross(buy, sell, 2, 5, { attestation }),
+			'Market order must cross at the attested price'
+		)
+
+		// 2 по 4 = 8 из 10: остатка 2 не хватает на следующую единицу, ордер на сумму завершается
+		await TickerToken.cross(buy, sell, 2, 4, { attestation })
+		await assert.rejects(TickerToken.order(buyer.publicKey, buy.id), 'Notional buy with a dust remainder should be closed')
+	})
+
+	await test('Crossing orders of the same maker fails', async () => {
+		const buyPayload = await oracle.payload(TickerToken.program.programId, orderMaker.publicKey, symbol, 1, 5)
+		await TickerToken.connect(orderMaker).buy(buyPayload.payload, buyPayload)
+
+		const sellPayload = await oracle.payload(TickerToken.program.programId, orderMaker.publicKey, symbol, 1, 5)
+		await TickerToken.connect(orderMaker).sell(sellPayload.payload, sellPayload)
+
+		await assert.rejects(
+			TickerToken.cross(
+				{ maker: orderMaker.publicKey, id: buyPayload.payload.id },
+				{ maker: orderMaker.publicKey, id: sellPayload.payload.id },
+				1, 5
+			),
+			'Self-cross must be rejected'
+		)
+	})
+
+	await test('Switch order', async () => {
+		const target = randomString()
+		await listTicker(target)
+
+		const fromBefore = await TickerToken.balance(symbol, orderMaker.publicKey)
+		const { amount: paymentBefore } = await token.account(orderMaker.publicKey)
+
+		const { payload, message, signature } = await oracle.switch(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, target, 2,
+			{ topUp: 10, fee: 1, minSellPrice: 5, maxBuyPrice: 4 }
+		)
+		await TickerToken.connect(orderMaker).switch(payload, { message, signature })
+
+		const order = await TickerToken.switchOrder(orderMaker.publicKey, payload.id)
+		assert.equal(order.amount.toString(), '2', 'Switch order amount mismatch')
+
+		// цена покупки B выше подписанной мейкером границы
+		const expensive = await oracle.switchReport(order, {
+			sold: 1n, proceeds: 5n, bought: 3n, cost: 15n, proofCid: await oracle.cid(payload.id)
+		})
+		await assert.rejects(
+			TickerToken.executeSwitch(expensive.report, expensive),
+			'Switch must not buy above max_buy_price'
+		)
+
+		// продано 1 A за 5, куплено 3 B за 12: недостача 12 + 1 - 5 = 8 берётся из доплаты
+		const { report, ...signed } = await oracle.switchReport(order, {
+			sold: 1n, proceeds: 5n, bought: 3n, cost: 12n, proofCid: await oracle.cid(payload.id)
+		})
+		await TickerToken.executeSwitch(report, signed)
+
+		assert.equal(await TickerToken.balance(target, orderMaker.publicKey), 3n, 'Ticker B must be minted')
+		assert.equal(
+			await TickerToken.balance(symbol, orderMaker.publicKey), fromBefore - 1n,
+			'Only sold ticker A must leave the maker'
+		)
+
+		const { amount: paymentAfter } = await token.account(orderMaker.publicKey)
+		assert.equal(paymentBefore - paymentAfter, 8n, 'Only the cash difference must be debited')
+
+		await assert.rejects(
+			TickerToken.switchOrder(orderMaker.publicKey, payload.id),
+			'Switch order should be closed after execution'
+		)
+	})
+
+	await test('Basket order', async () => {
+		const symbols = [randomString(), randomString(), randomString()]
+		for (const ticker of symbols) await listTicker(ticker)
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+
+		const { payload, message, signature } = await oracle.basket(
+			TickerToken.program.programId, user.publicKey,
+			symbols.map(symbol => ({ symbol, amount: 10, price: 5 }))
+		)
+		const { amount: balanceBefore } = await token.account(user.publicKey)
+		await TickerToken.connect(user).basket(payload, { message, signature })
+
+		const escrowed = 3n * 50n + BigInt(payload.fee)
+		const { amount: balanceLocked } = await token.account(user.publicKey)
+		assert.equal(balanceBefore - balanceLocked, escrowed, 'Basket must escrow all legs plus fee once')
+
+		let order = await TickerToken.basketOrder(user.publicKey, payload.id)
+		const first = await oracle.basketReport(order, {
+			fills: [{ leg: 0, filled: 10n, spent: 40n }, { leg: 1, filled: 4n, spent: 20n }],
+			complete: false,
+			proofCid: await oracle.cid(payload.id)
+		})
+		await TickerToken.executeBasket(first.report, first)
+
+		order = await TickerToken.basketOrder(user.publicKey, payload.id)
+		assert.equal(Object.keys(order.status)[0], 'partiallyFilled', 'Basket stays open after partial legs')
+		assert.equal(await TickerToken.balance(symbols[0], user.publicKey), 10n, 'Leg 0 must be minted')
+		assert.equal(await TickerToken.balance(symbols[1], user.publicKey), 4n, 'Leg 1 must be partially minted')
+		await assert.rejects(TickerToken.expireBasket(user.publicKey, payload.id), 'Basket cannot expire before expires_at')
+
+		const last = await oracle.basketReport(order, {
+			fills: [{ leg: 2, filled: 10n, spent: 50n }],
+			proofCid: await oracle.cid(payload.id)
+		})
+		await TickerToken.executeBasket(last.report, last)
+
+		const { amount: balanceAfter } = await token.account(user.publicKey)
+		assert.equal(
+			balanceBefore - balanceAfter, 40n + 20n + 50n + BigInt(payload.fee),
+			'Unused funds must be refunded at completion'
+		)
+		await assert.rejects(TickerToken.basketOrder(user.publicKey, payload.id), 'Basket should be closed')
+	})
+
+	await test('All-or-none basket rejects partial reports', async () => {
+		const symbols = [randomString(), randomString()]
+		for (const ticker of symbols) await listTicker(ticker)
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+
+		const { payload, message, signature } = await oracle.basket(
+			TickerToken.program.programId, user.publicKey,
+			symbols.map(symbol => ({ symbol, amount: 2, price: 5 })),
+			{ allOrNone: true }
+		)
+		await TickerToken.connect(user).basket(payload, { message, signature })
+		const order = await TickerToken.basketOrder(user.publicKey, payload.id)
+
+		const partial = await oracle.basketReport(order, {
+			fills: [{ leg: 0, filled: 2n, spent: 10n }],
+			proofCid: await oracle.cid(payload.id)
+		})
+		await assert.rejects(TickerToken.executeBasket(partial.report, partial), 'Partial all-or-none fill must fail')
+
+		await TickerToken.connect(user).cancelBasket(payload.id)
+		await assert.rejects(TickerToken.basketOrder(user.publicKey, payload.id), 'Canceled basket should be closed')
+	})
+
+	await test('Limit order book', async () => {
+		const { payload: { tickerMint, paymentMint } } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 1
+		)
+		// мейкер без комиссии, тейкер 1%
+		await TickerToken.createBook(tickerMint, paymentMint, 0, 100)
+
+		const seller = TickerToken.connect(orderMaker)
+		const sellerTickerBefore = await TickerToken.balance(symbol, orderMaker.publicKey)
+
+		await TickerToken.configureMarket(tickerMint, paymentMint, 'halted')
+		await assert.rejects(
+			seller.placeBookOrder(tickerMint, paymentMint, 'sell', 5, 2),
+			'Book must not accept orders on a halted market'
+		)
+		await TickerToken.configureMarket(tickerMint, paymentMint, 'open')
+
+		await seller.placeBookOrder(tickerMint, paymentMint, 'sell', 5, 2)
+
+		let book = await TickerToken.book(tickerMint, paymentMint)
+		assert.equal(book.asks.length, 1, 'Ask must rest in the book')
+		assert.equal(
+			await TickerToken.balance(symbol, orderMaker.publicKey), sellerTickerBefore - 2n,
+			'Ask must lock the ticker'
+		)
+
+		const buyer = await createUser()
+		await token.mintTo(buyer.publicKey, 1e18, buyer)
+		const { amount: buyerBefore } = await token.account(buyer.publicKey)
+
+		// bid выше лучшего ask исполняется по цене мейкера
+		await TickerToken.connect(buyer).placeBookOrder(tickerMint, paymentMint, 'buy', 6, 1)
+
+		assert.equal(await TickerToken.balance(symbol, buyer.publicKey), 1n, 'Taker must receive the ticker immediately')
+		const { amount: buyerAfter } = await token.account(buyer.publicKey)
+		assert.equal(buyerBefore - buyerAfter, 6n, 'Taker must pay the maker price plus the rounded up fee')
+
+		book = await TickerToken.book(tickerMint, paymentMint)
+		assert.equal(book.bids.length, 0, 'Filled bid must not rest')
+		assert.equal(book.asks[0].quantity.toString(), '1', 'Ask must be partially filled')
+		assert.equal(book.events.length, 1, 'Maker payout must be queued')
+
+		const { amount: sellerPaymentBefore } = await token.account(orderMaker.publicKey)
+		await TickerToken.consumeBookEvents(tickerMint, paymentMint)
+		const { amount: sellerPaymentAfter } = await token.account(orderMaker.publicKey)
+		assert.equal(sellerPaymentAfter - sellerPaymentBefore, 5n, 'Maker must receive the cost')
+
+		await assert.rejects(
+			TickerToken.connect(buyer).cancelBookOrder(tickerMint, paymentMint, 'sell', book.asks[0].id.toNumber()),
+			'Only the owner can cancel a book order'
+		)
+		await seller.cancelBookOrder(tickerMint, paymentMint, 'sell', book.asks[0].id.toNumber())
+		assert.equal(
+			await TickerToken.balance(symbol, orderMaker.publicKey), sellerTickerBefore - 1n,
+			'Canceled ask must return the unfilled ticker'
+		)
+	})
+
+	await test('Oracle-anchored AMM', async () => {
+		const { payload: { tickerMint, paymentMint } } = await oracle.payload(
+			TickerToken.program.programId, orderMaker.publicKey, symbol, 1
+		)
+		// спред 1%: покупка по 101, продажа по 99 при референсе 100
+		await TickerToken.createAmm(tickerMint, paymentMint, { marketMaker: orderMaker.publicKey, spreadBps: 100, maxAge: 300 })
+
+		const marketMaker = TickerToken.connect(orderMaker)
+		await marketMaker.addLiquidity(tickerMint, paymentMint, 2, 500)
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+		const trader = TickerToken.connect(user)
+
+		await assert.rejects(trader.ammSwap(tickerMint, paymentMint, 'buy', 1, 1000), 'Swap without a reference price must fail')
+
+		const reference = await oracle.attest(tickerMint, 100)
+		await TickerToken.updateAmmReference(reference.attestation, reference)
+		assert.equal((await TickerToken.amm(tickerMint, paymentMint)).referencePrice.toString(), '100')
+
+		await assert.rejects(trader.ammSwap(tickerMint, paymentMint, 'buy', 1, 100), 'Buy above the limit must fail')
+
+		const { amount: before } = await token.account(user.publicKey)
+		await trader.ammSwap(tickerMint, paymentMint, 'buy', 1, 101)
+		const { amount: afterBuy } = await token.account(user.publicKey)
+		assert.equal(before - afterBuy, 101n, 'Buy must pay the reference price plus spread')
+		assert.equal(await TickerToken.balance(symbol, user.publicKey), 1n)
+
+		await trader.ammSwap(tickerMint, paymentMint, 'sell', 1, 99)
+		const { amount: afterSell } = await token.account(user.publicKey)
+		assert.equal(afterSell - afterBuy, 99n, 'Sell must receive the reference price minus spread')
+
+		await assert.rejects(trader.ammSwap(tickerMint, paymentMint, 'buy', 3, 1000), 'Swap above the pool liquidity must fail')
+		await assert.rejects(trader.removeLiquidity(tickerMint, paymentMint, 1, 0), 'Only the market maker can remove liquidity')
+
+		await marketMaker.removeLiquidity(tickerMint, paymentMint, 2, 502)
+	})
+
+	await test('Price feed', async () => {
+		const ticker = randomString()
+		await listTicker(ticker)
+		const tickerMint = TickerToken.pda(['mint', ticker])
+
+		let feed = await TickerToken.priceFeed(tickerMint)
+		assert.equal(feed.publishTime.toString(), '0', 'Feed must be created empty with the ticker')
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+		const trader = TickerToken.connect(user)
+
+		await TickerToken.configurePriceFeed(tickerMint, { maxAge: 300, maxConfBps: 100, maxDeviationBps: 1000 })
+
+		const early = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 1, 10e6)
+		await assert.rejects(trader.buy(early.payload, early), 'Empty feed must not validate orders')
+
+		// 1000 * 10^-2 = 10 USD за тикер, 10e6 в единицах платёжного токена с 6 знаками
+		const wide = await oracle.feed(tickerMint, 1000, { expo: -2, conf: 50 })
+		await TickerToken.updatePriceFeed(wide.update, wide)
+		await assert.rejects(trader.buy(early.payload, early), 'Wide confidence must be rejected')
+
+		const fresh = await oracle.feed(tickerMint, 1000, { expo: -2, conf: 5, age: 0 })
+		await TickerToken.updatePriceFeed(fresh.update, fresh)
+		feed = await TickerToken.priceFeed(tickerMint)
+		assert.equal(feed.price.toString(), '1000')
+		assert.equal(feed.marketOpen, true)
+
+		const stale = await oracle.feed(tickerMint, 900, { expo: -2, age: 30 })
+		await assert.rejects(TickerToken.updatePriceFeed(stale.update, stale), 'Older price must not replace a newer one')
+
+		const other = await createUser()
+		await token.mintTo(other.publicKey, 1e18, other)
+		const far = await oracle.payload(TickerToken.program.programId, other.publicKey, ticker, 1, 20e6)
+		await assert.rejects(
+			TickerToken.connect(other).buy(far.payload, far),
+			'Limit price far from the feed must be rejected'
+		)
+		await trader.buy(early.payload, early)
+	})
+
+	await test('Limit-up/limit-down price band', async () => {
+		const ticker = randomString()
+		await listTicker(ticker)
+		const tickerMint = TickerToken.pda(['mint', ticker])
+
+		await TickerToken.configurePriceFeed(tickerMint, { maxAge: 300, maxConfBps: 100, maxDeviationBps: 0 })
+		// 100 * 10^-6 USD за тикер = 100 базовых единиц платёжного токена с 6 знаками
+		const price = await oracle.feed(tickerMint, 100, { expo: -6, age: 0 })
+		await TickerToken.updatePriceFeed(price.update, price)
+
+		// конфиг и фид создаются вместе с тикером, повторная инициализация для старых тикеров невозможна
+		await assert.rejects(TickerToken.initTickerConfig(ticker), 'Ticker config must not be re-initialized')
+		await assert.rejects(TickerToken.initPriceFeed(ticker), 'Price feed must not be re-initialized')
+
+		await assert.rejects(TickerToken.setPriceBand(tickerMint, 10_001), 'Band above 100% must be rejected')
+
+		// коридор ±10% от 100
+		await TickerToken.setPriceBand(tickerMint, 1000)
+		assert.equal((await TickerToken.tickerConfig(tickerMint)).bandBps, 1000)
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+		const trader = TickerToken.connect(user)
+
+		const fat = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 1, 150)
+		await assert.rejects(trader.buy(fat.payload, fat), 'Quote above the band must be rejected')
+
+		const inBand = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 2, 105)
+		await trader.buy(inBand.payload, inBand)
+		await TickerToken.process(user.publicKey, inBand.payload.id)
+
+		const order = await TickerToken.order(user.publicKey, inBand.payload.id)
+		// заявленная брокером цена в коридоре, но фактическая spent / filled = 80 — нет
+		const outside = await oracle.report(order, { filled: 1n, spent: 80n, price: 100n, complete: false, proofCid: await oracle.cid(inBand.payload.id) })
+		await assert.rejects(
+			TickerToken.execute(outside.report, outside, { priceFeed: true }),
+			'Fill below the band must be rejected'
+		)
+
+		const inside = await oracle.report(order, { filled: 2n, spent: 200n, price: 100n, proofCid: await oracle.cid(inBand.payload.id) })
+		await TickerToken.execute(inside.report, inside, { priceFeed: true })
+	})
+
+	await test('Market calendar', async () => {
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+		const trader = TickerToken.connect(user)
+
+		const now = Math.floor(Date.now() / 1000)
+		const today = Math.floor(now / 86400)
+		const weekday = (today + 3) % 7 // 0 — понедельник
+
+		try {
+			// сегодня выходной
+			await TickerToken.setMarketSession({ weekdays: 0x7f & ~(1 << weekday), openMinute: 0, closeMinute: 1440, utcOffset: 0 })
+
+			const market = await oracle.payload(TickerToken.program.programId, user.publicKey, symbol, 1)
+			await assert.rejects(trader.buy(market.payload, market), 'Market order must be rejected outside the session')
+
+			const basket = await oracle.basket(TickerToken.program.programId, user.publicKey, [{ symbol, amount: 1, price: 10 }], { market: true })
+			await assert.rejects(trader.basket(basket.payload, basket), 'Market basket must be rejected outside the session')
+
+			// стакан не зависит от брокера и торгует вне сессии
+			const { tickerMint, paymentMint } = market.payload
+			await trader.placeBookOrder(tickerMint, paymentMint, 'buy', 1, 1)
+			const bid = (await TickerToken.book(tickerMint, paymentMint)).bids.find(bid => bid.owner.equals(user.publicKey))
+			await trader.cancelBookOrder(tickerMint, paymentMint, 'buy', bid.id.toNumber())
+
+			const day = await oracle.payload(TickerToken.program.programId, user.publicKey, symbol, 1, 10, { timeInForce: TimeInForce.Day })
+			await trader.buy(day.payload, day)
+
+			// DAY-заявка истекает в конце ближайшей сессии (завтра), но не позже payload
+			const order = await TickerToken.order(user.publicKey, day.payload.id)
+			const close = (today + 2) * 86400
+			assert.equal(order.expiresAt.toNumber(), Math.min(close, day.payload.expiresAt.toNumber()), 'DAY order must expire at the session close')
+
+			// торговый день, но праздник
+			await TickerToken.setMarketSession({ weekdays: 0x7f, openMinute: 0, closeMinute: 1440, utcOffset: 0 })
+			await TickerToken.setMarketHolidays([today])
+			const other = await createUser()
+			await token.mintTo(other.publicKey, 1e18, other)
+			const holiday = await oracle.payload(TickerToken.program.programId, other.publicKey, symbol, 1)
+			await assert.rejects(TickerToken.connect(other).buy(holiday.payload, holiday), 'Market order must be rejected on a holiday')
+
+			await assert.rejects(
+				TickerToken.setMarketSession({ weekdays: 0x7f, openMinute: 600, closeMinute: 540, utcOffset: 0 }),
+				'Session must open before it closes'
+			)
+		} finally {
+			await TickerToken.setMarketHolidays([])
+			await TickerToken.setMarketSession({ weekdays: 0x7f, openMinute: 0, closeMinute: 1440, utcOffset: 0 })
+		}
+
+		assert.equal((await TickerToken.calendar()).holidays.length, 0)
+	})
+
+	await test('Order size and price limits', async () => {
+		const ticker = randomString()
+		await listTicker(ticker)
+		const tickerMint = TickerToken.pda(['mint', ticker])
+
+		await assert.rejects(
+			TickerToken.setOrderLimits(tickerMint, { minQuantity: 5, quantityStep: 2 }),
+			'Minimum must be a multiple of the step'
+		)
+		await TickerToken.setOrderLimits(tickerMint, { minQuantity: 4, quantityStep: 2, priceTick: 5, minNotional: 50 })
+
+		const limits = await TickerToken.tickerConfig(tickerMint)
+		assert.equal(limits.minQuantity.toString(), '4')
+		assert.equal(limits.priceTick.toString(), '5')
+
+		const cases : [number, number, string][] = [
+			[2, 25, 'Quantity below the minimum must be rejected'],
+			[5, 25, 'Quantity off the step must be rejected'],
+			[4, 12, 'Price off the tick must be rejected'],
+			[4, 10, 'Notional below the minimum must be rejected']
+		]
+		for (const [amount, price, message] of cases) {
+			const user = await createUser()
+			await token.mintTo(user.publicKey, 1e18, user)
+			const signed = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, amount, price)
+			await assert.rejects(TickerToken.connect(user).buy(signed.payload, signed), message)
+		}
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+		const valid = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 4, 15)
+		await TickerToken.connect(user).buy(valid.payload, valid)
+
+		const smallLeg = await oracle.basket(TickerToken.program.programId, user.publicKey, [{ symbol: ticker, amount: 3, price: 15 }])
+		await assert.rejects(TickerToken.connect(user).basket(smallLeg.payload, smallLeg), 'Basket leg below the minimum must be rejected')
+
+		await assert.rejects(TickerToken.migrateTickerConfig(tickerMint), 'Current config must not be migrated')
+	})
+
+	await test('Market per payment mint', async () => {
+		const ticker = randomString()
+		await TickerToken.createTicker(ticker)
+		const tickerMint = TickerToken.pda(['mint', ticker])
+
+		const user = await createUser()
+		await token.mintTo(user.publicKey, 1e18, user)
+
+		const unlisted = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 10, 100)
+		await assert.rejects(TickerToken.connect(user).buy(unlisted.payload, unlisted), 'Order without an open market must be rejected')
+
+		await TickerToken.openMarket(tickerMint, oracle.paymentMint, { maxQuantity: 50 })
+		const market = await TickerToken.market(tickerMint, oracle.paymentMint)
+		assert.ok('open' in market.status)
+		assert.equal(market.maxQuantity.toString(), '50')
+
+		const large = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 51, 100)
+		await assert.rejects(TickerToken.connect(user).buy(large.payload, large), 'Order above the market limit must be rejected')
+
+		await TickerToken.configureMarket(tickerMint, oracle.paymentMint, 'halted', { maxQuantity: 50 })
+		const halted = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 10, 100)
+		await assert.rejects(TickerToken.connect(user).buy(halted.payload, halted), 'Order on a halted market must be rejected')
+		const haltedBasket = await oracle.basket(TickerToken.program.programId, user.publicKey, [{ symbol: ticker, amount: 10, price: 100 }])
+		await assert.rejects(TickerToken.connect(user).basket(haltedBasket.payload, haltedBasket), 'Basket leg on a halted market must be rejected')
+
+		await TickerToken.configureMarket(tickerMint, oracle.paymentMint, 'open', { maxQuantity: 50 })
+		const valid = await oracle.payload(TickerToken.program.programId, user.publicKey, ticker, 10, 100)
+		await TickerToken.connect(user).buy(valid.payload, valid)
+	})
+
+	await test('Delisting and final settlement', async () => {
+		const ticker = randomString()
+		await listTicker(ticker)
+		const tickerMint = TickerToken.pda(['mint', ticker])
+
+		const holder = await createUser()
+		await token.mintTo(holder.publicKey, 1e18, holder)
+		const signed = await oracle.payload(TickerToken.program.programId, holder.publicKey, ticker, 10, 100)
+		await TickerToken.connect(holder).buy(signed.payload, signed)
+		await TickerToken.process(holder.publicKey, signed.payload.id)
+
+		const order = await TickerToken.order(holder.publicKey, signed.payload.id)
+		const { report, ...executed } = await oracle.report(order, { spent: 1000n, proofCid: await oracle.cid(signed.payload.id) })
+		await TickerToken.execute(report, executed)
+		assert.equal(await TickerToken.balance(ticker, holder.publicKey), 10n)
+
+		// встречные ордера, ожидающие сведения на момент делистинга
+		const buyer = await createUser()
+		await token.mintTo(buyer.publicKey, 1e18, buyer)
+		const bid = await oracle.payload(TickerToken.program.programId, buyer.publicKey, ticker, 1, 100)
+		await TickerToken.connect(buyer).buy(bid.payload, bid)
+		const ask = await oracle.payload(TickerToken.program.programId, holder.publicKey, ticker, 1, 100)
+		await TickerToken.connect(holder).sell(ask.payload, ask)
+
+		await assert.rejects(
+			TickerToken.connect(holder).redeemTicker(tickerMint),
+			'Listed ticker cannot be redeemed'
+		)
+		await assert.rejects(
+			TickerToken.connect(holder).delistTicker(tickerMint, oracle.paymentMint, 50),
+			'Only the authority can delist a ticker'
+		)
+
+		await TickerToken.delistTicker(tickerMint, oracle.paymentMint, 50)
+		const record = await TickerToken.tickerRecord(tickerMint)
+		assert.ok('delisted' in record.status)
+		assert.equal(record.finalPrice.toString(), '50')
+		assert.ok('closed' in (await TickerToken.market(tickerMint, oracle.paymentMint)).status)
+
+		const mint = await getMint(TickerToken.provider.connection, tickerMint)
+		assert.equal(mint.mintAuthority, null, 'Delisted ticker cannot be minted')
+		assert.equal(mint.freezeAuthority, null, 'Holders of a delisted ticker cannot be frozen')
+
+		await assert.rejects(
+			TickerToken.delistTicker(tickerMint, oracle.paymentMint, 50),
+			'Ticker can be delisted only once'
+		)
+		const late = await oracle.payload(TickerToken.program.programId, holder.publicKey, ticker, 10, 100)
+		await assert.rejects(TickerToken.connect(holder).buy(late.payload, late), 'Delisted ticker accepts no orders')
+		await assert.rejects(
+			TickerToken.cross({ maker: buyer.publicKey, id: bid.payload.id }, { maker: holder.publicKey, id: ask.payload.id }, 1, 100),
+			'Delisted ticker cannot be crossed'
+		)
+		await TickerToken.connect(buyer).cancel(bid.payload.id)
+		await TickerToken.connect(holder).cancel(ask.payload.id)
+
+		const { amount: paymentBefore } = await token.account(holder.publicKey)
+		await TickerToken.connect(holder).redeemTicker(tickerMint)
+		const { amount: paymentAfter } = await token.account(holder.publicKey)
+
+		assert.equal(paymentAfter - paymentBefore, 500n, 'Holder is paid the final price')
+		assert.equal(await TickerToken.balance(ticker, holder.publicKey), 0n)
+		assert.equal(await TickerToken.supply(ticker), 0n)
+
+		await assert.rejects(
+			TickerToken.connect(holder).redeemTicker(tickerMint),
+			'Nothing left to redeem'
+		)
+	})
+
+	await test('Recurring buy plan', async () => {
+		const user = await createUser()
+		const { payload, message, signature } = await oracle.plan(
+			TickerToken.program.programId, user.publicKey, symbol, 50, { interval: 3600, periods: 4 }
+		)
+		await token.mintTo(user.publicKey, 1e18, user)
+		await TickerToken.connect(user).createPlan(payload, { message, signature })
+
+		const perPeriod = BigInt(payload.amount) + BigInt(payload.fee)
+		const { delegatedAmount } = await token.account(user.publicKey)
+		assert.equal(delegatedAmount, perPeriod * 4n, 'Allowance must cover all periods')
+
+		// второй план на тот же ATA добавляет своё одобрение, а не заменяет первое
+		const second = await oracle.plan(
+			TickerToken.program.programId, user.publicKey, symbol, 30, { interval: 3600, periods: 2 }
+		)
+		await TickerToken.connect(user).createPlan(second.payload, { message: second.message, signature: second.signature })
+
+		const secondPerPeriod = BigInt(second.payload.amount) + BigInt(second.payload.fee)
+		const { delegatedAmount: sharedAllowance } = await token.account(user.publicKey)
+		assert.equal(sharedAllowance, perPeriod * 4n + secondPerPeriod * 2n, 'Allowances of both plans must add up')
+
+		const { amount: balanceBefore } = await token.account(user.publicKey)
+		await TickerToken.spawnPlanOrder(user.publicKey, payload.id)
+
+		const { amount: balanceAfter } = await token.account(user.publicKey)
+		assert.equal(balanceBefore - balanceAfter, perPeriod, 'One period must be escrowed')
+
+		const orderId = TickerToken.planOrderId(user.publicKey, payload.id, 0)
+		const order = await TickerToken.order(user.publicKey, orderId)
+		assert.equal(Object.keys(order.mode)[0], 'notional', 'Plan order must be notional')
+		assert.equal(order.amount.toString(), payload.amount.toString(), 'Plan order amount mismatch')
+
+		const plan = await TickerToken.plan(user.publicKey, payload.id)
+		assert.equal(plan.allowance.toString(), (perPeriod * 3n).toString(), 'Plan allowance must shrink by one period')
+
+		await assert.rejects(
+			TickerToken.spawnPlanOrder(user.publicKey, payload.id),
+			'Next period has not started yet'
+		)
+		await assert.rejects(
+			TickerToken.connect(orderMaker).pausePlan(payload.id),
+			'Only the maker can pause the plan'
+		)
+
+		await TickerToken.connect(user).pausePlan(payload.id)
+		assert.equal(Object.keys((await TickerToken.plan(user.publicKey, payload.id)).status)[0], 'paused')
+
+		await TickerToken.connect(user).resumePlan(payload.id)
+		await TickerToken.connect(user).cancelPlan(payload.id)
+
+		const { delegatedAmount: remaining } = await token.account(user.publicKey)
+		assert.equal(remaining, secondPerPeriod * 2n, 'Cancel must keep the allowance of the other plan')
+		await assert.rejects(TickerToken.plan(user.publicKey, payload.id), 'Plan should not exist after cancel')
+
+		await TickerToken.connect(user).cancelPlan(second.payload.id)
+		const { delegate } = await token.account(user.publicKey)
+		assert.equal(delegate, null, 'Allowance must be revoked when the last plan is canceled')
+	})
+
 	await test('Cancel order', async () => {
 		await test('Cancel executed order fails', async () => {
 			await assert.rejects(
@@ -66,4 +66,35 @@ test('[TickerTocker] creating ticker', async () => {
 		assert.equal(mint.decimals, decimals, `Mint for ${symbol} should have ${decimals} decimals`)
 		assert.equal(mint.supply.toString(), '0', `Mint for ${symbol} should have zero supply`)
 	})
+
+	await test('ticker is listed in the on-chain index', async () => {
+		const symbol = randomString()
+		const { tickerCount } = await TickerToken.registry
+
+		await TickerToken.createTicker(symbol, 2)
+		const mintPDA = TickerToken.pda(['mint', symbol])
+
+		const registry = await TickerToken.registry
+		assert.equal(registry.tickerCount.toString(), tickerCount.addn(1).toString(), 'Ticker count should grow by one')
+
+		const record = await TickerToken.tickerRecord(mintPDA)
+		assert.equal(record.symbol, symbol, 'Reverse lookup should return the symbol')
+		assert.equal(record.decimals, 2)
+		assert.equal(record.index.toString(), tickerCount.toString())
+
+		const tickers = await TickerToken.listTickers()
+		assert.equal(tickers.length, registry.tickerCount.toNumber(), 'Index should list every ticker')
+		assert.ok(
+			tickers.some(entry => entry.symbol === symbol && entry.mint.equals(mintPDA)),
+			'Index should contain the new ticker'
+		)
+
+		await assert.rejects(
+			TickerToken.createTicker('X'.repeat(33)),
+			'Symbol longer than a seed must be rejected'
+		)
+
+		await assert.rejects(TickerToken.indexTicker(symbol), 'Indexed ticker must not be indexed twice')
+		await assert.rejects(TickerToken.migrateRegistry(), 'Current registry must not be migrated')
+	})
 })